@@ -0,0 +1,64 @@
+// Hound -- A wav encoding and decoding library in Rust
+// Copyright (C) 2015 Ruud van Asseldonk
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// A running CRC-32 checksum, as used by zlib, PNG, and many archive formats.
+///
+/// This is the reflected CRC with polynomial 0xedb88320, an initial value of
+/// all ones, and a final inversion.
+pub struct Crc32 {
+    /// the remainder for every possible byte value
+    table: [u32; 256],
+    /// the checksum so far, before the final inversion
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Creates a checksum of no bytes.
+    pub fn new() -> Crc32 {
+        let mut table = [0u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut x = i as u32;
+            for _ in 0..8 {
+                x = if x & 1 == 1 { 0xedb8_8320 ^ (x >> 1) } else { x >> 1 };
+            }
+            *entry = x;
+        }
+        Crc32 {
+            table: table,
+            crc: 0xffff_ffff,
+        }
+    }
+
+    /// Updates the checksum with a number of bytes.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut crc = self.crc;
+        for &b in bytes {
+            crc = self.table[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.crc = crc;
+    }
+
+    /// Returns the checksum of all bytes so far.
+    pub fn value(&self) -> u32 {
+        !self.crc
+    }
+}
+
+#[test]
+fn crc32_matches_check_value() {
+    // The standard check value for the CRC-32 catalogue entry.
+    let mut crc = Crc32::new();
+    assert_eq!(crc.value(), 0);
+    crc.update(b"12345");
+    crc.update(b"6789");
+    assert_eq!(crc.value(), 0xcbf4_3926);
+}
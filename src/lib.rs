@@ -62,14 +62,19 @@ use std::result;
 use read::ReadExt;
 use write::WriteExt;
 
+mod crc;
 mod read;
 mod write;
 
-pub use read::{WavReader, WavIntoSamples, WavSamples, read_wave_header};
-pub use write::{SampleWriter16, WavWriter};
+pub use read::{WavReader, WavIntoSamples, WavSamples, ChainedSamples, ResyncSamples};
+pub use read::{CoercedSamples, GainSamples, TimedSamples, WavSource};
+pub use read::{MixedSamples, NormalizedSamples, ReadOptions, ReverseSamples, SampleBuffer, SplitFiles};
+pub use read::{Slice, ValidationReport};
+pub use read::{mix, probe, read_wave_header};
+pub use write::{SampleWriter16, StreamingWavWriter, WavEditor, WavWriter, canonical_header};
 
 pub use read::{ Chunk, ChunksReader };
-pub use write::ChunksWriter;
+pub use write::{ChunksWriter, RiffWriter};
 
 /// A type that can be used to represent audio samples.
 ///
@@ -87,6 +92,25 @@ pub trait Sample: Sized {
     /// the written sample out to `byte_width`.
     fn write_padded<W: io::Write>(self, writer: &mut W, bits: u16, byte_width: u16) -> Result<()>;
 
+    /// Writes the audio sample scaled from the full range of `Self` to `bits`.
+    ///
+    /// For integer samples, the value is shifted left or right by the
+    /// difference between the width of `Self` and `bits`, so that full scale
+    /// maps to full scale. Other samples are written as with `write_padded()`,
+    /// which is also the default implementation.
+    fn write_scaled<W: io::Write>(self, writer: &mut W, bits: u16, byte_width: u16) -> Result<()> {
+        self.write_padded(writer, bits, byte_width)
+    }
+
+    /// Returns the number of bytes a sample with `bits` valid bits occupies.
+    ///
+    /// This is the smallest number of whole bytes that holds `bits` bits,
+    /// which is how samples are stored in a standard WAVE file. It can be
+    /// used to size buffers for a given bit depth.
+    fn sample_bytes(bits: u16) -> u16 {
+        (bits + 7) / 8
+    }
+
     /// Reads the audio sample from the WAVE data chunk.
     fn read<R: io::Read>(reader: &mut R, SampleFormat, bytes: u16, bits: u16) -> Result<Self>;
 
@@ -98,6 +122,101 @@ pub trait Sample: Sized {
     fn as_i16(self) -> i16;
 }
 
+/// A sample type that can be amplified, as done by `WavReader::samples_with_gain()`.
+pub trait GainSample: Sample {
+    /// Multiplies the sample by `gain`, saturating at the bounds of `Self`.
+    ///
+    /// The product is computed as `f64`. For integer types it is rounded to
+    /// the nearest integer, and clamped to the range of the type.
+    fn apply_gain(self, gain: f64) -> Self;
+}
+
+macro_rules! impl_int_gain_sample {
+    ($t: ident) => {
+        impl GainSample for $t {
+            fn apply_gain(self, gain: f64) -> $t {
+                let x = (self as f64 * gain).round();
+                if x.is_nan() {
+                    0
+                } else if x >= $t::max_value() as f64 {
+                    $t::max_value()
+                } else if x <= $t::min_value() as f64 {
+                    $t::min_value()
+                } else {
+                    x as $t
+                }
+            }
+        }
+    }
+}
+
+impl_int_gain_sample!(i8);
+impl_int_gain_sample!(i16);
+impl_int_gain_sample!(i32);
+
+impl GainSample for f32 {
+    fn apply_gain(self, gain: f64) -> f32 {
+        let x = self as f64 * gain;
+        if x >= f32::MAX as f64 {
+            f32::MAX
+        } else if x <= f32::MIN as f64 {
+            f32::MIN
+        } else {
+            x as f32
+        }
+    }
+}
+
+#[test]
+fn apply_gain_saturates() {
+    assert_eq!(100_i8.apply_gain(2.0), 127);
+    assert_eq!((-100_i8).apply_gain(2.0), -128);
+    assert_eq!(1000_i16.apply_gain(0.5), 500);
+    assert_eq!(3_i16.apply_gain(0.5), 2);
+    assert_eq!(i32::max_value().apply_gain(1.5), i32::max_value());
+    assert_eq!(0.75_f32.apply_gain(2.0), 1.5);
+    assert_eq!(f32::MAX.apply_gain(2.0), f32::MAX);
+}
+
+/// An integer sample type that samples of any width can be coerced to.
+///
+/// This is used by `WavReader::samples_as()`.
+pub trait CoerceSample: Sample {
+    /// Converts a sample of `bits` bits to `Self`.
+    ///
+    /// If the sample fits, its value is preserved. Otherwise it is shifted
+    /// right to fit in `Self`, which discards the least significant bits.
+    fn coerce_from(x: i32, bits: u16) -> Self;
+}
+
+macro_rules! impl_coerce_sample {
+    ($t: ident, $bits: expr) => {
+        impl CoerceSample for $t {
+            fn coerce_from(x: i32, bits: u16) -> $t {
+                if bits <= $bits {
+                    x as $t
+                } else {
+                    scale_int(x, bits, $bits) as $t
+                }
+            }
+        }
+    }
+}
+
+impl_coerce_sample!(i8, 8);
+impl_coerce_sample!(i16, 16);
+impl_coerce_sample!(i32, 32);
+
+#[test]
+fn coerce_from_narrows_by_shifting() {
+    assert_eq!(i16::coerce_from(0x7f_ffff, 24), 0x7fff);
+    assert_eq!(i16::coerce_from(-0x80_0000, 24), -0x8000);
+    assert_eq!(i16::coerce_from(0x12_34ff, 24), 0x1234);
+    assert_eq!(i16::coerce_from(-3, 8), -3);
+    assert_eq!(i8::coerce_from(-0x8000, 16), -0x80);
+    assert_eq!(i32::coerce_from(-0x80_0000, 24), -0x80_0000);
+}
+
 /// Converts an unsigned integer in the range 0-255 to a signed one in the range -128-127.
 ///
 /// Presumably, the designers of the WAVE format did not like consistency. For
@@ -109,6 +228,18 @@ fn signed_from_u8(x: u8) -> i8 {
     (x as i16 - 128) as i8
 }
 
+/// Returns whether integer samples of this container size and width can be decoded.
+///
+/// `Sample::read` uses this to tell a format that is decoded, but that does
+/// not fit the destination type (`Error::TooWide`), from a format that cannot
+/// be decoded at all (`Error::Unsupported`).
+fn int_decodable(bytes: u16, bits: u16) -> bool {
+    // Apart from the usual container size, 24-bit samples may be stored in
+    // 4-byte containers.
+    SUPPORTED_FORMATS.contains(&(SampleFormat::Int, bits)) &&
+        (bytes == i32::sample_bytes(bits) || (bytes, bits) == (4, 24))
+}
+
 /// Converts a signed integer in the range -128-127 to an unsigned one in the range 0-255.
 fn u8_from_signed(x: i8) -> u8 {
     (x as i16 + 128) as u8
@@ -180,6 +311,26 @@ fn verify_narrow_to_i24() {
     assert!(narrow_to_i24(-8_388_609).is_err());
 }
 
+/// Scales an integer sample from `from_bits` to `to_bits` bits, by shifting.
+#[inline(always)]
+fn scale_int(x: i32, from_bits: u16, to_bits: u16) -> i32 {
+    if to_bits >= from_bits {
+        x << (to_bits - from_bits)
+    } else {
+        // Arithmetic shift, which rounds towards negative infinity.
+        x >> (from_bits - to_bits)
+    }
+}
+
+#[test]
+fn verify_scale_int() {
+    assert_eq!(scale_int(32767, 16, 24), 0x7fff00);
+    assert_eq!(scale_int(-32768, 16, 24), -0x800000);
+    assert_eq!(scale_int(0x7fffff, 24, 16), 32767);
+    assert_eq!(scale_int(-0x800000, 24, 16), -32768);
+    assert_eq!(scale_int(127, 8, 8), 127);
+}
+
 impl Sample for i8 {
     fn write<W: io::Write>(self, writer: &mut W, bits: u16) -> Result<()> {
         self.write_padded(writer, bits, bits / 8)
@@ -196,6 +347,10 @@ impl Sample for i8 {
         }
     }
 
+    fn write_scaled<W: io::Write>(self, writer: &mut W, bits: u16, byte_width: u16) -> Result<()> {
+        scale_int(self as i32, 8, bits).write_padded(writer, bits, byte_width)
+    }
+
     #[inline(always)]
     fn as_i16(self) -> i16 {
         self as i16
@@ -207,8 +362,8 @@ impl Sample for i8 {
         }
         match (bytes, bits) {
             (1, 8) => Ok(try!(reader.read_u8().map(signed_from_u8))),
-            (n, _) if n > 1 => Err(Error::TooWide),
-            // TODO: add a genric decoder for any bit depth.
+            _ if int_decodable(bytes, bits) => Err(Error::TooWide),
+            // TODO: add a generic decoder for any bit depth.
             _ => Err(Error::Unsupported),
         }
     }
@@ -232,6 +387,10 @@ impl Sample for i16 {
         }
     }
 
+    fn write_scaled<W: io::Write>(self, writer: &mut W, bits: u16, byte_width: u16) -> Result<()> {
+        scale_int(self as i32, 16, bits).write_padded(writer, bits, byte_width)
+    }
+
     #[inline(always)]
     fn as_i16(self) -> i16 {
         self
@@ -244,7 +403,7 @@ impl Sample for i16 {
         match (bytes, bits) {
             (1, 8) => Ok(try!(reader.read_u8().map(signed_from_u8).map(|x| x as i16))),
             (2, 16) => Ok(try!(reader.read_le_i16())),
-            (n, _) if n > 2 => Err(Error::TooWide),
+            _ if int_decodable(bytes, bits) => Err(Error::TooWide),
             // TODO: add a generic decoder for any bit depth.
             _ => Err(Error::Unsupported),
         }
@@ -269,6 +428,10 @@ impl Sample for i32 {
         }
     }
 
+    fn write_scaled<W: io::Write>(self, writer: &mut W, bits: u16, byte_width: u16) -> Result<()> {
+        scale_int(self, 32, bits).write_padded(writer, bits, byte_width)
+    }
+
     #[inline(always)]
     fn as_i16(self) -> i16 {
         self as i16
@@ -284,7 +447,6 @@ impl Sample for i32 {
             (3, 24) => Ok(try!(reader.read_le_i24())),
             (4, 24) => Ok(try!(reader.read_le_i24_4())),
             (4, 32) => Ok(try!(reader.read_le_i32())),
-            (n, _) if n > 4 => Err(Error::TooWide),
             // TODO: add a generic decoder for any bit depth.
             _ => Err(Error::Unsupported),
         }
@@ -312,7 +474,6 @@ impl Sample for f32 {
             SampleFormat::Float =>
                 match (bytes, bits) {
                     (4, 32) => Ok(try!(reader.read_le_f32())),
-                    (n, _) if n > 4 => Err(Error::TooWide),
                     _ => Err(Error::Unsupported),
                 },
             SampleFormat::Int =>
@@ -322,7 +483,7 @@ impl Sample for f32 {
                     (1, 8) => Ok(try!(reader.read_u8().map(signed_from_u8).map(|x| x as f32))),
                     (2, 16) => Ok(try!(reader.read_le_i16().map(|x| x as f32))),
                     (3, 24) => Ok(try!(reader.read_le_i24()) as f32),
-                    (n, _) if n > 3 => Err(Error::TooWide),
+                    _ if int_decodable(bytes, bits) => Err(Error::TooWide),
                     // TODO: add a generic decoder for any bit depth.
                     _ => Err(Error::Unsupported),
                 }
@@ -362,6 +523,66 @@ pub struct WavSpec {
     pub sample_format: SampleFormat,
 }
 
+/// A chunk of a WAVE file, with its content as it is stored in the file.
+///
+/// Chunks are collected by a `WavReader` when `ReadOptions::preserve_chunks`
+/// is set, and can be written back with `WavWriter::new_with_chunks()`. The
+/// position of the data chunk among the other chunks is marked by a chunk with
+/// id `data` and no content; its samples are not part of the chunk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawChunk {
+    /// The four-character code that identifies the chunk, such as `fmt `.
+    pub id: [u8; 4],
+
+    /// The content of the chunk, excluding the padding byte.
+    pub data: Vec<u8>,
+}
+
+/// Text metadata, as stored in a `LIST` chunk of type `INFO`.
+///
+/// Every field corresponds to a sub-chunk of the list, fields that are `None`
+/// are absent from the file. The list can contain other sub-chunks too, such
+/// as `ICRD` for the creation date; those are ignored when reading, but they
+/// are kept with `ReadOptions::preserve_chunks`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// The title, from the `INAM` sub-chunk.
+    pub title: Option<String>,
+
+    /// The artist, from the `IART` sub-chunk.
+    pub artist: Option<String>,
+
+    /// A comment, from the `ICMT` sub-chunk.
+    pub comment: Option<String>,
+}
+
+impl Metadata {
+    /// Returns the `LIST` chunk of type `INFO` that holds the fields that are set.
+    ///
+    /// Every string is stored null-terminated, and padded to an even length.
+    /// This is the chunk that `WavWriter::set_metadata()` writes. It can also
+    /// be passed to `WavWriter::new_with_chunks()`, to place it among other
+    /// chunks before the data chunk.
+    pub fn to_chunk(&self) -> RawChunk {
+        let mut data = b"INFO".to_vec();
+        let fields = [(b"INAM", &self.title), (b"IART", &self.artist), (b"ICMT", &self.comment)];
+        for &(id, field) in &fields {
+            if let Some(ref text) = *field {
+                // The size includes the null terminator, not the padding.
+                let len = text.len() as u32 + 1;
+                data.extend_from_slice(id);
+                data.write_le_u32(len).unwrap();
+                data.extend_from_slice(text.as_bytes());
+                data.push(0);
+                if len % 2 == 1 {
+                    data.push(0);
+                }
+            }
+        }
+        RawChunk { id: *b"LIST", data: data }
+    }
+}
+
 /// The error type for operations on `WavReader` and `WavWriter`.
 #[derive(Debug)]
 pub enum Error {
@@ -377,7 +598,27 @@ pub enum Error {
     /// because it requires more bits than the bits per sample specified.
     TooWide,
     /// The number of samples written is not a multiple of the number of channels.
-    UnfinishedSample,
+    ///
+    /// When reading, this means that the data ends in the middle of a frame.
+    /// `samples` is the number of samples that was written or read, and
+    /// `channels` is the number of channels that it should be a multiple of.
+    UnfinishedSample {
+        /// The number of samples.
+        samples: u64,
+        /// The number of channels.
+        channels: u16,
+    },
+    /// A frame or a set of planar channels does not have one entry per channel.
+    ///
+    /// This is returned by `WavWriter::write_frame()` and
+    /// `WavWriter::write_planar()`, for instance when a stereo frame is passed
+    /// to a mono writer.
+    ChannelCountMismatch {
+        /// The number of channels of the spec.
+        expected: u16,
+        /// The number of samples in the frame, or the number of channels passed.
+        actual: usize,
+    },
     /// The format is not supported.
     Unsupported,
     /// The sample format is different than the destination format.
@@ -390,6 +631,26 @@ pub enum Error {
     /// samples (which have a `SampleFormat::Int`) from a wav file that
     /// contains floating point data (`SampleFormat::Float`).
     InvalidSampleFormat,
+    /// The specs of two streams that were to be combined are not compatible.
+    ///
+    /// See `WavSpec::compatible_with()` for which specs are compatible. This
+    /// is also returned by `WavWriter::new_with_chunks()` when the `fmt `
+    /// chunk passed to it does not describe the spec.
+    IncompatibleSpec,
+    /// The stream ended before the end of the data chunk.
+    ///
+    /// This means that the file was cut off, for example because a download
+    /// did not complete, while the data that was present was well-formed.
+    Truncated,
+    /// The file declares a number of bits per sample that is not supported.
+    ///
+    /// For integer samples, 8, 12, 16, 20, 24 and 32 bits per sample are
+    /// accepted, for float samples 32 and 64 bits; however, not all of these
+    /// can be decoded (see `Error::Unsupported`). The value is the number of
+    /// bits per sample that the file declares, and it is included in the
+    /// message. `Error::Unsupported` itself carries no value, and adding one
+    /// would break code that matches on it, hence the separate variant.
+    UnsupportedBitsPerSample(u16),
 }
 
 impl fmt::Display for Error {
@@ -403,9 +664,15 @@ impl fmt::Display for Error {
             Error::TooWide => {
                 formatter.write_str("The sample has more bits than the destination type.")
             }
-            Error::UnfinishedSample => {
-                formatter.write_str(
-                    "The number of samples written is not a multiple of the number of channels.")
+            Error::UnfinishedSample { samples, channels } => {
+                write!(formatter,
+                       "The number of samples ({}) is not a multiple of the number of channels ({}).",
+                       samples, channels)
+            }
+            Error::ChannelCountMismatch { expected, actual } => {
+                write!(formatter,
+                       "Wrong number of channels: expected {}, got {}.",
+                       expected, actual)
             }
             Error::Unsupported => {
                 formatter.write_str("The wave format of the file is not supported.")
@@ -413,6 +680,15 @@ impl fmt::Display for Error {
             Error::InvalidSampleFormat => {
                 formatter.write_str("The sample format differs from the destination format.")
             }
+            Error::IncompatibleSpec => {
+                formatter.write_str("The specs of the wav streams are not compatible.")
+            }
+            Error::Truncated => {
+                formatter.write_str("The stream ended before the end of the data chunk.")
+            }
+            Error::UnsupportedBitsPerSample(bits) => {
+                write!(formatter, "Unsupported number of bits per sample: {}.", bits)
+            }
         }
     }
 }
@@ -423,9 +699,13 @@ impl error::Error for Error {
             Error::IoError(ref err) => err.description(),
             Error::FormatError(reason) => reason,
             Error::TooWide => "the sample has more bits than the destination type",
-            Error::UnfinishedSample => "the number of samples written is not a multiple of the number of channels",
+            Error::UnfinishedSample { .. } => "the number of samples written is not a multiple of the number of channels",
+            Error::ChannelCountMismatch { .. } => "the number of channels does not match the spec",
             Error::Unsupported => "the wave format of the file is not supported",
             Error::InvalidSampleFormat => "the sample format differs from the destination format",
+            Error::IncompatibleSpec => "the specs of the wav streams are not compatible",
+            Error::Truncated => "the stream ended before the end of the data chunk",
+            Error::UnsupportedBitsPerSample(_) => "the number of bits per sample is not supported",
         }
     }
 
@@ -434,13 +714,88 @@ impl error::Error for Error {
             Error::IoError(ref err) => Some(err),
             Error::FormatError(_) => None,
             Error::TooWide => None,
-            Error::UnfinishedSample => None,
+            Error::UnfinishedSample { .. } => None,
+            Error::ChannelCountMismatch { .. } => None,
             Error::Unsupported => None,
             Error::InvalidSampleFormat => None,
+            Error::IncompatibleSpec => None,
+            Error::Truncated => None,
+            Error::UnsupportedBitsPerSample(_) => None,
         }
     }
 }
 
+/// Clones the error.
+///
+/// An `io::Error` cannot be cloned, so the clone of an `Error::IoError` is a
+/// new `io::Error` with the same kind and message, which does not retain the
+/// original error, for instance its OS error code.
+impl Clone for Error {
+    fn clone(&self) -> Error {
+        match *self {
+            Error::IoError(ref err) => Error::IoError(io::Error::new(err.kind(), err.to_string())),
+            Error::FormatError(reason) => Error::FormatError(reason),
+            Error::TooWide => Error::TooWide,
+            Error::UnfinishedSample { samples, channels } => {
+                Error::UnfinishedSample { samples: samples, channels: channels }
+            }
+            Error::ChannelCountMismatch { expected, actual } => {
+                Error::ChannelCountMismatch { expected: expected, actual: actual }
+            }
+            Error::Unsupported => Error::Unsupported,
+            Error::InvalidSampleFormat => Error::InvalidSampleFormat,
+            Error::IncompatibleSpec => Error::IncompatibleSpec,
+            Error::Truncated => Error::Truncated,
+            Error::UnsupportedBitsPerSample(bits) => Error::UnsupportedBitsPerSample(bits),
+        }
+    }
+}
+
+/// Compares errors by variant and value.
+///
+/// Two `Error::IoError`s are equal when their `io::ErrorKind` is equal.
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (&Error::IoError(ref a), &Error::IoError(ref b)) => a.kind() == b.kind(),
+            (&Error::FormatError(a), &Error::FormatError(b)) => a == b,
+            (&Error::TooWide, &Error::TooWide) => true,
+            (&Error::UnfinishedSample { samples: a, channels: c },
+             &Error::UnfinishedSample { samples: b, channels: d }) => a == b && c == d,
+            (&Error::ChannelCountMismatch { expected: a, actual: c },
+             &Error::ChannelCountMismatch { expected: b, actual: d }) => a == b && c == d,
+            (&Error::Unsupported, &Error::Unsupported) => true,
+            (&Error::InvalidSampleFormat, &Error::InvalidSampleFormat) => true,
+            (&Error::IncompatibleSpec, &Error::IncompatibleSpec) => true,
+            (&Error::Truncated, &Error::Truncated) => true,
+            (&Error::UnsupportedBitsPerSample(a), &Error::UnsupportedBitsPerSample(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[test]
+fn errors_can_be_compared_and_cloned() {
+    assert_eq!(Error::TooWide, Error::TooWide);
+    assert_eq!(Error::Unsupported, Error::Unsupported);
+    assert!(Error::TooWide != Error::Unsupported);
+    assert_eq!(Error::FormatError("no riff tag found"), Error::FormatError("no riff tag found"));
+    assert!(Error::FormatError("no riff tag found") != Error::FormatError("no wave tag found"));
+    assert!(Error::UnfinishedSample { samples: 3, channels: 2 } !=
+            Error::UnfinishedSample { samples: 5, channels: 2 });
+
+    let eof = Error::IoError(io::Error::new(io::ErrorKind::UnexpectedEof, "eof"));
+    let other_eof = Error::IoError(io::Error::new(io::ErrorKind::UnexpectedEof, "other"));
+    assert_eq!(eof, other_eof);
+    assert!(eof != Error::IoError(io::Error::new(io::ErrorKind::Other, "eof")));
+    assert!(eof != Error::Truncated);
+
+    let clone = eof.clone();
+    assert_eq!(clone, eof);
+    assert_eq!(clone.to_string(), "eof");
+    assert_eq!(Error::UnsupportedBitsPerSample(12).clone(), Error::UnsupportedBitsPerSample(12));
+}
+
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Error {
         Error::IoError(err)
@@ -467,8 +822,89 @@ const KSDATAFORMAT_SUBTYPE_PCM: [u8; 16] = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
 const KSDATAFORMAT_SUBTYPE_IEEE_FLOAT: [u8; 16] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00,
                                                    0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71];
 
+/// The combinations of sample format and bits per sample that hound can decode and encode.
+const SUPPORTED_FORMATS: &'static [(SampleFormat, u16)] = &[
+    (SampleFormat::Int, 8),
+    (SampleFormat::Int, 16),
+    (SampleFormat::Int, 24),
+    (SampleFormat::Int, 32),
+    (SampleFormat::Float, 32),
+];
+
+/// The combinations that `WavReader` accepts in a header, but cannot decode.
+///
+/// Files in these formats can be opened to read the data with
+/// `WavReader::raw_data()`; any other combination is rejected with
+/// `Error::UnsupportedBitsPerSample`.
+const RAW_ONLY_READ_FORMATS: &'static [(SampleFormat, u16)] = &[
+    (SampleFormat::Int, 12),
+    (SampleFormat::Int, 20),
+    (SampleFormat::Float, 64),
+];
+
+/// Returns the pairs of sample format and bits per sample that `WavReader` can decode.
+///
+/// Files in other formats may still be opened, for instance to read the data
+/// with `WavReader::raw_data()`, but iterating their samples fails with
+/// `Error::Unsupported`.
+pub fn supported_read_formats() -> &'static [(SampleFormat, u16)] {
+    SUPPORTED_FORMATS
+}
+
+/// Returns the pairs of sample format and bits per sample that `WavWriter` can encode.
+///
+/// For other formats, creating the writer fails with `Error::Unsupported`.
+pub fn supported_write_formats() -> &'static [(SampleFormat, u16)] {
+    SUPPORTED_FORMATS
+}
 
 impl WavSpec {
+    /// Returns the spec for integer PCM samples, the most common format.
+    ///
+    /// This is a shorthand for a `WavSpec` with `SampleFormat::Int`.
+    pub fn new_pcm(channels: u16, sample_rate: u32, bits_per_sample: u16) -> WavSpec {
+        WavSpec {
+            channels: channels,
+            sample_rate: sample_rate,
+            bits_per_sample: bits_per_sample,
+            sample_format: SampleFormat::Int,
+        }
+    }
+
+    /// Checks that hound can write a file with this spec.
+    ///
+    /// The number of channels and the sample rate must be nonzero, otherwise
+    /// `Error::FormatError` is returned. The sample format and bits per sample
+    /// must be one of `supported_write_formats()`, otherwise
+    /// `Error::Unsupported` is returned. The `WavWriter` constructors call
+    /// this before they write anything.
+    pub fn validate(&self) -> Result<()> {
+        if self.channels == 0 {
+            return Err(Error::FormatError("spec contains zero channels"));
+        }
+        if self.sample_rate == 0 {
+            return Err(Error::FormatError("sample rate is zero"));
+        }
+        if !SUPPORTED_FORMATS.contains(&(self.sample_format, self.bits_per_sample)) {
+            return Err(Error::Unsupported);
+        }
+        Ok(())
+    }
+
+    /// Returns whether samples described by `other` can be mixed with samples described by `self`.
+    ///
+    /// Two specs are compatible when their sample streams can be concatenated
+    /// or combined sample by sample without conversion: they must have the same
+    /// number of channels, sample rate, bits per sample, and sample format.
+    /// How the samples are stored in the file (for instance, 24-bit samples in
+    /// a 3-byte or 4-byte container) does not affect compatibility.
+    pub fn compatible_with(&self, other: &WavSpec) -> bool {
+        self.channels == other.channels &&
+        self.sample_rate == other.sample_rate &&
+        self.bits_per_sample == other.bits_per_sample &&
+        self.sample_format == other.sample_format
+    }
+
     /// Get "stand-alone" wav header representing infinite or unknown size wav file.
     /// Use this if you need to write audio data to non-seekable sinks (like stdout).
     ///
@@ -505,12 +941,9 @@ impl WavSpec {
     /// }
     /// ```
     pub fn into_header_for_infinite_file(self) -> Vec<u8> {
-        let mut c = std::io::Cursor::new(Vec::with_capacity(0x44));
-        {
-            let w = WavWriter::new(&mut c, self);
-            drop(w);
-        }
-        let mut v = c.into_inner();
+        // The header of an empty file, without the space that `WavWriter`
+        // reserves for RF64, so the sizes are at fixed offsets.
+        let mut v = canonical_header(&self, 0).unwrap_or_default();
 
         // Set WAVE chunk size to a special signal value
         v[4] = 0xFF; v[5] = 0xFF; v[6] = 0xFF; v[7] = 0xFF;
@@ -559,6 +992,20 @@ fn write_read_i16_is_lossless() {
     }
 }
 
+#[test]
+fn new_pcm_reads_back_as_int() {
+    let spec = WavSpec::new_pcm(2, 22050, 16);
+    assert_eq!(spec.sample_format, SampleFormat::Int);
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(3_i16).unwrap();
+    writer.write_sample(-3_i16).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+
+    let reader = WavReader::new(io::Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.format_tag(), 1);
+    assert_eq!(reader.spec(), spec);
+}
+
 #[test]
 fn write_read_i16_via_sample_writer_is_lossless() {
     let mut buffer = io::Cursor::new(Vec::new());
@@ -667,6 +1114,113 @@ fn write_read_i24_is_lossless() {
         }
     }
 }
+
+#[test]
+fn write_read_i24_stereo_is_lossless() {
+    let mut buffer = io::Cursor::new(Vec::new());
+    let write_spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+
+    // Left and right use the extremes of the 24-bit range with both signs.
+    let expected: Vec<i32> = (-128_i32..128)
+        .flat_map(|x| vec![x * 65536 + 0x1234, -x * 65535 - 1])
+        .collect();
+    {
+        let mut writer = WavWriter::new(&mut buffer, write_spec).unwrap();
+        for &s in &expected {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    buffer.set_position(0);
+    {
+        let mut reader = WavReader::new(&mut buffer).unwrap();
+        assert_eq!(write_spec, reader.spec());
+        let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, expected);
+    }
+
+    // A 24-bit sample does not fit in an `i16`.
+    buffer.set_position(0);
+    let mut reader = WavReader::new(&mut buffer).unwrap();
+    match reader.samples::<i16>().next() {
+        Some(Err(Error::TooWide)) => {}
+        _ => panic!("expected Error::TooWide"),
+    }
+}
+
+#[test]
+fn write_read_i24_mono_extends_negative_extremes() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    // 0xff8000 as a 24-bit value is -0x8000, it has the sign bit set.
+    let expected = [-8_388_608_i32, -8_388_607, -0x8000, -1, 0, 1, 8_388_606, 8_388_607];
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for &s in &expected {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+
+    let mut reader = WavReader::new(io::Cursor::new(&bytes[..])).unwrap();
+    let start = reader.data_chunk_range().start as usize;
+    assert_eq!(&bytes[start + 6..start + 9], &[0x00, 0x80, 0xff]);
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, expected);
+}
+
+#[test]
+fn read_distinguishes_too_wide_from_unsupported() {
+    let bytes = [0u8; 8];
+    let int = SampleFormat::Int;
+
+    // Formats that are decoded, but do not fit the destination type.
+    match i8::read(&mut &bytes[..], int, 2, 16) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide for i8 from 16 bits"),
+    }
+    match i16::read(&mut &bytes[..], int, 3, 24) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide for i16 from 24 bits"),
+    }
+    match f32::read(&mut &bytes[..], int, 4, 32) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide for f32 from 32 bits"),
+    }
+    match f32::read(&mut &bytes[..], int, 4, 24) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide for f32 from 24 bits in 4 bytes"),
+    }
+
+    // Formats that cannot be decoded into any type.
+    for &(bytes_per_sample, bits) in &[(2, 12), (3, 20), (8, 64)] {
+        match i8::read(&mut &bytes[..], int, bytes_per_sample, bits) {
+            Err(Error::Unsupported) => {}
+            _ => panic!("expected Error::Unsupported for i8 from {} bits", bits),
+        }
+        match i16::read(&mut &bytes[..], int, bytes_per_sample, bits) {
+            Err(Error::Unsupported) => {}
+            _ => panic!("expected Error::Unsupported for i16 from {} bits", bits),
+        }
+        match i32::read(&mut &bytes[..], int, bytes_per_sample, bits) {
+            Err(Error::Unsupported) => {}
+            _ => panic!("expected Error::Unsupported for i32 from {} bits", bits),
+        }
+    }
+    match f32::read(&mut &bytes[..], SampleFormat::Float, 8, 64) {
+        Err(Error::Unsupported) => {}
+        _ => panic!("expected Error::Unsupported for 64-bit float"),
+    }
+}
+
 #[test]
 fn write_read_f32_is_lossless() {
     let mut buffer = io::Cursor::new(Vec::new());
@@ -698,6 +1252,62 @@ fn write_read_f32_is_lossless() {
     }
 }
 
+#[test]
+fn write_read_f32_ramp_is_lossless() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let ramp: Vec<f32> = (-100..101).map(|x| x as f32 / 100.0).collect();
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for &s in &ramp {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+
+    let mut reader = WavReader::new(io::Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.spec(), spec);
+    let samples: Vec<f32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, ramp);
+
+    // Float samples are not reinterpreted as integers.
+    let mut reader = WavReader::new(io::Cursor::new(&bytes[..])).unwrap();
+    match reader.samples::<i16>().next() {
+        Some(Err(Error::InvalidSampleFormat)) => {}
+        _ => panic!("expected Error::InvalidSampleFormat"),
+    }
+}
+
+#[test]
+fn validate_rejects_invalid_specs() {
+    let valid = WavSpec::new_pcm(2, 44100, 16);
+    assert!(valid.validate().is_ok());
+
+    let zero_channels = WavSpec { channels: 0, ..valid };
+    let zero_rate = WavSpec { sample_rate: 0, ..valid };
+    for spec in &[zero_channels, zero_rate] {
+        match WavWriter::new(io::Cursor::new(Vec::new()), *spec) {
+            Err(Error::FormatError(..)) => {}
+            _ => panic!("expected Error::FormatError for {:?}", spec),
+        }
+    }
+
+    let zero_bits = WavSpec { bits_per_sample: 0, ..valid };
+    let odd_bits = WavSpec { bits_per_sample: 12, ..valid };
+    let float_bits = WavSpec { sample_format: SampleFormat::Float, ..valid };
+    for spec in &[zero_bits, odd_bits, float_bits] {
+        let mut buffer = io::Cursor::new(Vec::new());
+        match WavWriter::new(&mut buffer, *spec) {
+            Err(Error::Unsupported) => {}
+            _ => panic!("expected Error::Unsupported for {:?}", spec),
+        }
+        // Nothing is written for an invalid spec.
+        assert_eq!(buffer.position(), 0);
+    }
+}
+
 #[test]
 #[should_panic]
 fn no_32_bps_for_float_sample_format_panics() {
@@ -755,6 +1365,23 @@ fn flush_should_produce_valid_file() {
     assert_eq!(&read_samples[..], &samples[..]);
 }
 
+#[test]
+fn flush_repeatedly_then_finalize_keeps_all_samples() {
+    let spec = WavSpec::new_pcm(1, 44100, 16);
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(2_i16).unwrap();
+    writer.flush().unwrap();
+    writer.flush().unwrap();
+    writer.write_sample(3_i16).unwrap();
+    writer.flush().unwrap();
+    writer.write_sample(5_i16).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|r| r.unwrap()).collect();
+    assert_eq!(&samples[..], &[2, 3, 5]);
+}
+
 #[test]
 fn new_append_should_append() {
     use std::io::Seek;
@@ -794,6 +1421,59 @@ fn new_append_should_append() {
     assert_eq!(&read_samples[..], &[2, 5, 7, 11, 2, 5, 7, 11]);
 }
 
+#[test]
+fn new_append_patches_sizes_and_keeps_spec() {
+    use std::io::Seek;
+
+    let spec = WavSpec::new_pcm(1, 44100, 16);
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for s in &[3_i16, 5, 7] { writer.write_sample(*s).unwrap(); }
+    let mut buffer = io::Cursor::new(writer.finalize_into_bytes().unwrap());
+
+    {
+        let mut writer = WavWriter::new_append(&mut buffer).unwrap();
+        assert_eq!(writer.len(), 3);
+        writer.write_sample(11_i16).unwrap();
+        writer.write_sample(13_i16).unwrap();
+        // New samples must fit the spec of the existing file.
+        match writer.write_sample(40_000_i32) {
+            Err(Error::TooWide) => {}
+            _ => panic!("a sample wider than the existing file should be rejected"),
+        }
+        match writer.write_sample(0.5_f32) {
+            Err(Error::Unsupported) => {}
+            _ => panic!("a float sample should not be appended to a 16-bit file"),
+        }
+        writer.finalize().unwrap();
+    }
+
+    // Both the RIFF size and the data size cover old and new samples.
+    let bytes = buffer.into_inner();
+    assert_eq!(bytes.len(), 80 + 10);
+    assert_eq!(&bytes[4..8], &[82, 0, 0, 0]);
+    assert_eq!(&bytes[76..80], &[10, 0, 0, 0]);
+
+    let mut cursor = io::Cursor::new(bytes);
+    {
+        let mut reader = WavReader::new(&mut cursor).unwrap();
+        assert_eq!(reader.spec(), spec);
+        let samples: Vec<i16> = reader.samples().map(|r| r.unwrap()).collect();
+        assert_eq!(&samples[..], &[3, 5, 7, 11, 13]);
+    }
+
+    // Appending a second time continues from the combined length.
+    cursor.seek(io::SeekFrom::Start(0)).unwrap();
+    {
+        let mut writer = WavWriter::new_append(&mut cursor).unwrap();
+        assert_eq!(writer.len(), 5);
+        writer.write_sample(17_i16).unwrap();
+    }
+    cursor.seek(io::SeekFrom::Start(0)).unwrap();
+    let mut reader = WavReader::new(cursor).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|r| r.unwrap()).collect();
+    assert_eq!(&samples[..], &[3, 5, 7, 11, 13, 17]);
+}
+
 #[test]
 fn new_append_does_not_corrupt_files() {
     use std::io::Read;
@@ -897,6 +1577,35 @@ fn append_works_on_files() {
     assert_contents("append.wav", &[11, 13, 17, 19, 23]);
 }
 
+#[test]
+fn buffer_size_can_be_configured() {
+    use std::fs;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    // A unique path in the temp dir, so parallel runs do not collide.
+    let path = std::env::temp_dir().join(format!("hound-buffered-{}.wav", std::process::id()));
+
+    // Even a buffer smaller than a sample works, it only costs more calls.
+    for &capacity in &[1, 512 * 1024] {
+        let mut writer = WavWriter::create_with_buffer_size(&path, spec, capacity).unwrap();
+        writer.write_sample(11_i16).unwrap();
+        writer.write_sample(-13_i16).unwrap();
+        writer.finalize().unwrap();
+
+        let mut reader = WavReader::open_with_buffer_size(&path, capacity).unwrap();
+        let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+        assert_eq!(&samples[..], &[11, -13]);
+    }
+
+    fs::remove_file(&path).unwrap();
+}
+
 #[cfg(test)]
 macro_rules! guard {
     ($pat:pat = $expr:expr => $block:block) => {
@@ -1011,3 +1720,54 @@ fmt \x28\x00\x00\x00\xfe\xff\x0a\x00\x80\x3e\x00\x00\x00\xe2\x04\x00\
 \x00\x00\x10\x00\x80\x00\x00\xaa\x00\x38\x9b\x71\
 data\xFF\xFF\xFF\xFF"[..]);
 }
+
+#[test]
+fn sample_bytes_rounds_up_to_whole_bytes() {
+    assert_eq!(i8::sample_bytes(8), 1);
+    assert_eq!(i16::sample_bytes(12), 2);
+    assert_eq!(i16::sample_bytes(16), 2);
+    assert_eq!(i32::sample_bytes(20), 3);
+    assert_eq!(i32::sample_bytes(24), 3);
+    assert_eq!(f32::sample_bytes(32), 4);
+}
+
+#[test]
+fn supported_formats_can_be_written_and_read() {
+    use std::io::Cursor;
+
+    for &(sample_format, bits_per_sample) in supported_write_formats() {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: bits_per_sample,
+            sample_format: sample_format,
+        };
+        let mut writer = WavWriter::new_in_memory(spec).unwrap();
+        match sample_format {
+            SampleFormat::Int => writer.write_sample(-1_i32).unwrap(),
+            SampleFormat::Float => writer.write_sample(-1.0_f32).unwrap(),
+        }
+        let bytes = writer.finalize_into_bytes().unwrap();
+
+        assert!(supported_read_formats().contains(&(sample_format, bits_per_sample)));
+        let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec(), spec);
+        match sample_format {
+            SampleFormat::Int => assert_eq!(reader.samples::<i32>().next().unwrap().unwrap(), -1),
+            SampleFormat::Float => assert_eq!(reader.samples::<f32>().next().unwrap().unwrap(), -1.0),
+        }
+    }
+
+    // Formats that are not listed, are indeed not supported.
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 12,
+        sample_format: SampleFormat::Int,
+    };
+    assert!(!supported_write_formats().contains(&(SampleFormat::Int, 12)));
+    match WavWriter::new_in_memory(spec) {
+        Err(Error::Unsupported) => {}
+        _ => panic!("expected Error::Unsupported"),
+    }
+}
@@ -13,10 +13,16 @@
 use std::cmp;
 use std::fs;
 use std::io;
+use std::iter;
 use std::marker;
 use std::mem;
+use std::ops;
 use std::path;
-use super::{Error, Result, Sample, SampleFormat, WavSpec};
+use std::slice;
+use super::{CoerceSample, Error, GainSample, Metadata, RawChunk, Result, Sample, SampleFormat, WavSpec};
+use super::{scale_int, signed_from_u8, RAW_ONLY_READ_FORMATS, SUPPORTED_FORMATS};
+use crc::Crc32;
+use write::WriteExt;
 
 /// Extends the functionality of `io::Read` with additional methods.
 ///
@@ -68,6 +74,9 @@ pub trait ReadExt: io::Read {
     /// Reads four bytes and interprets them as a little-endian 32-bit unsigned integer.
     fn read_le_u32(&mut self) -> io::Result<u32>;
 
+    /// Reads eight bytes and interprets them as a little-endian 64-bit unsigned integer.
+    fn read_le_u64(&mut self) -> io::Result<u64>;
+
     /// Reads four bytes and interprets them as a little-endian 32-bit IEEE float.
     fn read_le_f32(&mut self) -> io::Result<f32>;
 }
@@ -83,7 +92,7 @@ impl<R> ReadExt for R
             if progress > 0 {
                 n += progress;
             } else {
-                return Err(io::Error::new(io::ErrorKind::Other, "Failed to read enough bytes."));
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Failed to read enough bytes."));
             }
         }
         Ok(())
@@ -103,7 +112,7 @@ impl<R> ReadExt for R
             if progress > 0 {
                 n_read += progress;
             } else {
-                return Err(io::Error::new(io::ErrorKind::Other, "Failed to read enough bytes."));
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Failed to read enough bytes."));
             }
         }
         Ok(())
@@ -186,6 +195,13 @@ impl<R> ReadExt for R
            (buf[1] as u32) << 8  | (buf[0] as u32) << 0)
     }
 
+    #[inline(always)]
+    fn read_le_u64(&mut self) -> io::Result<u64> {
+        let lo = try!(self.read_le_u32());
+        let hi = try!(self.read_le_u32());
+        Ok((hi as u64) << 32 | lo as u64)
+    }
+
     #[inline(always)]
     fn read_le_f32(&mut self) -> io::Result<f32> {    
         let mut buf = [0u8; 4];
@@ -214,16 +230,15 @@ impl ChunkReadingState {
         let current_position = (self.len - self.remaining) as i64;
         let wanted_position = match seek {
            io::SeekFrom::Current(offset) => current_position + offset,
-           io::SeekFrom::Start(pos) => pos as i64,
+           io::SeekFrom::Start(pos) => cmp::min(pos, ::std::i64::MAX as u64) as i64,
            io::SeekFrom::End(pos) => pos as i64 + self.len as i64,
         };
         if wanted_position < 0 {
             Err(io::Error::new(io::ErrorKind::Other, "Seeking before begin of chunk"))
-        } else if wanted_position as u64 > self.len {
-            self.remaining = 0;
-            self.len = wanted_position as u64;
-            reader.seek(io::SeekFrom::Current(wanted_position - current_position))
         } else {
+            // Seeking beyond the end of the chunk stops at the end, so the
+            // reader cannot leave the chunk, and its length stays intact.
+            let wanted_position = cmp::min(wanted_position, self.len as i64);
             self.remaining = (self.len as i64 - wanted_position) as u64;
             reader.seek(io::SeekFrom::Current(wanted_position - current_position))
         }
@@ -277,6 +292,72 @@ pub enum Chunk<'r, R: 'r + io::Read> {
     Unknown([u8; 4], EmbeddedReader<'r, R>),
 }
 
+/// Limits and settings that control how a WAVE file is read.
+///
+/// The defaults are generous enough for any legitimate file, but finite, so
+/// that a file which claims to contain a huge chunk cannot make the reader
+/// exhaust memory. When reading untrusted files, for example on a server,
+/// the limits can be lowered further.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// The maximum number of bytes of a metadata chunk that will be loaded into memory.
+    ///
+    /// This applies to chunks that are parsed in full, such as the `fmt `
+    /// chunk. If a chunk is larger, `Error::FormatError` is returned.
+    pub max_metadata_bytes: u32,
+
+    /// The maximum number of bytes that will be allocated up front, based on
+    /// sizes read from the file.
+    ///
+    /// Buffers that should hold data of a size given in the file, such as the
+    /// one returned by `WavReader::raw_data()`, start out with at most this
+    /// capacity, and grow only as data is actually read.
+    pub max_preallocate: usize,
+
+    /// Whether to accept header values that are valid, but implausible.
+    ///
+    /// By default, a sample rate above 4 MHz is rejected with
+    /// `Error::FormatError`, as it is much more likely to be the result of
+    /// corruption than to be intended. A sample rate of zero is rejected
+    /// regardless of this setting.
+    pub lenient: bool,
+
+    /// Whether to keep the content of all chunks, so they can be written back.
+    ///
+    /// When set, `WavReader::preserved_chunks()` returns every chunk that was
+    /// read, in the order of the file, including the ones that hound does not
+    /// interpret. With `WavWriter::new_with_chunks()`, the file can then be
+    /// written back byte for byte; see there for the exceptions. Every chunk
+    /// other than the data chunk is loaded into memory, so chunks larger than
+    /// `max_metadata_bytes` are rejected with `Error::FormatError`.
+    pub preserve_chunks: bool,
+
+    /// Whether to verify the data against the checksum in a `cksm` chunk.
+    ///
+    /// The `cksm` chunk holds the CRC-32 of the contents of the data chunk,
+    /// as a 4-byte little-endian integer; see `WavWriter::data_crc32()`. It
+    /// may precede or follow the data chunk. When set, the checksum is
+    /// computed while the samples are read, and when the sample iterator
+    /// reaches the end of the data chunk, it yields one more item:
+    /// `Error::FormatError("checksum mismatch")` if the checksum differs, or
+    /// `Error::FormatError("checksum chunk not found")` if there is no `cksm`
+    /// chunk. To look for a `cksm` chunk after the data, the chunks that
+    /// follow it are read. Nothing is verified after seeking.
+    pub verify_checksum_chunk: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> ReadOptions {
+        ReadOptions {
+            max_metadata_bytes: 16 * 1024 * 1024,
+            max_preallocate: 64 * 1024 * 1024,
+            lenient: false,
+            preserve_chunks: false,
+            verify_checksum_chunk: false,
+        }
+    }
+}
+
 /// A Riff chunk Wave reader, giving access to all chunks in the file.
 ///
 /// For simple wave file decoding, prefer the `WavReader` facade.
@@ -290,6 +371,40 @@ pub struct ChunksReader<R: io::Read> {
     /// when inside the main data state, keeps track of decoding and chunk
     /// boundaries
     pub data_state: Option<DataReadingState>,
+    /// the 64-bit data chunk size from the `ds64` chunk of an RF64 file
+    rf64_data_len: Option<u64>,
+    /// the `fmt ` chunk bytes that follow the `cbSize` field, if any
+    fmt_extra: Vec<u8>,
+    /// whether to copy the extension bytes of the `fmt ` chunk into `fmt_extra`
+    keep_fmt_extra: bool,
+    /// whether the `fmt ` chunk includes the `cbSize` field of `WAVEFORMATEX`
+    fmt_has_cb_size: bool,
+    /// the format tag of the `fmt ` chunk, or 0 if it has not been read yet
+    format_tag: u16,
+    /// limits that apply while reading
+    options: ReadOptions,
+    /// the number of samples per channel from the `fact` chunk, if any
+    fact_samples: Option<u32>,
+    /// byte offset in the file of the next chunk header
+    next_chunk_offset: u64,
+    /// byte offset in the file of the contents of the data chunk, if found
+    data_offset: Option<u64>,
+    /// the text of the `DISP` chunk, if one was read
+    display_title: Option<String>,
+    /// the slice points of the `strc` chunk, if one was read
+    slices: Vec<Slice>,
+    /// the fields of the `INFO` list, if one was read
+    metadata: Metadata,
+    /// whether the data chunk has the placeholder size 0xffffffff: read until end of file
+    data_len_unknown: bool,
+    /// the audio of a `LIST` chunk of type `wavl`, if that replaces the data chunk
+    wave_list: Option<WaveList>,
+    /// the chunks read so far, if `ReadOptions::preserve_chunks` is set
+    preserved_chunks: Vec<RawChunk>,
+    /// the checksum of the data read so far, if it is to be verified
+    data_crc: Option<Crc32>,
+    /// the checksum from the `cksm` chunk, if one was read
+    stored_crc: Option<u32>,
 }
 
 /// This struct helps represent the inner state of the ChunksReader
@@ -302,18 +417,61 @@ pub struct DataReadingState {
     pub chunk: ChunkReadingState,
 }
 
+impl DataReadingState {
+    /// Returns the number of bytes at the end of the data chunk that do not form a whole frame.
+    fn partial_frame_len(&self) -> u64 {
+        let frame_len = self.spec_ex.bytes_per_sample as u64 * self.spec_ex.spec.channels as u64;
+        self.chunk.len % cmp::max(1, frame_len)
+    }
+
+    /// Returns the number of bytes that remain to be read, up to the last whole frame.
+    fn remaining_frames_len(&self) -> u64 {
+        self.chunk.remaining.saturating_sub(self.partial_frame_len())
+    }
+}
+
 impl<R: io::Read> ChunksReader<R> {
     /// Builds a ChunksReader from a std Reader.
     ///
     /// This function will only read the Riff header from the file
     /// in order to position the stream to the first chunk.
-    pub fn new(mut reader: R) -> Result<ChunksReader<R>> {
+    pub fn new(reader: R) -> Result<ChunksReader<R>> {
+        ChunksReader::new_with_options(reader, ReadOptions::default())
+    }
+
+    /// Builds a ChunksReader from a std Reader, with the given options.
+    ///
+    /// See `ReadOptions` for the available settings.
+    pub fn new_with_options(mut reader: R, options: ReadOptions) -> Result<ChunksReader<R>> {
         try!(read_wave_header(&mut reader));
-        Ok(ChunksReader {
+        Ok(ChunksReader::from_reader(reader, options))
+    }
+
+    /// Builds a ChunksReader positioned at the first chunk, without reading anything.
+    fn from_reader(reader: R, options: ReadOptions) -> ChunksReader<R> {
+        ChunksReader {
             reader: reader,
             spec_ex: None,
             data_state: None,
-        })
+            rf64_data_len: None,
+            fmt_extra: Vec::new(),
+            keep_fmt_extra: true,
+            fmt_has_cb_size: false,
+            format_tag: 0,
+            options: options,
+            fact_samples: None,
+            // The RIFF header is 12 bytes long, the first chunk follows it.
+            next_chunk_offset: 12,
+            data_offset: None,
+            display_title: None,
+            slices: Vec::new(),
+            metadata: Metadata::default(),
+            data_len_unknown: false,
+            wave_list: None,
+            preserved_chunks: Vec::new(),
+            data_crc: None,
+            stored_crc: None,
+        }
     }
 
     /// Returns an iterator over all samples.
@@ -367,69 +525,364 @@ impl<R: io::Read> ChunksReader<R> {
     /// keep track of the audio samples parsing.
     pub fn next(&mut self) -> Result<Option<Chunk<R>>> {
         if let Some(ref mut data) = self.data_state {
-            try!(data.chunk.skip_remaining(&mut self.reader))
+            // A wave list has been consumed entirely already.
+            if self.wave_list.take().is_none() {
+                try!(data.chunk.skip_remaining(&mut self.reader))
+            }
         }
         self.data_state = None;
-        let mut kind_str = [0; 4];
-        if self.reader.read_into(&mut kind_str).is_err() {
-            // FIXME EOF is indistinguishable from actual errors in read_into
-            return Ok(None);
-        }
-        let len = try!(self.reader.read_le_u32());
-        match &kind_str {
-            b"fmt " => {
-                let spec_ex = try!(self.read_fmt_chunk(len));
-                self.spec_ex = Some(spec_ex);
-                Ok(Some(Chunk::Fmt(spec_ex)))
-            }
-            b"fact" => {
-                // All (compressed) non-PCM formats must have a fact chunk
-                // (Rev. 3 documentation). The chunk contains at least one
-                // value, the number of samples in the file.
-                //
-                // The number of samples field is redundant for sampled
-                // data, since the Data chunk indicates the length of the
-                // data. The number of samples can be determined from the
-                // length of the data and the container size as determined
-                // from the Format chunk.
-                // http://www-mmsp.ece.mcgill.ca/documents/audioformats/wave/wave.html
-                let _samples_per_channel = self.reader.read_le_u32();
-                Ok(Some(Chunk::Fact))
+        // Chunks that are consumed here are not returned, reading simply
+        // continues with the next chunk. A loop rather than recursion keeps
+        // the stack bounded for files with many of them.
+        loop {
+            let mut kind_str = [0; 4];
+            if self.reader.read_into(&mut kind_str).is_err() {
+                // FIXME EOF is indistinguishable from actual errors in read_into
+                return Ok(None);
             }
-            b"data" => {
-                if let Some(spec_ex) = self.spec_ex {
-                    self.data_state = Some(DataReadingState {
-                        spec_ex: spec_ex,
-                        chunk: ChunkReadingState { len: len as u64, remaining: len as u64}
-                    });
-                    Ok(Some(Chunk::Data))
-                } else {
-                    Err(Error::FormatError("missing fmt chunk"))
+            let len = try!(self.reader.read_le_u32());
+            let chunk_offset = self.next_chunk_offset;
+            // Chunks are padded to an even size.
+            self.next_chunk_offset = chunk_offset + 8 + len as u64 + (len % 2) as u64;
+            // The arms that consume a chunk entirely yield the chunk to
+            // return, or `None` to continue with the next one. The others
+            // return directly, their padding is skipped by `skip_remaining()`.
+            let consumed = match &kind_str {
+                b"fmt " => {
+                    let spec_ex = try!(self.read_fmt_chunk(len));
+                    self.spec_ex = Some(spec_ex);
+                    Some(Chunk::Fmt(spec_ex))
                 }
-            }
-            _ => {
-                let reader = EmbeddedReader {
-                    reader: &mut self.reader,
-                    state: ChunkReadingState { len: len as u64, remaining: len as u64 }
-                };
-                Ok(Some(Chunk::Unknown(kind_str, reader)))
+                b"fact" => {
+                    // All (compressed) non-PCM formats must have a fact chunk
+                    // (Rev. 3 documentation). The chunk contains at least one
+                    // value, the number of samples in the file.
+                    //
+                    // The number of samples field is redundant for sampled
+                    // data, since the Data chunk indicates the length of the
+                    // data. The number of samples can be determined from the
+                    // length of the data and the container size as determined
+                    // from the Format chunk.
+                    // http://www-mmsp.ece.mcgill.ca/documents/audioformats/wave/wave.html
+                    if len < 4 {
+                        return Err(Error::FormatError("invalid fact chunk size"));
+                    }
+                    let samples_per_channel = try!(self.reader.read_le_u32());
+                    // Some writers add more fields; skip those, so the next chunk
+                    // header is read from the right position.
+                    if self.options.preserve_chunks {
+                        let mut data = vec![0u8; len as usize];
+                        try!(self.reader.read_into(&mut data[4..]));
+                        try!((&mut data[..4]).write_le_u32(samples_per_channel));
+                        self.preserve(kind_str, data);
+                    } else {
+                        try!(self.reader.skip_bytes(len as usize - 4));
+                    }
+                    self.fact_samples = Some(samples_per_channel);
+                    Some(Chunk::Fact)
+                }
+                b"ds64" => {
+                    // RF64 files store the 64-bit sizes in a ds64 chunk, which
+                    // must be the first chunk. The 32-bit size fields of the RIFF
+                    // header and the data chunk are then set to 0xffffffff.
+                    if len < 28 {
+                        return Err(Error::FormatError("invalid ds64 chunk size"));
+                    }
+                    let _riff_len = try!(self.reader.read_le_u64());
+                    let data_len = try!(self.reader.read_le_u64());
+                    let _num_frames = try!(self.reader.read_le_u64());
+                    // The table of sizes of other chunks is not needed, only the
+                    // data chunk can be larger than 4 GiB in files that we read.
+                    try!(self.reader.skip_bytes(len as usize - 24));
+                    self.rf64_data_len = Some(data_len);
+                    None
+                }
+                b"DISP" => {
+                    // The display chunk starts with a clipboard format, followed
+                    // by the data. Only CF_TEXT (1), a null-terminated string, is
+                    // of interest; other formats, such as bitmaps, are skipped.
+                    // The chunk is consumed here, and not returned as a chunk.
+                    const CF_TEXT: u32 = 1;
+                    if len < 4 {
+                        return Err(Error::FormatError("invalid DISP chunk size"));
+                    }
+                    if len > self.options.max_metadata_bytes {
+                        return Err(Error::FormatError("DISP chunk exceeds max_metadata_bytes"));
+                    }
+                    let format = try!(self.reader.read_le_u32());
+                    if format == CF_TEXT || self.options.preserve_chunks {
+                        let mut data = vec![0u8; len as usize];
+                        try!(self.reader.read_into(&mut data[4..]));
+                        try!((&mut data[..4]).write_le_u32(format));
+                        if format == CF_TEXT {
+                            let text = &data[4..];
+                            let text_len = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+                            let title = String::from_utf8_lossy(&text[..text_len]).into_owned();
+                            self.display_title = Some(title);
+                        }
+                        self.preserve(kind_str, data);
+                    } else {
+                        try!(self.reader.skip_bytes(len as usize - 4));
+                    }
+                    None
+                }
+                b"strc" => {
+                    // The stretch chunk of Acid-style loops holds slice points.
+                    // Like `DISP`, it is consumed here, see `parse_strc()`.
+                    if len > self.options.max_metadata_bytes {
+                        return Err(Error::FormatError("strc chunk exceeds max_metadata_bytes"));
+                    }
+                    let mut data = vec![0u8; len as usize];
+                    try!(self.reader.read_into(&mut data));
+                    self.slices = parse_strc(&data);
+                    self.preserve(kind_str, data);
+                    None
+                }
+                b"cksm" if self.options.verify_checksum_chunk => {
+                    // The checksum is consumed here, and verified at the end of
+                    // the data, see `verify_data_crc()`.
+                    if len != 4 {
+                        return Err(Error::FormatError("invalid cksm chunk size"));
+                    }
+                    let crc = try!(self.reader.read_le_u32());
+                    let mut data = vec![0u8; 4];
+                    try!((&mut data[..]).write_le_u32(crc));
+                    self.preserve(kind_str, data);
+                    self.stored_crc = Some(crc);
+                    None
+                }
+                b"data" => {
+                    if let Some(spec_ex) = self.spec_ex {
+                        // Files that are written as a live stream may set the size
+                        // to the maximum, as a placeholder meaning "until EOF".
+                        // That is only known when the end is reached, or by
+                        // seeking, see `resolve_unknown_data_len()`.
+                        self.data_len_unknown = self.rf64_data_len.is_none() && len == 0xffff_ffff;
+                        let len = match self.rf64_data_len {
+                            Some(data_len) if len == 0xffff_ffff => data_len,
+                            _ => len as u64,
+                        };
+                        self.next_chunk_offset = chunk_offset + 8 + len + len % 2;
+                        self.data_offset = Some(chunk_offset + 8);
+                        self.preserve(kind_str, Vec::new());
+                        self.start_data_crc();
+                        self.data_state = Some(DataReadingState {
+                            spec_ex: spec_ex,
+                            chunk: ChunkReadingState { len: len, remaining: len }
+                        });
+                        return Ok(Some(Chunk::Data));
+                    } else {
+                        return Err(Error::FormatError("missing fmt chunk"));
+                    }
+                }
+                _ => {
+                    let reader = EmbeddedReader {
+                        reader: &mut self.reader,
+                        state: ChunkReadingState { len: len as u64, remaining: len as u64 }
+                    };
+                    return Ok(Some(Chunk::Unknown(kind_str, reader)));
+                }
+            };
+            try!(self.reader.skip_bytes(len as usize % 2));
+            if let Some(chunk) = consumed {
+                return Ok(Some(chunk));
             }
         }
         // If no data chunk is ever encountered, the function will return
         // via one of the try! macros that return an Err on end of file.
     }
 
+    /// Starts computing the checksum of the data, if `ReadOptions::verify_checksum_chunk` is set.
+    fn start_data_crc(&mut self) {
+        if self.options.verify_checksum_chunk {
+            self.data_crc = Some(Crc32::new());
+        }
+    }
+
+    /// Compares the checksum of the data read with the one from the `cksm` chunk.
+    ///
+    /// This should be called when the end of the data chunk has been reached.
+    /// If the `cksm` chunk has not been read yet, the chunks after the data
+    /// chunk are read to find it. This is done only once, afterwards, or if
+    /// no checksum is being computed, this returns `Ok`.
+    fn verify_data_crc(&mut self) -> Result<()> {
+        let crc = match self.data_crc.take() {
+            Some(crc) => crc.value(),
+            None => return Ok(()),
+        };
+        if self.stored_crc.is_none() {
+            // Reading the next chunks leaves the data chunk, but the reader
+            // should remain usable as being at the end of the data.
+            let data_state = self.data_state;
+            try!(self.read_remaining_chunks());
+            self.data_state = data_state;
+        }
+        match self.stored_crc {
+            None => Err(Error::FormatError("checksum chunk not found")),
+            Some(stored) if stored != crc => Err(Error::FormatError("checksum mismatch")),
+            Some(..) => Ok(()),
+        }
+    }
+
+    /// Consumes the bytes at the end of the data chunk that are too few for a frame.
+    ///
+    /// A data chunk whose length is not a multiple of the frame size ends in
+    /// a partial frame, which is not part of the samples, not even the whole
+    /// samples in it. Like the padding byte after an odd-sized chunk, it is
+    /// skipped. This does nothing before the last whole frame has been read,
+    /// or if the data chunk has a placeholder size. If the stream ends within
+    /// the partial frame, the data ends there, and an `UnexpectedEof` error is
+    /// returned.
+    fn skip_partial_frame(&mut self) -> io::Result<()> {
+        let data = self.data_state.expect("Not in the data chunk.");
+        if self.data_len_unknown || data.chunk.remaining == 0 || data.remaining_frames_len() > 0 {
+            return Ok(());
+        }
+        try!(io::copy(&mut io::Read::take(&mut *self, data.chunk.remaining), &mut io::sink()));
+        let state = self.data_state.as_mut().expect("Not in the data chunk.");
+        if state.chunk.remaining > 0 {
+            state.chunk.len -= state.chunk.remaining;
+            state.chunk.remaining = 0;
+            self.data_crc = None;
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "data ends in a partial frame"));
+        }
+        Ok(())
+    }
+
+    /// Records a chunk, if `ReadOptions::preserve_chunks` is set.
+    fn preserve(&mut self, id: [u8; 4], data: Vec<u8>) {
+        if self.options.preserve_chunks {
+            self.preserved_chunks.push(RawChunk { id: id, data: data });
+        }
+    }
+
+    /// Records a chunk returned as `Chunk::Unknown`, and parses it if it is an `INFO` list.
+    ///
+    /// Apart from the chunks that are preserved, this is called for `INFO`
+    /// lists regardless of `ReadOptions::preserve_chunks`.
+    fn keep_unknown_chunk(&mut self, id: [u8; 4], data: Vec<u8>) {
+        if &id == b"LIST" && data.starts_with(b"INFO") {
+            self.metadata = parse_info(&data[4..]);
+        }
+        self.preserve(id, data);
+    }
+
+    /// Reads the content of a chunk returned as `Chunk::Unknown`, to preserve it.
+    fn read_unknown_chunk<T: io::Read>(reader: &mut EmbeddedReader<T>,
+                                       options: ReadOptions)
+                                       -> Result<Vec<u8>> {
+        let len = reader.state.remaining;
+        if len > options.max_metadata_bytes as u64 {
+            return Err(Error::FormatError("chunk exceeds max_metadata_bytes"));
+        }
+        let mut data = vec![0u8; len as usize];
+        try!(reader.read_into(&mut data));
+        Ok(data)
+    }
+
+    /// Returns the chunks that have been read so far.
+    ///
+    /// This is empty unless `ReadOptions::preserve_chunks` is set. See
+    /// `RawChunk` for how the data chunk is represented.
+    pub fn preserved_chunks(&self) -> &[RawChunk] {
+        &self.preserved_chunks
+    }
+
+    /// Skips the rest of the data chunk, and reads all chunks that follow it.
+    ///
+    /// If `ReadOptions::preserve_chunks` is set, the chunks are then available
+    /// through `preserved_chunks()`. Afterwards, no more samples can be read.
+    pub fn read_remaining_chunks(&mut self) -> Result<()> {
+        loop {
+            let options = self.options;
+            let preserved = match try!(self.next()) {
+                None => return Ok(()),
+                Some(Chunk::Unknown(kind, mut reader)) => {
+                    if options.preserve_chunks {
+                        let data = try!(ChunksReader::<R>::read_unknown_chunk(&mut reader, options));
+                        Some((kind, data))
+                    } else if &kind == b"LIST" {
+                        let mut list_type = [0u8; 4];
+                        try!(reader.read_into(&mut list_type));
+                        if &list_type == b"INFO" {
+                            let mut data = list_type.to_vec();
+                            data.extend(try!(ChunksReader::<R>::read_unknown_chunk(&mut reader, options)));
+                            Some((kind, data))
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    }
+                }
+                Some(..) => None,
+            };
+            if let Some((kind, data)) = preserved {
+                self.keep_unknown_chunk(kind, data);
+            }
+        }
+    }
+
     /// Reads chunks until a data chunk is encountered.
     ///
     /// Returns true if a data chunk has been found.  Afterwards, the reader
     /// will be positioned at the first content byte of the data chunk.
+    ///
+    /// A `LIST` chunk of type `wavl`, which contains alternating `data`
+    /// chunks and `slnt` chunks with runs of silence, is also accepted in
+    /// place of the data chunk. The wave list is read into memory, and the
+    /// samples are then read from there, with silence expanded. In that case
+    /// `data_chunk_range()` returns `None`, as the data is not contiguous.
     pub fn read_until_data(&mut self) -> Result<bool> {
-        while let Some(chunk) = try!(self.next()) {
-            if let Chunk::Data = chunk {
-                return Ok(true)
+        loop {
+            let spec_ex = self.spec_ex;
+            let options = self.options;
+            let (wave_list, preserved) = match try!(self.next()) {
+                None => return Ok(false),
+                Some(Chunk::Data) => return Ok(true),
+                Some(Chunk::Unknown(kind, mut reader)) => {
+                    if options.preserve_chunks {
+                        let data = try!(ChunksReader::<R>::read_unknown_chunk(&mut reader, options));
+                        if &kind == b"LIST" && data.starts_with(b"wavl") {
+                            (Some(try!(WaveList::read(&mut &data[4..], spec_ex, options))), None)
+                        } else {
+                            (None, Some((kind, data)))
+                        }
+                    } else if &kind == b"LIST" {
+                        let mut list_type = [0u8; 4];
+                        try!(reader.read_into(&mut list_type));
+                        match &list_type {
+                            b"wavl" => (Some(try!(WaveList::read(&mut reader, spec_ex, options))), None),
+                            b"INFO" => {
+                                let mut data = list_type.to_vec();
+                                data.extend(try!(ChunksReader::<R>::read_unknown_chunk(&mut reader, options)));
+                                (None, Some((kind, data)))
+                            }
+                            _ => (None, None),
+                        }
+                    } else {
+                        (None, None)
+                    }
+                }
+                Some(..) => (None, None),
+            };
+            if let Some((kind, data)) = preserved {
+                self.keep_unknown_chunk(kind, data);
+            }
+            if let Some(wave_list) = wave_list {
+                // The wave list takes the place of the data chunk, and it is
+                // preserved as such.
+                self.preserve(*b"data", Vec::new());
+                self.start_data_crc();
+                let len = wave_list.len();
+                self.data_state = Some(DataReadingState {
+                    spec_ex: spec_ex.expect("wave list implies known format"),
+                    chunk: ChunkReadingState { len: len, remaining: len },
+                });
+                self.wave_list = Some(wave_list);
+                return Ok(true);
             }
         }
-        Ok(false)
     }
 
     /// Reads the fmt chunk of the file, returns the information it provides.
@@ -441,6 +894,9 @@ impl<R: io::Read> ChunksReader<R> {
         if chunk_len < 16 {
             return Err(Error::FormatError("invalid fmt chunk size"));
         }
+        if chunk_len > self.options.max_metadata_bytes {
+            return Err(Error::FormatError("fmt chunk exceeds max_metadata_bytes"));
+        }
 
         // Read the WAVEFORMAT struct, as defined at
         // https://msdn.microsoft.com/en-us/library/ms713498.aspx.
@@ -485,12 +941,105 @@ impl<R: io::Read> ChunksReader<R> {
         let n_bytes_per_sec = try!(self.reader.read_le_u32());
         let block_align = try!(self.reader.read_le_u16());
         let bits_per_sample = try!(self.reader.read_le_u16());
+        self.format_tag = format_tag;
+
+        // Read the remainder of the chunk at once, so the extension bytes can
+        // be kept. For the common formats it fits on the stack.
+        let rest_len = chunk_len as usize - 16;
+        let mut rest_small = [0u8; 24];
+        let mut rest_large = Vec::new();
+        let rest: &[u8] = if rest_len <= rest_small.len() {
+            try!(self.reader.read_into(&mut rest_small[..rest_len]));
+            &rest_small[..rest_len]
+        } else {
+            rest_large.resize(rest_len, 0);
+            try!(self.reader.read_into(&mut rest_large));
+            &rest_large
+        };
+        self.fmt_extra.clear();
+        self.fmt_has_cb_size = rest.len() >= 2;
+        if self.keep_fmt_extra && rest.len() > 2 {
+            self.fmt_extra.extend_from_slice(&rest[2..]);
+        }
+        if self.options.preserve_chunks {
+            // The fields were read as-is, so this reproduces the chunk exactly.
+            let mut data = Vec::with_capacity(chunk_len as usize);
+            try!(data.write_le_u16(format_tag));
+            try!(data.write_le_u16(n_channels));
+            try!(data.write_le_u32(n_samples_per_sec));
+            try!(data.write_le_u32(n_bytes_per_sec));
+            try!(data.write_le_u16(block_align));
+            try!(data.write_le_u16(bits_per_sample));
+            data.extend_from_slice(rest);
+            self.preserve(*b"fmt ", data);
+        }
 
         if n_channels == 0 {
             return Err(Error::FormatError("file contains zero channels"));
         }
+        if n_samples_per_sec == 0 {
+            return Err(Error::FormatError("sample rate is zero"));
+        }
+        if n_samples_per_sec > 4_000_000 && !self.options.lenient {
+            return Err(Error::FormatError("implausibly high sample rate"));
+        }
 
         let bytes_per_sample = block_align / n_channels;
+
+        // The different format tag definitions can be found in mmreg.h that is
+        // part of the Windows SDK. The vast majority are esoteric vendor-
+        // specific formats. We handle only a few. The following values could
+        // be of interest:
+        const PCM: u16 = 0x0001;
+        const IEEE_FLOAT: u16 = 0x0003;
+        const EXTENSIBLE: u16 = 0xfffe;
+        const ALAW: u16 = 0x0006;
+        const MULAW: u16 = 0x0007;
+
+        // Compressed and vendor-specific formats (such as ADPCM, or DTS) can
+        // not be decoded, but the data chunk can still be extracted as an
+        // opaque payload with `raw_data()`. The fields of the spec are taken
+        // as they are, the sample format is meaningless in this case.
+        match format_tag {
+            PCM | IEEE_FLOAT | EXTENSIBLE => {}
+            // G.711 stores every sample as one 8-bit codeword, that expands to
+            // a 16-bit linear sample. The spec describes the decoded samples,
+            // so it claims 16 bits, even though a sample takes only one byte.
+            ALAW | MULAW => {
+                if bits_per_sample != 8 {
+                    return Err(Error::UnsupportedBitsPerSample(bits_per_sample));
+                }
+                if block_align != n_channels {
+                    return Err(Error::FormatError("invalid block align"));
+                }
+                let spec = WavSpec {
+                    channels: n_channels,
+                    sample_rate: n_samples_per_sec,
+                    bits_per_sample: 16,
+                    sample_format: SampleFormat::Int,
+                };
+                return Ok(WavSpecEx {
+                    spec: spec,
+                    bytes_per_sample: 1,
+                });
+            }
+            _ => {
+                if bytes_per_sample == 0 {
+                    return Err(Error::FormatError("invalid block align"));
+                }
+                let spec = WavSpec {
+                    channels: n_channels,
+                    sample_rate: n_samples_per_sec,
+                    bits_per_sample: bits_per_sample,
+                    sample_format: SampleFormat::Int,
+                };
+                return Ok(WavSpecEx {
+                    spec: spec,
+                    bytes_per_sample: bytes_per_sample,
+                });
+            }
+        }
+
         // We allow bits_per_sample to be less than bytes_per_sample so that
         // we can support things such as 24 bit samples in 4 byte containers.
         if Some(bits_per_sample) > bytes_per_sample.checked_mul(8) {
@@ -503,6 +1052,12 @@ impl<R: io::Read> ChunksReader<R> {
             return Err(Error::FormatError("inconsistent fmt chunk"));
         }
 
+        // Fewer than 8 bits per sample (such as 1-bit audio) can not be
+        // stored in the WAVE format in a way that hound can represent.
+        if bits_per_sample > 0 && bits_per_sample < 8 {
+            return Err(Error::UnsupportedBitsPerSample(bits_per_sample));
+        }
+
         // The bits per sample for a WAVEFORMAT struct is the number of bits
         // used to store a sample. Therefore, it must be a multiple of 8.
         if bits_per_sample % 8 != 0 {
@@ -520,30 +1075,27 @@ impl<R: io::Read> ChunksReader<R> {
             sample_format: SampleFormat::Int,
         };
 
-        // The different format tag definitions can be found in mmreg.h that is
-        // part of the Windows SDK. The vast majority are esoteric vendor-
-        // specific formats. We handle only a few. The following values could
-        // be of interest:
-        const PCM: u16 = 0x0001;
-        const ADPCM: u16 = 0x0002;
-        const IEEE_FLOAT: u16 = 0x0003;
-        const EXTENSIBLE: u16 = 0xfffe;
         // We may update our WavSpec based on more data we read from the header.
         match format_tag {
-            PCM => try!(self.read_wave_format_pcm(chunk_len, &spec)),
-            ADPCM => return Err(Error::Unsupported),
-            IEEE_FLOAT => try!(self.read_wave_format_ieee_float(chunk_len, &mut spec)),
-            EXTENSIBLE => try!(self.read_wave_format_extensible(chunk_len, &mut spec)),
-            _ => return Err(Error::Unsupported),
+            PCM => try!(Self::read_wave_format_pcm(rest, chunk_len, &spec)),
+            IEEE_FLOAT => try!(Self::read_wave_format_ieee_float(rest, chunk_len, &mut spec)),
+            EXTENSIBLE => try!(Self::read_wave_format_extensible(rest, chunk_len, &mut spec)),
+            _ => unreachable!(),
         };
 
+        // The extensible format can declare any number of valid bits, reject
+        // the ones that do not make sense, rather than failing later.
+        if !bits_supported(&spec) {
+            return Err(Error::UnsupportedBitsPerSample(spec.bits_per_sample));
+        }
+
         Ok(WavSpecEx {
             spec: spec,
             bytes_per_sample: bytes_per_sample,
         })
     }
 
-    fn read_wave_format_pcm(&mut self, chunk_len: u32, spec: &WavSpec) -> Result<()> {
+    fn read_wave_format_pcm(mut rest: &[u8], chunk_len: u32, spec: &WavSpec) -> Result<()> {
         // When there is a PCMWAVEFORMAT struct, the chunk is 16 bytes long.
         // The WAVEFORMATEX structs includes two extra bytes, `cbSize`.
         let is_wave_format_ex = match chunk_len {
@@ -560,7 +1112,7 @@ impl<R: io::Read> ChunksReader<R> {
             // additional data. However, for WAVE_FORMAT_PCM, the member should
             // be ignored, see https://msdn.microsoft.com/en-us/library/ms713497.aspx.
             // Nonzero values do in fact occur in practice.
-            let _cb_size = try!(self.reader.read_le_u16());
+            let _cb_size = try!(rest.read_le_u16());
 
             // For WAVE_FORMAT_PCM in WAVEFORMATEX, only 8 or 16 bits per
             // sample are valid according to
@@ -576,14 +1128,12 @@ impl<R: io::Read> ChunksReader<R> {
             }
         }
 
-        // If the chunk len was longer than expected, ignore the additional bytes.
-        if chunk_len == 40 {
-            try!(self.reader.skip_bytes(22));
-        }
+        // If the chunk len was longer than expected, the additional bytes are
+        // ignored. They have been consumed already.
         Ok(())
     }
 
-    fn read_wave_format_ieee_float(&mut self, chunk_len: u32, spec: &mut WavSpec) -> Result<()> {
+    fn read_wave_format_ieee_float(mut rest: &[u8], chunk_len: u32, spec: &mut WavSpec) -> Result<()> {
         // When there is a PCMWAVEFORMAT struct, the chunk is 16 bytes long.
         // The WAVEFORMATEX structs includes two extra bytes, `cbSize`.
         let is_wave_format_ex = chunk_len == 18;
@@ -595,7 +1145,7 @@ impl<R: io::Read> ChunksReader<R> {
         if is_wave_format_ex {
             // For WAVE_FORMAT_IEEE_FLOAT which we are reading, there should
             // be no extra data, so `cbSize` should be 0.
-            let cb_size = try!(self.reader.read_le_u16());
+            let cb_size = try!(rest.read_le_u16());
             if cb_size != 0 {
                 return Err(Error::FormatError("unexpected WAVEFORMATEX size"));
             }
@@ -608,14 +1158,14 @@ impl<R: io::Read> ChunksReader<R> {
         // Note that some applications support 64 bits per sample. This is
         // not yet supported by hound.
         if spec.bits_per_sample != 32 {
-            return Err(Error::FormatError("bits per sample is not 32"));
+            return Err(Error::UnsupportedBitsPerSample(spec.bits_per_sample));
         }
 
         spec.sample_format = SampleFormat::Float;
         Ok(())
     }
 
-    fn read_wave_format_extensible(&mut self, chunk_len: u32, spec: &mut WavSpec) -> Result<()> {
+    fn read_wave_format_extensible(mut rest: &[u8], chunk_len: u32, spec: &mut WavSpec) -> Result<()> {
         // 16 bytes were read already, there must be two more for the `cbSize`
         // field, and `cbSize` itself must be at least 22, so the chunk length
         // must be at least 40.
@@ -624,7 +1174,7 @@ impl<R: io::Read> ChunksReader<R> {
         }
 
         // `cbSize` is the last field of the WAVEFORMATEX struct.
-        let cb_size = try!(self.reader.read_le_u16());
+        let cb_size = try!(rest.read_le_u16());
 
         // `cbSize` must be at least 22, but in this case we assume that it is
         // 22, because we would not know how to handle extra data anyway.
@@ -646,10 +1196,10 @@ impl<R: io::Read> ChunksReader<R> {
         //   GUID    SubFormat;
         // } WAVEFORMATEXTENSIBLE, *PWAVEFORMATEXTENSIBLE;
         // ```
-        let valid_bits_per_sample = try!(self.reader.read_le_u16());
-        let _channel_mask = try!(self.reader.read_le_u32()); // Not used for now.
+        let valid_bits_per_sample = try!(rest.read_le_u16());
+        let _channel_mask = try!(rest.read_le_u32()); // Not used for now.
         let mut subformat = [0u8; 16];
-        try!(self.reader.read_into(&mut subformat));
+        try!(rest.read_into(&mut subformat));
 
         // Several GUIDS are defined. At the moment, only the following are supported:
         //
@@ -670,6 +1220,142 @@ impl<R: io::Read> ChunksReader<R> {
         Ok(())
     }
 
+    /// Returns the format tag of the `fmt ` chunk.
+    ///
+    /// This is 1 for integer PCM, 3 for IEEE float, and 0xfffe for
+    /// `WAVEFORMATEXTENSIBLE`. Other values indicate compressed or
+    /// vendor-specific formats, for which samples cannot be decoded. Returns 0
+    /// if the `fmt ` chunk has not been read yet.
+    pub fn format_tag(&self) -> u16 {
+        self.format_tag
+    }
+
+    /// Returns the range of byte offsets in the file that the data chunk occupies.
+    ///
+    /// The range starts at the first byte of the first sample (so its start
+    /// is also the length of the header), and excludes the padding byte that
+    /// follows a data chunk of odd length. Returns `None` if the data chunk has
+    /// not been reached yet.
+    pub fn data_chunk_range(&self) -> Option<ops::Range<u64>> {
+        match (self.data_offset, self.data_state) {
+            (Some(start), Some(data)) if self.wave_list.is_none() => {
+                Some(start..start + data.chunk.len)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the `DISP` chunk, if one has been read.
+    ///
+    /// `DISP` chunks are parsed when they are encountered by `next()`, and
+    /// they are not returned as chunks themselves.
+    pub fn display_title(&self) -> Option<&str> {
+        self.display_title.as_ref().map(|t| &t[..])
+    }
+
+    /// Returns the number of samples per channel from the `fact` chunk, if one has been read.
+    pub fn fact_samples(&self) -> Option<u32> {
+        self.fact_samples
+    }
+
+    /// Returns the fields of the `INFO` list, if one has been read.
+    ///
+    /// `INFO` lists are parsed when they are encountered by `read_until_data()`
+    /// or `read_remaining_chunks()`. Until then, all fields are `None`.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the slice points of the `strc` chunk, if one has been read.
+    ///
+    /// `strc` chunks are parsed when they are encountered by `next()`, and
+    /// they are not returned as chunks themselves.
+    pub fn slices(&self) -> &[Slice] {
+        &self.slices
+    }
+
+    /// Returns whether hound can decode the samples of the format read.
+    fn can_decode(&self) -> bool {
+        match self.format_tag {
+            0x0001 | 0x0003 | 0x0006 | 0x0007 | 0xfffe => true,
+            _ => false,
+        }
+    }
+
+    /// Reads the remaining bytes of the data chunk, regardless of the format.
+    ///
+    /// This function will panic if it is called while the reader is not in
+    /// the data chunk.
+    pub fn raw_data(&mut self) -> Result<Vec<u8>> {
+        use std::io::Read;
+        let remaining = self.data_state.expect("Not in the data chunk.").chunk.remaining;
+        let capacity = cmp::min(remaining, self.options.max_preallocate as u64);
+        let mut buffer = Vec::with_capacity(capacity as usize);
+        try!(self.read_to_end(&mut buffer));
+        if (buffer.len() as u64) < remaining {
+            return Err(Error::Truncated);
+        }
+        Ok(buffer)
+    }
+
+    /// Returns whether the data chunk has a placeholder size, and its length is not known yet.
+    pub fn data_len_unknown(&self) -> bool {
+        self.data_len_unknown
+    }
+
+    /// Determines the length of a data chunk with a placeholder size from the stream length.
+    ///
+    /// If the data chunk has the placeholder size 0xffffffff, the data is
+    /// assumed to extend to the end of the stream, and the length is set
+    /// accordingly. Otherwise this does nothing. The reader remains at the
+    /// same position.
+    pub fn resolve_unknown_data_len(&mut self) -> io::Result<()>
+        where R: io::Seek
+    {
+        if !self.data_len_unknown {
+            return Ok(());
+        }
+        let position = try!(self.reader.seek(io::SeekFrom::Current(0)));
+        let end = try!(self.reader.seek(io::SeekFrom::End(0)));
+        try!(self.reader.seek(io::SeekFrom::Start(position)));
+
+        let data = self.data_state.as_mut().expect("Not in the data chunk.");
+        let consumed = data.chunk.len - data.chunk.remaining;
+        let remaining = end.saturating_sub(position);
+        data.chunk = ChunkReadingState { len: consumed + remaining, remaining: remaining };
+        self.data_len_unknown = false;
+        Ok(())
+    }
+
+    /// Limits the length of the data chunk to the bytes that remain in the stream.
+    ///
+    /// A truncated or crafted file can declare a data chunk that is larger than
+    /// the file. The remaining length is then set to the whole frames that are
+    /// actually present, so reading stops cleanly at the end of the stream,
+    /// rather than with `Error::Truncated`. Data chunks that fit, and those with
+    /// a placeholder size, are left unchanged. The reader remains at the same
+    /// position.
+    pub fn clamp_data_len_to_stream(&mut self) -> io::Result<()>
+        where R: io::Seek
+    {
+        if self.data_len_unknown || self.wave_list.is_some() {
+            return Ok(());
+        }
+        let position = try!(self.reader.seek(io::SeekFrom::Current(0)));
+        let end = try!(self.reader.seek(io::SeekFrom::End(0)));
+        try!(self.reader.seek(io::SeekFrom::Start(position)));
+
+        let data = self.data_state.as_mut().expect("Not in the data chunk.");
+        let available = end.saturating_sub(position);
+        if available < data.chunk.remaining {
+            let block_align = data.spec_ex.bytes_per_sample as u64 * data.spec_ex.spec.channels as u64;
+            let remaining = available - available % cmp::max(1, block_align);
+            data.chunk.len -= data.chunk.remaining - remaining;
+            data.chunk.remaining = remaining;
+        }
+        Ok(())
+    }
+
     /// Unwrap the raw Reader from this Chunkreader
     pub fn into_inner(self) -> R {
         self.reader
@@ -680,18 +1366,38 @@ impl<R: io::Read> ChunksReader<R> {
     /// The given time is measured in number of samples (independent of the
     /// number of channels) since the beginning of the audio data. To seek to
     /// a particular time in seconds, multiply the number of seconds with
-    /// `WavSpec::sample_rate`. The given time should not exceed the duration of
-    /// the file (returned by `duration()`). The behavior when seeking beyond
-    /// `duration()` depends on the reader's `Seek` implementation.
+    /// `WavSpec::sample_rate`. Seeking beyond the duration of the file
+    /// (returned by `duration()`) positions the reader at the end of the
+    /// audio data, so no samples remain.
     ///
     /// This method requires that the inner reader `R` implements `Seek`.
     pub fn seek(&mut self, time: u32) -> io::Result<()>
         where R: io::Seek,
     {
+        self.seek_u64(time as u64)
+    }
+
+    /// Seek to the given time within the file, which may exceed `u32::MAX`.
+    ///
+    /// This is like `seek()`, but it can reach every frame of an RF64 file.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn seek_u64(&mut self, time: u64) -> io::Result<()>
+        where R: io::Seek,
+    {
+        try!(self.resolve_unknown_data_len());
+        // The checksum covers the data in order, it cannot be verified anymore.
+        self.data_crc = None;
         let data = self.data_state.as_mut().expect("Not in the data chunk.");
-        let wanted_sample = time as i64 * data.spec_ex.spec.channels as i64;
-        let wanted_byte = wanted_sample * data.spec_ex.bytes_per_sample as i64;
-        try!(data.chunk.seek(&mut self.reader, io::SeekFrom::Start(wanted_byte as u64)));
+        // A time beyond the end stops at the end, even if its offset overflows.
+        let wanted_byte = time.checked_mul(data.spec_ex.spec.channels as u64)
+            .and_then(|sample| sample.checked_mul(data.spec_ex.bytes_per_sample as u64))
+            .unwrap_or(::std::u64::MAX);
+        let wanted = io::SeekFrom::Start(wanted_byte);
+        match self.wave_list {
+            Some(ref mut wave_list) => try!(data.chunk.seek(wave_list, wanted)),
+            None => try!(data.chunk.seek(&mut self.reader, wanted)),
+        };
         Ok(())
     }
 }
@@ -699,7 +1405,159 @@ impl<R: io::Read> ChunksReader<R> {
 impl<R: io::Read> io::Read for ChunksReader<R> {
     fn read(&mut self, buffer: &mut[u8]) -> io::Result<usize> {
         let data = self.data_state.as_mut().expect("Not in the data chunk.");
-        data.chunk.read(&mut self.reader, buffer)
+        let n = try!(match self.wave_list {
+            Some(ref mut wave_list) => data.chunk.read(wave_list, buffer),
+            None => data.chunk.read(&mut self.reader, buffer),
+        });
+        if let Some(ref mut crc) = self.data_crc {
+            crc.update(&buffer[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// A run of silence or a block of samples in a wave list.
+enum WaveListSegment {
+    /// a number of bytes of silence
+    Silence(u64),
+    /// the contents of a data chunk
+    Data(Vec<u8>),
+}
+
+impl WaveListSegment {
+    /// Returns the length of the segment with silence expanded, in bytes.
+    fn len(&self) -> u64 {
+        match *self {
+            WaveListSegment::Silence(len) => len,
+            WaveListSegment::Data(ref data) => data.len() as u64,
+        }
+    }
+}
+
+/// The audio of a `LIST` chunk of type `wavl`, which can be read as one stream.
+struct WaveList {
+    segments: Vec<WaveListSegment>,
+    /// the value of the bytes of a silent sample
+    silence: u8,
+    /// the position in the expanded stream, in bytes
+    position: u64,
+    /// the index of the segment that the last read ended in
+    segment: usize,
+    /// the position in the expanded stream where that segment starts
+    segment_start: u64,
+}
+
+impl WaveList {
+    /// Reads the content of a `LIST` chunk of type `wavl` that follows the list type.
+    fn read<R: io::Read>(reader: &mut R,
+                         spec_ex: Option<WavSpecEx>,
+                         options: ReadOptions)
+                         -> Result<WaveList> {
+        let spec_ex = match spec_ex {
+            Some(spec_ex) => spec_ex,
+            None => return Err(Error::FormatError("missing fmt chunk")),
+        };
+        let block_align = spec_ex.bytes_per_sample as u64 * spec_ex.spec.channels as u64;
+
+        let mut segments = Vec::new();
+        loop {
+            let mut kind = [0u8; 4];
+            match reader.read_into(&mut kind) {
+                Ok(()) => {}
+                // The end of the list is the end of the embedded reader.
+                Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(Error::IoError(err)),
+            }
+            let len = try!(reader.read_le_u32()) as u64;
+            let mut chunk = io::Read::take(&mut *reader, len);
+            match &kind {
+                b"slnt" => {
+                    if len < 4 {
+                        return Err(Error::FormatError("invalid slnt chunk size"));
+                    }
+                    let num_frames = try!(chunk.read_le_u32()) as u64;
+                    segments.push(WaveListSegment::Silence(num_frames * block_align));
+                }
+                b"data" => {
+                    let capacity = cmp::min(len, options.max_preallocate as u64);
+                    let mut data = Vec::with_capacity(capacity as usize);
+                    try!(io::Read::read_to_end(&mut chunk, &mut data));
+                    if (data.len() as u64) < len {
+                        return Err(Error::Truncated);
+                    }
+                    segments.push(WaveListSegment::Data(data));
+                }
+                _ => {}
+            }
+            // Skip the rest of the chunk, and the padding byte, which may be
+            // missing after the last chunk.
+            try!(io::copy(&mut chunk, &mut io::sink()));
+            try!(io::copy(&mut io::Read::take(&mut *reader, len % 2), &mut io::sink()));
+        }
+
+        // Samples of 8 bits are unsigned, so silence is not zero for them.
+        let silence = if spec_ex.bytes_per_sample == 1 { 0x80 } else { 0 };
+        Ok(WaveList {
+            segments: segments,
+            silence: silence,
+            position: 0,
+            segment: 0,
+            segment_start: 0,
+        })
+    }
+
+    /// Returns the length of the stream with silence expanded, in bytes.
+    fn len(&self) -> u64 {
+        self.segments.iter().map(|segment| segment.len()).sum()
+    }
+}
+
+impl io::Read for WaveList {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        // Reads continue in the segment where the previous one ended, only
+        // after seeking backwards the segments are searched from the start.
+        if self.position < self.segment_start {
+            self.segment = 0;
+            self.segment_start = 0;
+        }
+        while let Some(segment) = self.segments.get(self.segment) {
+            let len = segment.len();
+            if self.position < self.segment_start + len {
+                let offset = self.position - self.segment_start;
+                let n = cmp::min(buffer.len() as u64, len - offset) as usize;
+                match *segment {
+                    WaveListSegment::Silence(..) => {
+                        for x in &mut buffer[..n] {
+                            *x = self.silence;
+                        }
+                    }
+                    WaveListSegment::Data(ref data) => {
+                        let offset = offset as usize;
+                        buffer[..n].copy_from_slice(&data[offset..offset + n]);
+                    }
+                }
+                self.position += n as u64;
+                return Ok(n);
+            }
+            self.segment_start += len;
+            self.segment += 1;
+        }
+        Ok(0)
+    }
+}
+
+impl io::Seek for WaveList {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            io::SeekFrom::Start(pos) => pos as i64,
+            io::SeekFrom::Current(offset) => self.position as i64 + offset,
+            io::SeekFrom::End(offset) => self.len() as i64 + offset,
+        };
+        if position < 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "seek before start of wave list"));
+        }
+        self.position = position as u64;
+        Ok(self.position)
     }
 }
 
@@ -730,41 +1588,318 @@ pub struct WavReader<R: io::Read> {
     reader: ChunksReader<R>,
 }
 
-/// An iterator that yields samples of type `S` read from a `WavReader`.
+/// Samples of a type that is chosen at runtime, as returned by `WavReader::read_all_boxed()`.
 ///
-/// The type `S` must have at least as many bits as the bits per sample of the
-/// file, otherwise every iteration will return an error.
-pub struct WavSamples<'wr, R, S>
-    where R: io::Read + 'wr
-{
-    reader: &'wr mut ChunksReader<R>,
-    phantom_sample: marker::PhantomData<S>,
+/// The samples are stored in the smallest type that holds them, and can be
+/// converted to a common type on demand.
+pub trait SampleBuffer {
+    /// Returns the number of samples in the buffer.
+    fn len(&self) -> usize;
+
+    /// Returns whether the buffer contains no samples.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the samples as floats, where full scale maps to [-1.0, 1.0].
+    ///
+    /// Integer samples are divided by 2 to the power `bits_per_sample - 1`,
+    /// float samples are returned as they are.
+    fn as_f32_vec(&self) -> Vec<f32>;
+
+    /// Returns the samples as 32-bit integers.
+    ///
+    /// Integer samples are returned with their value unchanged, float samples
+    /// are scaled so that [-1.0, 1.0] maps to the full range of `i32`, and
+    /// values outside of that range are clamped.
+    fn as_i32_vec(&self) -> Vec<i32>;
 }
 
-/// An iterator that yields samples of type `S` read from a `WavReader`.
+/// Returns 2 to the power `bits_per_sample - 1`, the full scale of an integer sample.
 ///
-/// The type `S` must have at least as many bits as the bits per sample of the
-/// file, otherwise every iteration will return an error.
-pub struct WavIntoSamples<R: io::Read, S> {
-    reader: ChunksReader<R>,
-    phantom_sample: marker::PhantomData<S>,
+/// This is computed for formats that hound cannot decode too, where the bits
+/// per sample can be anything; there it returns 1 rather than overflowing.
+fn int_full_scale(bits_per_sample: u16) -> f64 {
+    bits_per_sample.checked_sub(1)
+        .and_then(|shift| 1_u64.checked_shl(shift as u32))
+        .map_or(1.0, |full_scale| full_scale as f64)
 }
 
-/// Reads the RIFF WAVE header, returns the supposed file size.
-///
-/// This function can be used to quickly check if the file could be a wav file
-/// by reading 12 bytes of the header. If an `Ok` is returned, the file is
-/// likely a wav file. If an `Err` is returned, it is definitely not a wav
+/// A `SampleBuffer` that stores samples of type `S`.
+struct NativeBuffer<S> {
+    samples: Vec<S>,
+    bits_per_sample: u16,
+}
+
+macro_rules! impl_int_sample_buffer {
+    ($t: ty) => {
+        impl SampleBuffer for NativeBuffer<$t> {
+            fn len(&self) -> usize {
+                self.samples.len()
+            }
+
+            fn as_f32_vec(&self) -> Vec<f32> {
+                let full_scale = int_full_scale(self.bits_per_sample) as f32;
+                self.samples.iter().map(|&x| x as f32 / full_scale).collect()
+            }
+
+            fn as_i32_vec(&self) -> Vec<i32> {
+                self.samples.iter().map(|&x| x as i32).collect()
+            }
+        }
+    }
+}
+
+impl_int_sample_buffer!(i8);
+impl_int_sample_buffer!(i16);
+impl_int_sample_buffer!(i32);
+
+impl SampleBuffer for NativeBuffer<f32> {
+    fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn as_f32_vec(&self) -> Vec<f32> {
+        self.samples.clone()
+    }
+
+    fn as_i32_vec(&self) -> Vec<i32> {
+        self.samples.iter().map(|&x| {
+            let scaled = (x as f64 * 2147483648.0).round();
+            if scaled.is_nan() {
+                0
+            } else if scaled >= i32::max_value() as f64 {
+                i32::max_value()
+            } else if scaled <= i32::min_value() as f64 {
+                i32::min_value()
+            } else {
+                scaled as i32
+            }
+        }).collect()
+    }
+}
+
+/// A slice point from the `strc` chunk of an Acid-style loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slice {
+    /// The position of the slice, in frames since the start of the data.
+    pub position: u64,
+}
+
+/// Parses the slice points of a `strc` chunk.
+///
+/// The chunk is not officially documented. It starts with a header of seven
+/// 32-bit fields, of which the second is the number of slices. Every slice
+/// entry contains its position as a 64-bit integer at offset 8. Entries are
+/// usually 32 bytes, but the entry size is derived from the chunk size to
+/// tolerate other writers. A chunk that does not fit this layout yields no
+/// slices, rather than an error.
+fn parse_strc(data: &[u8]) -> Vec<Slice> {
+    const HEADER_LEN: usize = 28;
+    if data.len() < HEADER_LEN {
+        return Vec::new();
+    }
+    let num_slices = (&data[4..8]).read_le_u32().unwrap() as usize;
+    let entries = &data[HEADER_LEN..];
+    if num_slices == 0 || entries.len() / num_slices < 16 {
+        return Vec::new();
+    }
+    let entry_len = entries.len() / num_slices;
+    entries.chunks(entry_len).take(num_slices).map(|entry| {
+        Slice { position: (&entry[8..16]).read_le_u64().unwrap() }
+    }).collect()
+}
+
+/// Parses the sub-chunks of an `INFO` list, that follow the list type.
+///
+/// Strings are null-terminated, but the terminator is not always present,
+/// and bytes that are not valid UTF-8 are replaced. Unknown sub-chunks are
+/// skipped, and a truncated sub-chunk ends the list, rather than an error.
+fn parse_info(mut data: &[u8]) -> Metadata {
+    let mut metadata = Metadata::default();
+    while data.len() >= 8 {
+        let len = (&data[4..8]).read_le_u32().unwrap() as usize;
+        if data.len() - 8 < len {
+            break;
+        }
+        let text = &data[8..8 + len];
+        let text_len = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+        let text = Some(String::from_utf8_lossy(&text[..text_len]).into_owned());
+        match &data[..4] {
+            b"INAM" => metadata.title = text,
+            b"IART" => metadata.artist = text,
+            b"ICMT" => metadata.comment = text,
+            _ => {}
+        }
+        // Sub-chunks are padded to an even size, but the padding may be
+        // missing after the last one.
+        data = &data[cmp::min(data.len(), 8 + len + len % 2)..];
+    }
+    metadata
+}
+
+/// The result of `WavReader::validate_sample_range()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The number of samples that were checked.
+    pub num_samples: u32,
+    /// The smallest sample value observed, using the full container width.
+    pub min: i32,
+    /// The largest sample value observed, using the full container width.
+    pub max: i32,
+    /// Whether any sample does not fit in the declared valid bits per sample.
+    pub exceeds_valid_bits: bool,
+}
+
+/// An iterator that yields samples of type `S` read from a `WavReader`.
+///
+/// The type `S` must have at least as many bits as the bits per sample of the
+/// file, otherwise every iteration will return an error.
+pub struct WavSamples<'wr, R, S>
+    where R: io::Read + 'wr
+{
+    reader: &'wr mut ChunksReader<R>,
+    phantom_sample: marker::PhantomData<S>,
+}
+
+/// An iterator that yields samples of type `S` read from a `WavReader`.
+///
+/// The type `S` must have at least as many bits as the bits per sample of the
+/// file, otherwise every iteration will return an error.
+pub struct WavIntoSamples<R: io::Read, S> {
+    reader: ChunksReader<R>,
+    phantom_sample: marker::PhantomData<S>,
+}
+
+/// An iterator that yields the sums of the samples of two `WavReader`s.
+///
+/// A `MixedSamples` can be obtained by calling [`mix`](fn.mix.html).
+pub struct MixedSamples<R1: io::Read, R2: io::Read> {
+    a: WavIntoSamples<R1, i32>,
+    b: WavIntoSamples<R2, i32>,
+}
+
+/// An iterator that yields samples, and skips to the next frame after an error.
+///
+/// A `ResyncSamples` can be obtained by calling [`WavReader::samples_resync`](
+/// struct.WavReader.html#method.samples_resync).
+pub struct ResyncSamples<'wr, R, S>
+    where R: io::Read + 'wr
+{
+    reader: &'wr mut ChunksReader<R>,
+    /// set when the reader could not skip past an error
+    stopped: bool,
+    phantom_sample: marker::PhantomData<S>,
+}
+
+/// An iterator that yields samples multiplied by a gain.
+///
+/// A `GainSamples` can be obtained by calling [`WavReader::samples_with_gain`](
+/// struct.WavReader.html#method.samples_with_gain).
+pub struct GainSamples<'wr, R, S>
+    where R: io::Read + 'wr
+{
+    samples: WavSamples<'wr, R, S>,
+    /// the linear gain factor
+    gain: f64,
+}
+
+/// An iterator that yields samples coerced to a possibly narrower type.
+///
+/// A `CoercedSamples` can be obtained by calling [`WavReader::samples_as`](
+/// struct.WavReader.html#method.samples_as).
+pub struct CoercedSamples<'wr, R, S>
+    where R: io::Read + 'wr
+{
+    samples: WavSamples<'wr, R, i32>,
+    bits_per_sample: u16,
+    phantom_sample: marker::PhantomData<S>,
+}
+
+/// An iterator that yields samples as `f32` in the range -1.0 to 1.0.
+///
+/// A `NormalizedSamples` can be obtained by calling [`WavReader::samples_normalized`](
+/// struct.WavReader.html#method.samples_normalized).
+pub struct NormalizedSamples<'wr, R>
+    where R: io::Read + 'wr
+{
+    reader: &'wr mut ChunksReader<R>,
+    /// the value that an integer sample is divided by, or `None` for float samples
+    full_scale: Option<f64>,
+}
+
+/// An iterator that yields the frames of a `WavReader` from the last one to the first.
+///
+/// A `ReverseSamples` can be obtained by calling [`WavReader::samples_rev`](
+/// struct.WavReader.html#method.samples_rev).
+pub struct ReverseSamples<'wr, R, S>
+    where R: io::Read + 'wr
+{
+    reader: &'wr mut ChunksReader<R>,
+    channels: usize,
+    /// the number of frames before the current block, that are still to be read
+    frames_left: u64,
+    /// the frames of the current block, in file order
+    block: Vec<S>,
+    /// the number of frames at the start of `block` that have not been yielded
+    block_frames: usize,
+    /// the channel of the next sample to yield, within its frame
+    channel: usize,
+}
+
+/// An owning iterator of 16-bit samples, with the properties that playback needs.
+///
+/// A `WavSource` can be obtained by calling [`WavReader::into_source`](
+/// struct.WavReader.html#method.into_source). Audio playback libraries
+/// typically consume a source as an iterator of plain `i16` samples, together
+/// with the number of channels and the sample rate; this type provides exactly
+/// that. Because the items are not `Result`s, iteration stops at the first
+/// error, which can be inspected afterwards with `take_error()`.
+pub struct WavSource<R: io::Read> {
+    reader: ChunksReader<R>,
+    spec: WavSpec,
+    error: Option<Error>,
+}
+
+/// An iterator that yields samples together with their time in seconds.
+///
+/// A `TimedSamples` can be obtained by calling [`WavReader::samples_timed`](
+/// struct.WavReader.html#method.samples_timed).
+pub struct TimedSamples<'wr, R, S>
+    where R: io::Read + 'wr
+{
+    samples: WavSamples<'wr, R, S>,
+    sample_rate: f64,
+}
+
+/// An iterator that yields the samples of one `WavReader`, followed by those of another.
+///
+/// A `ChainedSamples` can be obtained by calling [`WavReader::chain`](
+/// struct.WavReader.html#method.chain).
+pub struct ChainedSamples<R1: io::Read, R2: io::Read, S> {
+    first: WavIntoSamples<R1, S>,
+    second: WavIntoSamples<R2, S>,
+}
+
+/// Reads the RIFF WAVE header, returns the supposed file size.
+///
+/// This function can be used to quickly check if the file could be a wav file
+/// by reading 12 bytes of the header. If an `Ok` is returned, the file is
+/// likely a wav file. If an `Err` is returned, it is definitely not a wav
 /// file.
 ///
 /// The returned file size cannot be larger than 2<sup>32</sup> + 7 bytes.
+///
+/// Many encoders write an incorrect size, so the readers in this crate do not
+/// use it: chunks are read based on their own sizes until the end of the
+/// stream, regardless of the size in the header.
 pub fn read_wave_header<R: io::Read>(reader: &mut R) -> Result<u64> {
     // Every WAVE file starts with the four bytes 'RIFF' and a file length.
-    // TODO: the old approach of having a slice on the stack and reading
-    // into it is more cumbersome, but also avoids a heap allocation. Is
-    // the compiler smart enough to avoid the heap allocation anyway? I
-    // would not expect it to be.
-    if b"RIFF" != &try!(reader.read_4_bytes())[..] {
+    // The tags are read into arrays on the stack, so this does not allocate.
+    // RF64 files start with 'RF64' instead, and store the actual length in
+    // the ds64 chunk that follows.
+    let riff_tag = try!(reader.read_4_bytes());
+    if b"RIFF" != &riff_tag[..] && b"RF64" != &riff_tag[..] {
         return Err(Error::FormatError("no RIFF tag found"));
     }
 
@@ -780,6 +1915,23 @@ pub fn read_wave_header<R: io::Read>(reader: &mut R) -> Result<u64> {
     Ok(file_len as u64 + 8)
 }
 
+/// Parses the contents of a `fmt ` chunk, such as a preserved one.
+///
+/// The chunk is checked as when a file is read, except for the plausibility
+/// of the sample rate.
+pub fn parse_fmt_chunk(data: &[u8]) -> Result<WavSpecEx> {
+    if data.len() > u32::MAX as usize {
+        return Err(Error::FormatError("fmt chunk is too large"));
+    }
+    let options = ReadOptions {
+        max_metadata_bytes: data.len() as u32,
+        lenient: true,
+        ..ReadOptions::default()
+    };
+    let mut reader = ChunksReader::from_reader(data, options);
+    reader.read_fmt_chunk(data.len() as u32)
+}
+
 impl<R> WavReader<R>
     where R: io::Read
 {
@@ -788,7 +1940,15 @@ impl<R> WavReader<R>
     /// The header is read immediately. Reading the data will be done on
     /// demand.
     pub fn new(reader: R) -> Result<WavReader<R>> {
-        let mut reader = try!(ChunksReader::new(reader));
+        WavReader::new_with_options(reader, ReadOptions::default())
+    }
+
+    /// Attempts to create a reader that reads the WAVE format, with the given options.
+    ///
+    /// This is like `new()`, but the limits of `options` apply instead of the
+    /// defaults. See `ReadOptions` for the available settings.
+    pub fn new_with_options(reader: R, options: ReadOptions) -> Result<WavReader<R>> {
+        let mut reader = try!(ChunksReader::new_with_options(reader, options));
         try!(reader.read_until_data());
         if reader.spec_ex.is_none() {
             return Err(Error::FormatError("Wave file with no fmt header"))
@@ -798,6 +1958,179 @@ impl<R> WavReader<R>
         })
     }
 
+    /// Attempts to create a reader for a WAVE file that starts at `offset` in `reader`.
+    ///
+    /// This is useful for reading WAVE files that are embedded in a larger
+    /// file, such as an archive. The reader is first positioned at `offset`,
+    /// which is then treated as the start of the file: offsets such as the one
+    /// returned by `data_chunk_range()` are relative to it, and so is seeking.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn new_at(mut reader: R, offset: u64) -> Result<WavReader<R>>
+        where R: io::Seek,
+    {
+        try!(reader.seek(io::SeekFrom::Start(offset)));
+        WavReader::new_seekable(reader)
+    }
+
+    /// Attempts to create a reader for a WAVE file in a reader that can seek.
+    ///
+    /// This is like `new()`, but the length of the data chunk is checked
+    /// against the length of the stream right away, so `len()` and the size
+    /// hints of the sample iterators are accurate: a placeholder size
+    /// 0xffffffff is resolved, and a data chunk that claims more bytes than
+    /// the stream holds is limited to the whole frames that are present, see
+    /// `clamp_len_to_stream()`. The `open()`, `from_bytes()` and `new_at()`
+    /// constructors use this.
+    pub fn new_seekable(reader: R) -> Result<WavReader<R>>
+        where R: io::Seek,
+    {
+        let mut reader = try!(WavReader::new(reader));
+        try!(reader.reader.resolve_unknown_data_len());
+        try!(reader.reader.clamp_data_len_to_stream());
+        Ok(reader)
+    }
+
+    /// Creates a reader for raw sample data without a header, such as a PCM dump.
+    ///
+    /// The samples are read as described by `spec`, from the `data_len` bytes
+    /// that start at `data_offset`. No RIFF parsing happens at all, so this
+    /// also works for files whose header is destroyed, while the audio is
+    /// intact. The bytes before `data_offset` are skipped by reading them.
+    /// Afterwards the reader behaves as if the file had a data chunk at the
+    /// given offset.
+    ///
+    /// The data is not validated against any header: whether the spec matches
+    /// the data is up to the caller. Only the spec itself is checked: it must
+    /// have at least one channel and a bit depth that hound can read,
+    /// otherwise `Error::FormatError` or `Error::UnsupportedBitsPerSample` is
+    /// returned. Samples are little-endian, in a container of the bits per
+    /// sample rounded up to whole bytes, and 8-bit samples are unsigned, as in
+    /// WAVE files.
+    pub fn new_raw(reader: R, spec: WavSpec, data_offset: u64, data_len: u64)
+                   -> Result<WavReader<R>> {
+        if spec.channels == 0 {
+            return Err(Error::FormatError("spec contains zero channels"));
+        }
+        if !bits_supported(&spec) {
+            return Err(Error::UnsupportedBitsPerSample(spec.bits_per_sample));
+        }
+        let spec_ex = WavSpecEx {
+            spec: spec,
+            bytes_per_sample: i32::sample_bytes(spec.bits_per_sample),
+        };
+
+        let mut chunks = ChunksReader::from_reader(reader, ReadOptions::default());
+        let skipped = try!(io::copy(&mut io::Read::take(&mut chunks.reader, data_offset),
+                                    &mut io::sink()));
+        if skipped < data_offset {
+            return Err(Error::Truncated);
+        }
+        chunks.format_tag = match spec.sample_format {
+            SampleFormat::Int => 0x0001,
+            SampleFormat::Float => 0x0003,
+        };
+        chunks.spec_ex = Some(spec_ex);
+        chunks.data_offset = Some(data_offset);
+        chunks.next_chunk_offset = data_offset + data_len;
+        chunks.data_state = Some(DataReadingState {
+            spec_ex: spec_ex,
+            chunk: ChunkReadingState { len: data_len, remaining: data_len },
+        });
+        Ok(WavReader {
+            reader: chunks,
+        })
+    }
+
+    /// Reads only the header of a WAVE file, up to and including the `fmt ` chunk.
+    ///
+    /// This is a cheap way to find out the spec of a file when the samples are
+    /// not needed. Chunks before the `fmt ` chunk are skipped, and reading
+    /// stops right after it, so the reader is left positioned after the `fmt `
+    /// chunk. For files without extension data in the `fmt ` chunk, this
+    /// performs no heap allocations.
+    pub fn read_spec_only(reader: &mut R) -> Result<WavSpec> {
+        let mut chunks = try!(ChunksReader::new(reader));
+        // The extension bytes are not needed, and keeping them would allocate.
+        chunks.keep_fmt_extra = false;
+        loop {
+            match try!(chunks.next()) {
+                Some(Chunk::Fmt(spec_ex)) => return Ok(spec_ex.spec),
+                Some(..) => continue,
+                None => return Err(Error::FormatError("no fmt chunk found")),
+            }
+        }
+    }
+
+    /// Returns the extension bytes of the `fmt ` chunk.
+    ///
+    /// These are the bytes that follow the `cbSize` field of a `WAVEFORMATEX`
+    /// structure, such as the 22 bytes that make up the rest of a
+    /// `WAVEFORMATEXTENSIBLE` structure, or vendor-specific data. The slice is
+    /// empty if the chunk has no extension.
+    pub fn fmt_extra_bytes(&self) -> &[u8] {
+        &self.reader.fmt_extra
+    }
+
+    /// Returns the extension bytes of an extended `fmt ` chunk.
+    ///
+    /// These are the same bytes as returned by `fmt_extra_bytes()`, but this
+    /// tells apart a chunk without a `cbSize` field, such as `PCMWAVEFORMAT`,
+    /// for which `None` is returned, from a `WAVEFORMATEX` chunk with a
+    /// `cbSize` of 0, for which the slice is empty.
+    pub fn extension_bytes(&self) -> Option<&[u8]> {
+        if self.reader.fmt_has_cb_size {
+            Some(&self.reader.fmt_extra)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the format tag of the `fmt ` chunk.
+    ///
+    /// See `ChunksReader::format_tag()` for the meaning of the values. For
+    /// formats that hound cannot decode, iterating the samples yields
+    /// `Error::Unsupported`, but the data can be extracted with `raw_data()`.
+    pub fn format_tag(&self) -> u16 {
+        self.reader.format_tag()
+    }
+
+    /// Reads the remaining bytes of the data chunk, regardless of the format.
+    ///
+    /// When called before any samples have been read, this returns the entire
+    /// data chunk. This is useful for files that contain a compressed
+    /// bitstream, which can then be passed to a different decoder.
+    pub fn raw_data(&mut self) -> Result<Vec<u8>> {
+        self.reader.raw_data()
+    }
+
+    /// Reads the remaining bytes of the data chunk, and returns their CRC-32 checksum.
+    ///
+    /// When called before any samples have been read, this is the checksum of
+    /// the entire data chunk, which depends only on the audio: two files with
+    /// the same samples in the same format have the same checksum, regardless
+    /// of their metadata. The checksum is the common one used by zlib and PNG.
+    /// `WavWriter::with_data_crc32()` computes the same checksum while writing.
+    ///
+    /// If the data chunk is shorter than its header claims, `Error::Truncated`
+    /// is returned.
+    pub fn data_crc32(&mut self) -> Result<u32> {
+        let mut crc = Crc32::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let n = try!(io::Read::read(&mut self.reader, &mut buffer));
+            if n == 0 {
+                break;
+            }
+            crc.update(&buffer[..n]);
+        }
+        let data = self.reader.data_state.expect("Not in the data chunk.");
+        if data.chunk.remaining > 0 && !self.reader.data_len_unknown {
+            return Err(Error::Truncated);
+        }
+        Ok(crc.value())
+    }
+
     /// Returns information about the WAVE file.
     pub fn spec(&self) -> WavSpec {
         self.reader.spec_ex
@@ -805,6 +2138,90 @@ impl<R> WavReader<R>
             .spec
     }
 
+    /// Returns the range of byte offsets in the file that the data chunk occupies.
+    ///
+    /// The start of the range is the offset of the first sample. For files
+    /// with a minimal header this is 44, but files may contain other chunks
+    /// before the data chunk, or an extended `fmt ` chunk, so do not assume
+    /// that the samples start at byte 44.
+    pub fn data_chunk_range(&self) -> ops::Range<u64> {
+        self.reader.data_chunk_range().expect("Not in the data chunk.")
+    }
+
+    /// Returns the display title from the `DISP` chunk, if the file has one.
+    ///
+    /// Only a `DISP` chunk that precedes the data chunk is found by the
+    /// reader. The title is expected to contain text in the `CF_TEXT`
+    /// clipboard format, bytes that are not valid UTF-8 are replaced.
+    pub fn display_title(&self) -> Option<&str> {
+        self.reader.display_title()
+    }
+
+    /// Returns the text metadata from the `INFO` list, if the file has one.
+    ///
+    /// Like for `display_title()`, only an `INFO` list that precedes the data
+    /// chunk is found by the reader. The fields of an absent list are `None`.
+    pub fn metadata(&self) -> &Metadata {
+        self.reader.metadata()
+    }
+
+    /// Returns the number of samples per channel from the `fact` chunk, if the file has one.
+    ///
+    /// Compressed formats must have a `fact` chunk, and some writers add one
+    /// for float files too. For formats that hound decodes, `duration()` is
+    /// computed from the size of the data chunk regardless, but where the two
+    /// differ, for instance because the data is padded, the `fact` value is
+    /// the number of frames that are meant to be played. Only a `fact` chunk
+    /// that precedes the data chunk is found by the reader.
+    pub fn fact_samples(&self) -> Option<u32> {
+        self.reader.fact_samples()
+    }
+
+    /// Returns the slice points from the `strc` chunk, if the file has one.
+    ///
+    /// Acid-style loops use these to time-stretch the audio slice by slice.
+    /// Only a `strc` chunk that precedes the data chunk is found by the
+    /// reader. For files without one, the result is empty.
+    pub fn slices(&self) -> &[Slice] {
+        self.reader.slices()
+    }
+
+    /// Returns the chunks that precede the samples, if they were preserved.
+    ///
+    /// This is empty unless `ReadOptions::preserve_chunks` was set. The chunks
+    /// are in file order, and end with the marker for the data chunk. In
+    /// combination with `WavWriter::new_with_chunks()`, this allows writing a
+    /// file while the samples are being read. To include the chunks that follow
+    /// the samples as well, use `into_preserved_chunks()`.
+    pub fn preserved_chunks(&self) -> &[RawChunk] {
+        self.reader.preserved_chunks()
+    }
+
+    /// Destroys the `WavReader` and returns all chunks of the file.
+    ///
+    /// The rest of the data chunk is skipped, and the chunks after it are
+    /// read. Like `preserved_chunks()`, this returns nothing unless
+    /// `ReadOptions::preserve_chunks` was set.
+    pub fn into_preserved_chunks(mut self) -> Result<Vec<RawChunk>> {
+        try!(self.reader.read_remaining_chunks());
+        Ok(self.reader.preserved_chunks)
+    }
+
+    /// Returns the number of channels, a shorthand for `spec().channels`.
+    pub fn channels(&self) -> u16 {
+        self.spec().channels
+    }
+
+    /// Returns the sample rate, a shorthand for `spec().sample_rate`.
+    pub fn sample_rate(&self) -> u32 {
+        self.spec().sample_rate
+    }
+
+    /// Returns the bits per sample, a shorthand for `spec().bits_per_sample`.
+    pub fn bits_per_sample(&self) -> u16 {
+        self.spec().bits_per_sample
+    }
+
     /// Returns an iterator over all samples.
     ///
     /// The channel data is is interleaved. The iterator is streaming. That is,
@@ -831,626 +2248,3729 @@ impl<R> WavReader<R>
         self.reader.into_samples()
     }
 
-    /// Returns the duration of the file in samples.
+    /// Reads all remaining samples into a vector.
     ///
-    /// The duration is independent of the number of channels. It is expressed
-    /// in units of samples. The duration in seconds can be obtained by
-    /// dividing this number by the sample rate. The duration is independent of
-    /// how many samples have been read already.
-    pub fn duration(&self) -> u32 {
+    /// This is equivalent to `samples().collect::<Result<Vec<S>>>()`: reading
+    /// stops at the first error, which is returned. It is faster, because the
+    /// data is read in large blocks, and then decoded from memory.
+    pub fn collect_samples<S: Sample>(&mut self) -> Result<Vec<S>> {
         let data = self.reader.data_state.expect("Not in the data chunk.");
-        self.len() / data.spec_ex.spec.channels as u32
-    }
+        if data.chunk.remaining > 0 && !self.reader.can_decode() {
+            return Err(Error::Unsupported);
+        }
+        if self.reader.data_len_unknown {
+            return self.samples().collect();
+        }
+        let format_tag = self.reader.format_tag;
+        let bytes_per_sample = cmp::max(1, data.spec_ex.bytes_per_sample as usize);
+        let num_samples = self.samples::<S>().len();
+        let max_samples = self.reader.options.max_preallocate / cmp::max(1, mem::size_of::<S>());
+        let mut samples = Vec::with_capacity(cmp::min(num_samples, max_samples));
+
+        let mut buffer = vec![0u8; 4096 * bytes_per_sample];
+        let mut samples_left = num_samples;
+        while samples_left > 0 {
+            let block_len = cmp::min(samples_left, 4096);
+            let block = &mut buffer[..block_len * bytes_per_sample];
+            try!(self.reader.read_into(block).map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => Error::Truncated,
+                _ => Error::IoError(err),
+            }));
+            let mut encoded = &block[..];
+            for _ in 0..block_len {
+                samples.push(try!(read_sample(&mut encoded, format_tag, data.spec_ex)));
+            }
+            samples_left -= block_len;
+        }
 
-    /// Returns the number of values that the sample iterator will yield.
-    ///
-    /// The length of the file is its duration (in samples) times the number of
-    /// channels. The length is independent of how many samples have been read
-    /// already. To get the number of samples left, use `len()` on the
-    /// `samples()` iterator.
-    pub fn len(&self) -> u32 {
-        let data = self.reader.data_state.expect("not in the data chunk");
-        (data.chunk.len as usize / data.spec_ex.bytes_per_sample as usize) as u32
+        // A trailing partial frame is skipped, as by the iterator.
+        try!(self.reader.skip_partial_frame().map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => Error::Truncated,
+            _ => Error::IoError(err),
+        }));
+        Ok(samples)
     }
 
-    /// Destroys the `WavReader` and returns the underlying reader.
-    pub fn into_inner(self) -> R {
-        self.reader.into_inner()
+    /// Reads all remaining samples into a buffer whose sample type depends on the spec.
+    ///
+    /// Integer samples of 8 and 16 bits are stored as `i8` and `i16`, those
+    /// of 24 and 32 bits as `i32`, and float samples as `f32`. This allows
+    /// handling arbitrary files with a single code path, at the cost of a
+    /// conversion when the samples are taken out of the buffer. For formats
+    /// that are not in `supported_read_formats()`, `Error::Unsupported` is
+    /// returned.
+    pub fn read_all_boxed(mut self) -> Result<Box<dyn SampleBuffer>> {
+        let spec = self.spec();
+        let bits = spec.bits_per_sample;
+        if !SUPPORTED_FORMATS.contains(&(spec.sample_format, bits)) {
+            return Err(Error::Unsupported);
+        }
+        match (spec.sample_format, bits) {
+            (SampleFormat::Float, 32) => {
+                let samples = try!(self.collect_samples::<f32>());
+                Ok(Box::new(NativeBuffer { samples: samples, bits_per_sample: bits }))
+            }
+            (SampleFormat::Int, 8) => {
+                let samples = try!(self.collect_samples::<i8>());
+                Ok(Box::new(NativeBuffer { samples: samples, bits_per_sample: bits }))
+            }
+            (SampleFormat::Int, 16) => {
+                let samples = try!(self.collect_samples::<i16>());
+                Ok(Box::new(NativeBuffer { samples: samples, bits_per_sample: bits }))
+            }
+            (SampleFormat::Int, 24) | (SampleFormat::Int, 32) => {
+                let samples = try!(self.collect_samples::<i32>());
+                Ok(Box::new(NativeBuffer { samples: samples, bits_per_sample: bits }))
+            }
+            _ => Err(Error::Unsupported),
+        }
     }
 
-    /// Seek to the given time within the file.
+    /// Reads samples into `buf`, and returns the number of samples read.
     ///
-    /// The given time is measured in number of samples (independent of the
-    /// number of channels) since the beginning of the audio data. To seek to
-    /// a particular time in seconds, multiply the number of seconds with
-    /// `WavSpec::sample_rate`. The given time should not exceed the duration of
-    /// the file (returned by `duration()`). The behavior when seeking beyond
-    /// `duration()` depends on the reader's `Seek` implementation.
+    /// This reads `buf.len()` samples, or the number of samples that remain if
+    /// that is less; 0 is returned once all samples have been read. Unlike
+    /// the `samples()` iterator, which decodes one sample per read from the
+    /// underlying reader, the bytes of the whole block are read at once, and
+    /// then decoded from memory. That makes bulk decoding faster, especially
+    /// for unbuffered readers.
     ///
-    /// This method requires that the inner reader `R` implements `Seek`.
-    pub fn seek(&mut self, time: u32) -> io::Result<()>
-        where R: io::Seek,
-    {
-        self.reader.seek(time)
+    /// Before anything is read, it is checked that the samples can be decoded
+    /// into `S`. If the stream ends before the end of the data chunk,
+    /// `Error::Truncated` is returned, and the samples of the block are lost.
+    pub fn read_into<S: Sample>(&mut self, buf: &mut [S]) -> Result<usize> {
+        let data = self.reader.data_state.expect("Not in the data chunk.");
+        if !self.reader.can_decode() {
+            return Err(Error::Unsupported);
+        }
+        // The bytes are read and decoded in blocks of this size, so that no
+        // allocation is needed.
+        const BLOCK_BYTES: usize = 4096;
+        let mut block = [0u8; BLOCK_BYTES];
+        let format_tag = self.reader.format_tag;
+        let width = data.spec_ex.bytes_per_sample as usize;
+        try!(read_sample::<_, S>(&mut &block[..cmp::min(width, BLOCK_BYTES)],
+                                 format_tag, data.spec_ex));
+
+        let n = cmp::min(buf.len() as u64, data.remaining_frames_len() / width as u64) as usize;
+        let block_samples = BLOCK_BYTES / width;
+        let mut count = 0;
+        while count < n {
+            let want = cmp::min(n - count, block_samples) * width;
+            let mut filled = 0;
+            while filled < want {
+                match io::Read::read(&mut self.reader, &mut block[filled..want]) {
+                    Ok(0) => break,
+                    Ok(k) => filled += k,
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(Error::IoError(err)),
+                }
+            }
+
+            let mut src = &block[..filled - filled % width];
+            for slot in &mut buf[count..count + filled / width] {
+                *slot = try!(read_sample(&mut src, format_tag, data.spec_ex));
+            }
+            count += filled / width;
+
+            if filled < want {
+                // For a data chunk with a placeholder size, the data ends at
+                // the end of the stream, as long as that is not inside a sample.
+                if !self.reader.data_len_unknown || filled % width != 0 {
+                    return Err(Error::Truncated);
+                }
+                let state = self.reader.data_state.as_mut().expect("Not in the data chunk.");
+                state.chunk.len -= state.chunk.remaining;
+                state.chunk.remaining = 0;
+                self.reader.data_len_unknown = false;
+                break;
+            }
+        }
+        try!(self.reader.skip_partial_frame().map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => Error::Truncated,
+            _ => Error::IoError(err),
+        }));
+        if self.reader.data_state.map_or(false, |data| data.chunk.remaining == 0) {
+            try!(self.reader.verify_data_crc());
+        }
+        Ok(count)
     }
-}
 
-impl WavReader<io::BufReader<fs::File>> {
-    /// Attempts to create a reader that reads from the specified file.
+    /// Replaces the contents of `buf` with up to `frames` frames, and returns the number of frames read.
     ///
-    /// This is a convenience constructor that opens a `File`, wraps it in a
-    /// `BufReader` and then constructs a `WavReader` from it.
-    pub fn open<P: AsRef<path::Path>>(filename: P) -> Result<WavReader<io::BufReader<fs::File>>> {
-        let file = try!(fs::File::open(filename));
-        let buf_reader = io::BufReader::new(file);
-        WavReader::new(buf_reader)
+    /// The buffer is cleared first, and its capacity is reused, so once it is
+    /// large enough, no allocations are made. This fits audio callbacks that
+    /// process a fixed number of frames at a time. Fewer frames are read only
+    /// at the end of the data, and 0 is returned once all frames have been
+    /// read. A partial frame at the end of the data chunk is ignored, but a
+    /// stream with a placeholder size can still end in the middle of a frame,
+    /// and then `Error::UnfinishedSample` is returned. On error, `buf` holds
+    /// the samples that were read before the error.
+    pub fn refill<S: Sample>(&mut self, buf: &mut Vec<S>, frames: usize) -> Result<usize> {
+        let channels = self.spec().channels as usize;
+        buf.clear();
+        buf.reserve(frames * channels);
+        let mut samples = self.samples::<S>();
+        for frame in 0..frames {
+            for channel in 0..channels {
+                match samples.next() {
+                    Some(sample) => buf.push(try!(sample)),
+                    None if channel == 0 => return Ok(frame),
+                    None => return Err(Error::UnfinishedSample {
+                        samples: samples.sample_position() as u64,
+                        channels: channels as u16,
+                    }),
+                }
+            }
+        }
+        Ok(frames)
     }
-}
 
-fn iter_next<R, S>(reader: &mut ChunksReader<R>) -> Option<Result<S>>
-    where R: io::Read,
-          S: Sample
-{
-    let data = reader.data_state.expect("reader not in data chunk");
-    if data.chunk.remaining > 0 {
-        let sample = Sample::read(reader,
-                                  data.spec_ex.spec.sample_format,
-                                  data.spec_ex.bytes_per_sample,
-                                  data.spec_ex.spec.bits_per_sample);
-        Some(sample.map_err(Error::from))
-    } else {
-        None
+    /// Reads all remaining samples into one vector per channel.
+    ///
+    /// The outer vector has `spec().channels` elements; when no samples have
+    /// been read yet, every channel has `duration()` samples. A partial frame
+    /// at the end of the data chunk is ignored, as by the sample iterators.
+    pub fn read_planar<S: Sample>(&mut self) -> Result<Vec<Vec<S>>> {
+        let channels = self.spec().channels as usize;
+        let len_unknown = self.reader.data_len_unknown;
+        let num_samples = if len_unknown { 0 } else { self.samples::<S>().len() };
+        if num_samples % channels != 0 {
+            return Err(Error::UnfinishedSample {
+                samples: num_samples as u64,
+                channels: channels as u16,
+            });
+        }
+        let num_frames = num_samples / channels;
+        let max_frames = self.reader.options.max_preallocate
+                       / cmp::max(1, mem::size_of::<S>() * channels);
+        let capacity = cmp::min(num_frames, max_frames);
+
+        let mut planar: Vec<Vec<S>> = (0..channels).map(|_| Vec::with_capacity(capacity)).collect();
+        let mut samples = self.samples::<S>();
+        let mut frames = 0;
+        'frames: while len_unknown || frames < num_frames {
+            frames += 1;
+            for (i, channel) in planar.iter_mut().enumerate() {
+                match samples.next() {
+                    Some(sample) => channel.push(try!(sample)),
+                    // Without a known length, the end is found when reached.
+                    None if i == 0 && len_unknown => break 'frames,
+                    None => return Err(Error::UnfinishedSample {
+                        samples: samples.sample_position() as u64,
+                        channels: channels as u16,
+                    }),
+                }
+            }
+        }
+        Ok(planar)
+    }
+
+    /// Destroys the `WavReader` and returns its remaining samples split by channel.
+    ///
+    /// This is the consuming counterpart of `read_planar()`: channel 0, the
+    /// left channel of a stereo file, comes first.
+    pub fn into_channels<S: Sample>(mut self) -> Result<Vec<Vec<S>>> {
+        self.read_planar()
+    }
+
+    /// Reads all remaining samples, and checks them against the valid bits per sample.
+    ///
+    /// A file can declare fewer valid bits per sample than the size of the
+    /// container a sample is stored in, for instance 24 valid bits in a 4-byte
+    /// container. The decoding iterators ignore the extra bits, this method
+    /// instead reads the entire container, and reports the range of values
+    /// observed, and whether any value falls outside of the range of the valid
+    /// bits. Only integer samples of up to 32 bits can be checked.
+    pub fn validate_sample_range(&mut self) -> Result<ValidationReport> {
+        let data = self.reader.data_state.expect("Not in the data chunk.");
+        let spec = data.spec_ex.spec;
+        if !self.reader.can_decode() {
+            return Err(Error::Unsupported);
+        }
+        if spec.sample_format != SampleFormat::Int {
+            return Err(Error::InvalidSampleFormat);
+        }
+        let width = data.spec_ex.bytes_per_sample as usize;
+        if width > 4 {
+            return Err(Error::TooWide);
+        }
+
+        let valid_min = -(1_i64 << (spec.bits_per_sample - 1));
+        let valid_max = (1_i64 << (spec.bits_per_sample - 1)) - 1;
+        let mut report = ValidationReport {
+            num_samples: 0,
+            min: 0,
+            max: 0,
+            exceeds_valid_bits: false,
+        };
+        let mut buffer = [0u8; 4];
+        while self.reader.data_state.expect("Not in the data chunk.").chunk.remaining > 0 {
+            match self.reader.read_into(&mut buffer[..width]) {
+                Ok(()) => {}
+                Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(Error::Truncated);
+                }
+                Err(err) => return Err(Error::from(err)),
+            }
+            // Sign-extend the little-endian container. 8-bit samples are
+            // stored unsigned.
+            let mut x = 0_u32;
+            for i in 0..width {
+                x |= (buffer[i] as u32) << (8 * i);
+            }
+            let shift = 32 - 8 * width as u32;
+            let value = if width == 1 {
+                signed_from_u8(buffer[0]) as i32
+            } else {
+                ((x << shift) as i32) >> shift
+            };
+
+            if report.num_samples == 0 || value < report.min {
+                report.min = value;
+            }
+            if report.num_samples == 0 || value > report.max {
+                report.max = value;
+            }
+            if (value as i64) < valid_min || (value as i64) > valid_max {
+                report.exceeds_valid_bits = true;
+            }
+            report.num_samples += 1;
+        }
+        Ok(report)
+    }
+
+    /// Reads all remaining samples, and returns the peak absolute value per channel.
+    ///
+    /// The result has one element for every channel. Samples of any integer
+    /// bit depth are widened to `i32`; the magnitude of the most negative
+    /// 32-bit value saturates to `i32::max_value()`. Float samples cannot be
+    /// read as `i32`, for them `Error::InvalidSampleFormat` is returned.
+    pub fn channel_peaks(&mut self) -> Result<Vec<i32>> {
+        let channels = self.spec().channels as usize;
+        let mut peaks = vec![0_i32; channels];
+        for (i, sample) in self.samples::<i32>().enumerate() {
+            let x = try!(sample);
+            let magnitude = if x == i32::min_value() { i32::max_value() } else { x.abs() };
+            let peak = &mut peaks[i % channels];
+            *peak = cmp::max(*peak, magnitude);
+        }
+        Ok(peaks)
+    }
+
+    /// Reads all remaining samples, and returns the mean value per channel.
+    ///
+    /// The result has one element for every channel, in the units of the
+    /// samples: integer samples are not normalized, and float samples are
+    /// returned as they are. The sums are accumulated in `f64`, so they do not
+    /// overflow. A nonzero mean indicates a DC offset. The mean of a channel
+    /// without samples is 0.0.
+    pub fn dc_offset(&mut self) -> Result<Vec<f64>> {
+        let spec = self.spec();
+        let channels = spec.channels as usize;
+        let mut sums = vec![0.0_f64; channels];
+        let mut counts = vec![0_u64; channels];
+        match spec.sample_format {
+            SampleFormat::Int => for (i, sample) in self.samples::<i32>().enumerate() {
+                sums[i % channels] += try!(sample) as f64;
+                counts[i % channels] += 1;
+            },
+            SampleFormat::Float => for (i, sample) in self.samples::<f32>().enumerate() {
+                sums[i % channels] += try!(sample) as f64;
+                counts[i % channels] += 1;
+            },
+        }
+        Ok(sums.iter().zip(counts.iter()).map(|(&sum, &n)| {
+            if n == 0 { 0.0 } else { sum / n as f64 }
+        }).collect())
+    }
+
+    /// Returns an iterator over all samples that continues after errors.
+    ///
+    /// This is like `samples()`, and errors are yielded as they occur, but
+    /// after an error the reader skips to the start of the next frame, rather
+    /// than attempting to decode the rest of the failed frame. One corrupt
+    /// frame then does not affect the frames after it, and channels stay
+    /// aligned. The iterator stops when the end of the data chunk cannot be
+    /// reached, for instance because the stream was truncated.
+    pub fn samples_resync<'wr, S: Sample>(&'wr mut self) -> ResyncSamples<'wr, R, S> {
+        let _data_state = self.reader.data_state.expect("Not in the data chunk.");
+        ResyncSamples {
+            reader: &mut self.reader,
+            stopped: false,
+            phantom_sample: marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator over all samples, amplified by `gain_db` decibels.
+    ///
+    /// Every sample is multiplied by the linear gain `10^(gain_db / 20)`, as
+    /// `f64`. The result saturates at the bounds of `S`: at high gains, integer
+    /// samples clip to the minimum and maximum values of `S`. Note that these
+    /// are the bounds of the type, not of the bits per sample of the file, so
+    /// a 16-bit file read as `i32` can exceed the 16-bit range. Float samples
+    /// are not clipped to [-1.0, 1.0].
+    pub fn samples_with_gain<'wr, S: GainSample>(&'wr mut self, gain_db: f64) -> GainSamples<'wr, R, S> {
+        GainSamples {
+            samples: self.samples(),
+            gain: 10.0_f64.powf(gain_db / 20.0),
+        }
+    }
+
+    /// Returns an iterator over all samples, with the frames in reverse order.
+    ///
+    /// The iterator starts at the last whole frame of the data chunk, and ends
+    /// with the first frame; samples of an incomplete frame at the end are
+    /// skipped. Within a frame, the channels are in the usual order. So for a
+    /// stereo file with frames `(l0, r0), (l1, r1)`, this yields `l1, r1, l0,
+    /// r0`. All frames are yielded, independent of how many samples have been
+    /// read already. Afterwards, the position of the reader is unspecified;
+    /// use `seek()` to continue reading in forward order.
+    ///
+    /// The frames are read in blocks, seeking backwards one block at a time.
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn samples_rev<'wr, S: Sample + Copy>(&'wr mut self) -> ReverseSamples<'wr, R, S>
+        where R: io::Seek,
+    {
+        // If the stream length cannot be determined, the placeholder length
+        // is used, and reading the first block reports the error.
+        let _ = self.reader.resolve_unknown_data_len();
+        let data = self.reader.data_state.expect("Not in the data chunk.");
+        let channels = data.spec_ex.spec.channels as usize;
+        let frame_len = data.spec_ex.bytes_per_sample as u64 * channels as u64;
+        ReverseSamples {
+            reader: &mut self.reader,
+            channels: channels,
+            frames_left: data.chunk.len / frame_len,
+            block: Vec::new(),
+            block_frames: 0,
+            channel: 0,
+        }
+    }
+
+    /// Returns an iterator over the samples between two times in seconds.
+    ///
+    /// The reader seeks to the frame at `start_s` and the iterator stops
+    /// before the frame at `end_s`, so it yields whole frames of interleaved
+    /// samples. Times are converted to frames using the sample rate, rounding
+    /// down. Both times are clamped to the duration of the file, and if
+    /// `end_s` does not exceed `start_s` the iterator is empty.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn samples_between<'wr, S: Sample>(&'wr mut self, start_s: f64, end_s: f64)
+                                           -> io::Result<iter::Take<WavSamples<'wr, R, S>>>
+        where R: io::Seek,
+    {
+        let duration = self.duration_u64();
+        let sample_rate = self.sample_rate() as f64;
+        let to_frame = |t: f64| {
+            let frame = (t * sample_rate).floor();
+            // Also catches NaN, which compares false to everything.
+            if !(frame > 0.0) {
+                0
+            } else if frame >= duration as f64 {
+                duration
+            } else {
+                frame as u64
+            }
+        };
+        let start = to_frame(start_s);
+        let end = cmp::max(start, to_frame(end_s));
+        try!(self.seek_u64(start));
+        let n = (end - start) * self.channels() as u64;
+        Ok(self.samples().take(n as usize))
+    }
+
+    /// Converts the reader into a source of 16-bit samples for audio playback.
+    ///
+    /// Integer samples of any width are scaled to 16 bits, so a full-scale
+    /// sample remains full-scale: 8-bit samples are shifted left, and samples
+    /// wider than 16 bits are shifted right, which discards their least
+    /// significant bits. Float samples in the range -1.0 to 1.0 are scaled to
+    /// the full range of `i16`, and values outside of that range are clamped.
+    /// See `WavSource` for how errors are reported.
+    pub fn into_source(self) -> WavSource<R> {
+        let spec = self.spec();
+        WavSource {
+            reader: self.reader,
+            spec: spec,
+            error: None,
+        }
+    }
+
+    /// Returns an iterator over all samples, coerced to `S`, which may lose precision.
+    ///
+    /// Unlike `samples()`, this never fails with `Error::TooWide`. Samples
+    /// that fit in `S` are yielded unchanged, exactly as `samples()` would.
+    /// Samples that are wider than `S` are shifted right to fit, discarding
+    /// their least significant bits: when reading a 24-bit file as `i16`, the
+    /// lowest 8 bits of every sample are lost. This is a lossy conversion,
+    /// use `samples()` to read samples without loss of precision.
+    ///
+    /// Only integer samples can be coerced, float samples result in
+    /// `Error::InvalidSampleFormat`.
+    pub fn samples_as<'wr, S: CoerceSample>(&'wr mut self) -> CoercedSamples<'wr, R, S> {
+        let bits_per_sample = self.bits_per_sample();
+        CoercedSamples {
+            samples: self.samples(),
+            bits_per_sample: bits_per_sample,
+            phantom_sample: marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator over all samples, normalized to `f32` in the range -1.0 to 1.0.
+    ///
+    /// Integer samples are divided by the full-scale value for the bits per
+    /// sample, 2 to the power `bits_per_sample - 1`, so for a 16-bit file
+    /// they are divided by 32768. The most negative value maps to exactly
+    /// -1.0, and the most positive value to slightly less than 1.0. Float
+    /// samples are yielded unchanged, so they can exceed that range. For a
+    /// format that hound cannot decode, the iterator yields
+    /// `Error::Unsupported`.
+    pub fn samples_normalized<'wr>(&'wr mut self) -> NormalizedSamples<'wr, R> {
+        let spec = self.spec();
+        let full_scale = match spec.sample_format {
+            SampleFormat::Int => Some(int_full_scale(spec.bits_per_sample)),
+            SampleFormat::Float => None,
+        };
+        NormalizedSamples {
+            reader: &mut self.reader,
+            full_scale: full_scale,
+        }
+    }
+
+    /// Returns an iterator over all samples, paired with their time in seconds.
+    ///
+    /// The time of a sample is the index of its frame divided by the sample
+    /// rate, so all channels of a frame have the same time. The time is
+    /// computed from the frame index for every sample, rather than
+    /// accumulated, so it does not lose precision in long files.
+    pub fn samples_timed<'wr, S: Sample>(&'wr mut self) -> TimedSamples<'wr, R, S> {
+        let sample_rate = self.sample_rate() as f64;
+        TimedSamples {
+            samples: self.samples(),
+            sample_rate: sample_rate,
+        }
+    }
+
+    /// Reads all remaining samples, and calls `f` once for every frame.
+    ///
+    /// A frame consists of one sample per channel, so `f` receives a slice of
+    /// `spec().channels` samples. The slice points into a buffer that is
+    /// reused for every frame, so no allocation happens per frame.
+    ///
+    /// Decoding stops at the first error, which is then returned. A partial
+    /// frame at the end of the data chunk is ignored, but if a stream with a
+    /// placeholder size ends in the middle of a frame,
+    /// `Error::UnfinishedSample` is returned after all complete frames have
+    /// been passed to `f`.
+    pub fn for_each_frame<S: Sample, F: FnMut(&[S])>(self, mut f: F) -> Result<()> {
+        let channels = self.spec().channels as usize;
+        let mut frame = Vec::with_capacity(channels);
+        let mut num_samples = 0;
+        for sample in self.into_samples::<S>() {
+            frame.push(try!(sample));
+            num_samples += 1;
+            if frame.len() == channels {
+                f(&frame);
+                frame.clear();
+            }
+        }
+        if !frame.is_empty() {
+            return Err(Error::UnfinishedSample {
+                samples: num_samples,
+                channels: channels as u16,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the samples of this file followed by the samples of `other`.
+    ///
+    /// The specs of both files must be compatible (see
+    /// `WavSpec::compatible_with()`), otherwise `Error::IncompatibleSpec` is
+    /// returned up front. The resulting iterator yields all samples of this
+    /// file, and then continues seamlessly with the samples of `other`. Its
+    /// `len()` is the combined number of samples left in both files.
+    pub fn chain<R2, S>(self, other: WavReader<R2>) -> Result<ChainedSamples<R, R2, S>>
+        where R2: io::Read,
+              S: Sample
+    {
+        if !self.spec().compatible_with(&other.spec()) {
+            return Err(Error::IncompatibleSpec);
+        }
+        Ok(ChainedSamples {
+            first: self.into_samples(),
+            second: other.into_samples(),
+        })
+    }
+
+    /// Returns the duration of the file in samples.
+    ///
+    /// The duration is independent of the number of channels. It is expressed
+    /// in units of samples. The duration in seconds can be obtained by
+    /// dividing this number by the sample rate. The duration is independent of
+    /// how many samples have been read already.
+    ///
+    /// An RF64 file can have a duration that does not fit in a `u32`, use
+    /// `duration_u64()` for such files.
+    pub fn duration(&self) -> u32 {
+        self.duration_u64() as u32
+    }
+
+    /// Returns the duration of the file in samples, as a `u64`.
+    ///
+    /// This is like `duration()`, but it does not overflow for large RF64 files.
+    pub fn duration_u64(&self) -> u64 {
+        let data = self.reader.data_state.expect("Not in the data chunk.");
+        self.len_u64() / data.spec_ex.spec.channels as u64
+    }
+
+    /// Returns the number of values that the sample iterator will yield.
+    ///
+    /// The length of the file is its duration (in samples) times the number of
+    /// channels. The length is independent of how many samples have been read
+    /// already. To get the number of samples left, use `len()` on the
+    /// `samples()` iterator.
+    ///
+    /// An RF64 file can have more samples than fit in a `u32`, use `len_u64()`
+    /// for such files.
+    pub fn len(&self) -> u32 {
+        self.len_u64() as u32
+    }
+
+    /// Returns the number of values that the sample iterator will yield, as a `u64`.
+    ///
+    /// This is like `len()`, but it does not overflow for large RF64 files.
+    pub fn len_u64(&self) -> u64 {
+        let data = self.reader.data_state.expect("not in the data chunk");
+        (data.chunk.len - data.partial_frame_len()) / data.spec_ex.bytes_per_sample as u64
+    }
+
+    /// Returns the number of values that the sample iterator will yield, if it is known.
+    ///
+    /// This is `None` when the data chunk has the placeholder size 0xffffffff
+    /// that is used by live streams, meaning that the data continues until
+    /// the end of the file. For such files, `len()` returns a meaningless
+    /// value based on the placeholder, the sample iterators have a size hint
+    /// of `(0, None)`, so their `ExactSizeIterator::len()` must not be used,
+    /// and they simply stop at the end of the stream. If the reader
+    /// implements `Seek`, the actual length can be determined with
+    /// `resolve_unknown_len()`. The `new_seekable()`, `open()`,
+    /// `from_bytes()` and `new_at()` constructors do this automatically, and
+    /// so does seeking.
+    pub fn try_len(&self) -> Option<u32> {
+        if self.reader.data_len_unknown {
+            None
+        } else {
+            Some(self.len())
+        }
+    }
+
+    /// Determines the length of a data chunk with the placeholder size 0xffffffff.
+    ///
+    /// The data is assumed to extend to the end of the stream. See also
+    /// `try_len()`. For other files this does nothing.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn resolve_unknown_len(&mut self) -> io::Result<()>
+        where R: io::Seek,
+    {
+        self.reader.resolve_unknown_data_len()
+    }
+
+    /// Limits the reported length to the whole frames that are present in the stream.
+    ///
+    /// If the data chunk claims to be larger than the rest of the stream,
+    /// `len()` and `duration()` are reduced accordingly, and the sample
+    /// iterators end at the last whole frame, rather than failing with
+    /// `Error::Truncated`. The `new_seekable()`, `open()`, `from_bytes()` and
+    /// `new_at()` constructors do this automatically, so that `len()` never
+    /// reports samples that are not there; this method is for readers created
+    /// with `new()`. Without `Seek`, there is no way to know the stream length
+    /// up front, and reading past the end returns `Error::Truncated`.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn clamp_len_to_stream(&mut self) -> io::Result<()>
+        where R: io::Seek,
+    {
+        self.reader.clamp_data_len_to_stream()
+    }
+
+    /// Destroys the `WavReader` and returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Seek to the given time within the file.
+    ///
+    /// The given time is measured in number of samples (independent of the
+    /// number of channels) since the beginning of the audio data. To seek to
+    /// a particular time in seconds, multiply the number of seconds with
+    /// `WavSpec::sample_rate`. Seeking beyond the duration of the file
+    /// (returned by `duration()`) positions the reader at the end of the
+    /// audio data, so no samples remain.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn seek(&mut self, time: u32) -> io::Result<()>
+        where R: io::Seek,
+    {
+        self.reader.seek(time)
+    }
+
+    /// Seek to the given time within the file, which may exceed `u32::MAX`.
+    ///
+    /// This is like `seek()`, but it can reach every frame of an RF64 file,
+    /// whose duration is given by `duration_u64()`.
+    ///
+    /// This method requires that the inner reader `R` implements `Seek`.
+    pub fn seek_u64(&mut self, time: u64) -> io::Result<()>
+        where R: io::Seek,
+    {
+        self.reader.seek_u64(time)
     }
 }
 
-fn iter_size_hint<R: io::Read>(reader: &ChunksReader<R>) -> (usize, Option<usize>) {
-    let data = reader.data_state.expect("reader not in data chunk");
-    let samples_left = data.chunk.remaining as usize / data.spec_ex.bytes_per_sample as usize;
-    (samples_left, Some(samples_left))
+/// Returns whether hound can read samples with the bits per sample of `spec`.
+///
+/// Apart from the formats that hound can decode, those of
+/// `RAW_ONLY_READ_FORMATS` are accepted, so that their data can still be read
+/// with `raw_data()`.
+fn bits_supported(spec: &WavSpec) -> bool {
+    let key = (spec.sample_format, spec.bits_per_sample);
+    SUPPORTED_FORMATS.contains(&key) || RAW_ONLY_READ_FORMATS.contains(&key)
 }
 
-impl<'wr, R, S> Iterator for WavSamples<'wr, R, S>
-    where R: io::Read,
-          S: Sample
-{
-    type Item = Result<S>;
+/// Reads the spec and the duration (in frames) of a WAVE file.
+///
+/// This is a cheap way to index many files: only the header is read, up to
+/// the start of the data chunk, after which the file is closed again. The
+/// duration is determined from the size of the data chunk, as for
+/// `WavReader::duration()`. For formats that hound cannot decode, the number
+/// of samples per channel from the `fact` chunk is used if it is present.
+pub fn probe<P: AsRef<path::Path>>(filename: P) -> Result<(WavSpec, u32)> {
+    let file = try!(fs::File::open(filename));
+    let mut reader = try!(ChunksReader::new(io::BufReader::new(file)));
+    reader.keep_fmt_extra = false;
+    if !try!(reader.read_until_data()) {
+        return Err(Error::FormatError("no data chunk found"));
+    }
+    let data = reader.data_state.expect("data chunk was found");
+    let spec = data.spec_ex.spec;
+    let duration = match reader.fact_samples {
+        Some(n) if !reader.can_decode() => n,
+        _ => {
+            let frame_len = data.spec_ex.bytes_per_sample as u64 * spec.channels as u64;
+            (data.chunk.len / frame_len) as u32
+        }
+    };
+    Ok((spec, duration))
+}
 
-    fn next(&mut self) -> Option<Result<S>> {
-        iter_next(&mut self.reader)
+impl WavReader<io::BufReader<fs::File>> {
+    /// Attempts to create a reader that reads from the specified file.
+    ///
+    /// This is a convenience constructor that opens a `File`, wraps it in a
+    /// `BufReader` and then constructs a `WavReader` from it.
+    pub fn open<P: AsRef<path::Path>>(filename: P) -> Result<WavReader<io::BufReader<fs::File>>> {
+        let file = try!(fs::File::open(filename));
+        let buf_reader = io::BufReader::new(file);
+        WavReader::new_seekable(buf_reader)
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        iter_size_hint(&self.reader)
+    /// Attempts to create a reader that reads from the specified file, with a buffer of the given capacity.
+    ///
+    /// This is like `open()`, but the `BufReader` holds up to `capacity` bytes
+    /// rather than the default of the standard library. A larger buffer uses
+    /// more memory, but it means fewer `read` calls on the file, which pays
+    /// off on storage where every call is expensive, such as a network file
+    /// system. Seeking discards the buffer, so for random access a large
+    /// buffer can be wasteful.
+    pub fn open_with_buffer_size<P: AsRef<path::Path>>(filename: P, capacity: usize)
+                                                       -> Result<WavReader<io::BufReader<fs::File>>> {
+        let file = try!(fs::File::open(filename));
+        let buf_reader = io::BufReader::with_capacity(capacity, file);
+        WavReader::new_seekable(buf_reader)
     }
 }
 
-impl<'wr, R, S> ExactSizeIterator for WavSamples<'wr, R, S>
+/// A reader that reads a sequence of files as if they were concatenated.
+///
+/// This is the underlying reader of a `WavReader` created by
+/// [`WavReader::open_split()`](struct.WavReader.html#method.open_split).
+pub struct SplitFiles {
+    /// the file that is currently being read
+    current: io::BufReader<fs::File>,
+    /// the files that follow the current one, in reverse order
+    rest: Vec<fs::File>,
+}
+
+impl io::Read for SplitFiles {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = try!(self.current.read(buf));
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            match self.rest.pop() {
+                Some(file) => self.current = io::BufReader::new(file),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+impl WavReader<SplitFiles> {
+    /// Attempts to create a reader that reads a WAVE file split over multiple files.
+    ///
+    /// Some recorders span a long recording over a set of files, such as
+    /// `file.wav`, `file.w01`, `file.w02`, where only the first file has a
+    /// header, and the data chunk continues in the next file, as if the files
+    /// were concatenated. The header, and thus the spec, is read from the
+    /// first file.
+    ///
+    /// The data chunk length in the header often describes only the part in
+    /// the first file. When the header claims no more than what the first file
+    /// contains, the data is assumed to extend to the end of the last file.
+    /// Otherwise the length of the header is used, if the files are not
+    /// shorter than that.
+    pub fn open_split(paths: &[path::PathBuf]) -> Result<WavReader<SplitFiles>> {
+        let (first, others) = match paths.split_first() {
+            Some(split) => split,
+            None => return Err(Error::IoError(io::Error::new(io::ErrorKind::InvalidInput,
+                                                             "no files to open"))),
+        };
+        let first_file = try!(fs::File::open(first));
+        let first_len = try!(first_file.metadata()).len();
+        let mut rest = Vec::with_capacity(others.len());
+        let mut rest_len = 0;
+        for path in others {
+            let file = try!(fs::File::open(path));
+            rest_len += try!(file.metadata()).len();
+            rest.push(file);
+        }
+        rest.reverse();
+
+        let split_files = SplitFiles {
+            current: io::BufReader::new(first_file),
+            rest: rest,
+        };
+        let mut reader = try!(WavReader::new(split_files));
+        {
+            let chunks = &mut reader.reader;
+            let data_offset = chunks.data_offset.expect("Not in the data chunk.");
+            let first_part = first_len.saturating_sub(data_offset);
+            let total = first_part + rest_len;
+            let data = chunks.data_state.as_mut().expect("Not in the data chunk.");
+            let len = if data.chunk.len <= first_part {
+                total
+            } else {
+                cmp::min(data.chunk.len, total)
+            };
+            data.chunk = ChunkReadingState { len: len, remaining: len };
+        }
+        Ok(reader)
+    }
+}
+
+impl<'a> WavReader<io::Cursor<&'a [u8]>> {
+    /// Creates a reader for a WAVE file that is in memory, such as an embedded asset.
+    ///
+    /// The bytes are borrowed, not copied. They are wrapped in a `Cursor`, so
+    /// the reader can seek, and like for `open()`, a data chunk with the
+    /// placeholder size 0xffffffff extends to the end of the bytes. To borrow
+    /// 16-bit samples without decoding them, see `as_i16_slice()` on a reader
+    /// created with `WavReader::new()` on the slice itself.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<WavReader<io::Cursor<&'a [u8]>>> {
+        WavReader::new_seekable(io::Cursor::new(bytes))
+    }
+}
+
+impl<'a> WavReader<&'a [u8]> {
+    /// Returns the remaining samples as a slice that borrows the input directly.
+    ///
+    /// On a little-endian host, the data chunk of a 16-bit integer PCM file is
+    /// identical in memory to a slice of `i16`, so it can be used without
+    /// copying or decoding. The channel data is interleaved. The slice starts
+    /// at the current position of the reader; it covers the entire data chunk
+    /// if no samples have been read yet.
+    ///
+    /// Returns `None` if the host is big-endian, the file does not contain
+    /// 16-bit integer samples, the data is not aligned to a 2-byte boundary,
+    /// or the data chunk extends beyond the end of the input.
+    pub fn as_i16_slice(&self) -> Option<&'a [i16]> {
+        if cfg!(target_endian = "big") {
+            return None;
+        }
+        let data = match self.reader.data_state {
+            Some(data) => data,
+            None => return None,
+        };
+        let spec = data.spec_ex.spec;
+        if !self.reader.can_decode()
+            || self.reader.wave_list.is_some()
+            || spec.sample_format != SampleFormat::Int
+            || spec.bits_per_sample != 16
+            || data.spec_ex.bytes_per_sample != 2 {
+            return None;
+        }
+
+        let bytes: &'a [u8] = self.reader.reader;
+        if (bytes.len() as u64) < data.chunk.remaining {
+            return None;
+        }
+        if bytes.as_ptr() as usize % mem::align_of::<i16>() != 0 {
+            return None;
+        }
+
+        let num_samples = data.remaining_frames_len() as usize / 2;
+        // This is safe because the pointer is aligned, the slice is in bounds
+        // and borrowed for 'a, and every bit pattern is a valid i16. The byte
+        // order matches because the host is little-endian.
+        Some(unsafe { slice::from_raw_parts(bytes.as_ptr() as *const i16, num_samples) })
+    }
+}
+
+/// Expands a G.711 A-law codeword into a 16-bit linear sample.
+fn alaw_to_linear(codeword: u8) -> i16 {
+    let x = codeword ^ 0x55;
+    let exponent = (x >> 4) & 0x07;
+    let mantissa = ((x & 0x0f) as i16) << 4;
+    let magnitude = match exponent {
+        0 => mantissa + 0x08,
+        e => (mantissa + 0x108) << (e - 1),
+    };
+    if x & 0x80 != 0 { magnitude } else { -magnitude }
+}
+
+/// Expands a G.711 μ-law codeword into a 16-bit linear sample.
+fn mulaw_to_linear(codeword: u8) -> i16 {
+    let x = !codeword;
+    let exponent = (x >> 4) & 0x07;
+    let mantissa = (x & 0x0f) as i16;
+    // The bias of 0x84 makes the segments line up, without it the magnitude
+    // might overflow an i16.
+    let magnitude = ((mantissa << 3) + 0x84) << exponent;
+    if x & 0x80 != 0 { 0x84 - magnitude } else { magnitude - 0x84 }
+}
+
+/// Reads a single sample in the encoding indicated by the format tag.
+///
+/// A-law and μ-law codewords are expanded to 16-bit integers first, and then
+/// converted to `S` like any other 16-bit sample.
+fn read_sample<R, S>(reader: &mut R, format_tag: u16, spec_ex: WavSpecEx) -> Result<S>
     where R: io::Read,
           S: Sample
 {
+    let linear = match format_tag {
+        0x0006 => alaw_to_linear(try!(reader.read_u8())),
+        0x0007 => mulaw_to_linear(try!(reader.read_u8())),
+        _ => return S::read(reader,
+                            spec_ex.spec.sample_format,
+                            spec_ex.bytes_per_sample,
+                            spec_ex.spec.bits_per_sample),
+    };
+    let bytes = [linear as u8, (linear >> 8) as u8];
+    S::read(&mut &bytes[..], SampleFormat::Int, 2, 16)
 }
 
-impl<R, S> Iterator for WavIntoSamples<R, S>
+fn iter_next<R, S>(reader: &mut ChunksReader<R>) -> Option<Result<S>>
     where R: io::Read,
           S: Sample
 {
-    type Item = Result<S>;
-
-    fn next(&mut self) -> Option<Result<S>> {
-        iter_next(&mut self.reader)
+    if let Err(err) = reader.skip_partial_frame() {
+        return Some(Err(match err.kind() {
+            io::ErrorKind::UnexpectedEof => Error::Truncated,
+            _ => Error::IoError(err),
+        }));
     }
-
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        iter_size_hint(&self.reader)
+    let data = reader.data_state.expect("reader not in data chunk");
+    if data.chunk.remaining > 0 {
+        if !reader.can_decode() {
+            return Some(Err(Error::Unsupported));
+        }
+        let format_tag = reader.format_tag;
+        let sample = read_sample(reader, format_tag, data.spec_ex);
+        // For a data chunk with a placeholder size, reaching the end of the
+        // stream before a sample means that the data ends there.
+        if reader.data_len_unknown {
+            let state = reader.data_state.as_mut().expect("reader not in data chunk");
+            let at_eof = match sample {
+                Err(Error::IoError(ref io_err)) => io_err.kind() == io::ErrorKind::UnexpectedEof,
+                _ => false,
+            };
+            if at_eof && state.chunk.remaining == data.chunk.remaining {
+                state.chunk.len -= state.chunk.remaining;
+                state.chunk.remaining = 0;
+                reader.data_len_unknown = false;
+                return reader.verify_data_crc().err().map(Err);
+            }
+        }
+        // The data chunk announced more samples, so running out of input
+        // means that the stream was cut off. The data ends there, so the
+        // error is returned once, and then the iterator ends.
+        Some(sample.map_err(|err| match err {
+            Error::IoError(ref io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => {
+                let state = reader.data_state.as_mut().expect("reader not in data chunk");
+                state.chunk.len -= state.chunk.remaining;
+                state.chunk.remaining = 0;
+                reader.data_crc = None;
+                Error::Truncated
+            }
+            err => err,
+        }))
+    } else {
+        reader.verify_data_crc().err().map(Err)
+    }
+}
+
+fn iter_size_hint<R: io::Read>(reader: &ChunksReader<R>) -> (usize, Option<usize>) {
+    // With a placeholder size, the samples continue up to the end of the
+    // stream, which is not known in advance.
+    if reader.data_len_unknown {
+        return (0, None);
+    }
+    let data = reader.data_state.expect("reader not in data chunk");
+    let samples_left = data.remaining_frames_len() as usize / data.spec_ex.bytes_per_sample as usize;
+    (samples_left, Some(samples_left))
+}
+
+fn iter_sample_position<R: io::Read>(reader: &ChunksReader<R>) -> u32 {
+    let data = reader.data_state.expect("reader not in data chunk");
+    let bytes_read = data.chunk.len - data.chunk.remaining;
+    (bytes_read / data.spec_ex.bytes_per_sample as u64) as u32
+}
+
+fn iter_frame_position<R: io::Read>(reader: &ChunksReader<R>) -> u32 {
+    let data = reader.data_state.expect("reader not in data chunk");
+    iter_sample_position(reader) / data.spec_ex.spec.channels as u32
+}
+
+impl<'wr, R, S> WavSamples<'wr, R, S>
+    where R: io::Read
+{
+    /// Returns the index of the sample that the next call to `next()` yields.
+    ///
+    /// The index counts individual samples of all channels since the start of
+    /// the data chunk. This accounts for any `seek()` that happened before the
+    /// iterator was created.
+    pub fn sample_position(&self) -> u32 {
+        iter_sample_position(&self.reader)
+    }
+
+    /// Returns the index of the frame that the next sample belongs to.
+    ///
+    /// A frame contains one sample for every channel, so this is
+    /// `sample_position()` divided by the number of channels.
+    pub fn frame_position(&self) -> u32 {
+        iter_frame_position(&self.reader)
+    }
+}
+
+impl<R, S> WavIntoSamples<R, S>
+    where R: io::Read
+{
+    /// Returns the index of the sample that the next call to `next()` yields.
+    ///
+    /// See `WavSamples::sample_position()` for more info.
+    pub fn sample_position(&self) -> u32 {
+        iter_sample_position(&self.reader)
+    }
+
+    /// Returns the index of the frame that the next sample belongs to.
+    ///
+    /// See `WavSamples::frame_position()` for more info.
+    pub fn frame_position(&self) -> u32 {
+        iter_frame_position(&self.reader)
+    }
+}
+
+impl<'wr, R, S> Iterator for WavSamples<'wr, R, S>
+    where R: io::Read,
+          S: Sample
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        iter_next(&mut self.reader)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        iter_size_hint(&self.reader)
+    }
+}
+
+impl<'wr, R, S> Iterator for CoercedSamples<'wr, R, S>
+    where R: io::Read,
+          S: CoerceSample
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        let bits = self.bits_per_sample;
+        self.samples.next().map(|sample| sample.map(|s| S::coerce_from(s, bits)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl<'wr, R, S> ExactSizeIterator for CoercedSamples<'wr, R, S>
+    where R: io::Read,
+          S: CoerceSample
+{}
+
+impl<'wr, R> Iterator for NormalizedSamples<'wr, R>
+    where R: io::Read
+{
+    type Item = Result<f32>;
+
+    fn next(&mut self) -> Option<Result<f32>> {
+        match self.full_scale {
+            Some(full_scale) => {
+                iter_next::<R, i32>(self.reader)
+                    .map(|sample| sample.map(|s| (s as f64 / full_scale) as f32))
+            }
+            None => iter_next(self.reader),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        iter_size_hint(self.reader)
+    }
+}
+
+impl<'wr, R> ExactSizeIterator for NormalizedSamples<'wr, R>
+    where R: io::Read
+{}
+
+impl<'wr, R, S> ReverseSamples<'wr, R, S>
+    where R: io::Read + io::Seek,
+          S: Sample + Copy
+{
+    /// Reads the block of frames that precedes the frames read so far.
+    fn read_block(&mut self) -> Result<()> {
+        let num_frames = cmp::min(self.frames_left, 1024);
+        let start = self.frames_left - num_frames;
+        try!(self.reader.seek_u64(start));
+        self.block.clear();
+        for _ in 0..num_frames as usize * self.channels {
+            match iter_next(self.reader) {
+                Some(sample) => self.block.push(try!(sample)),
+                None => return Err(Error::Truncated),
+            }
+        }
+        self.frames_left = start;
+        self.block_frames = num_frames as usize;
+        Ok(())
+    }
+}
+
+impl<'wr, R, S> Iterator for ReverseSamples<'wr, R, S>
+    where R: io::Read + io::Seek,
+          S: Sample + Copy
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        if self.block_frames == 0 {
+            if self.frames_left == 0 {
+                return None;
+            }
+            if let Err(err) = self.read_block() {
+                // Do not try again, the error would most likely repeat.
+                self.frames_left = 0;
+                self.block_frames = 0;
+                return Some(Err(err));
+            }
+        }
+        let sample = self.block[(self.block_frames - 1) * self.channels + self.channel];
+        self.channel += 1;
+        if self.channel == self.channels {
+            self.channel = 0;
+            self.block_frames -= 1;
+        }
+        Some(Ok(sample))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let frames = self.frames_left as usize + self.block_frames;
+        let len = frames * self.channels - self.channel;
+        (len, Some(len))
+    }
+}
+
+impl<'wr, R, S> ExactSizeIterator for ReverseSamples<'wr, R, S>
+    where R: io::Read + io::Seek,
+          S: Sample + Copy
+{}
+
+impl<R: io::Read> WavSource<R> {
+    /// Returns the number of channels, samples are interleaved.
+    pub fn channels(&self) -> u16 {
+        self.spec.channels
+    }
+
+    /// Returns the number of frames per second.
+    pub fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    /// Returns the error that ended iteration, if there was one.
+    ///
+    /// When the iterator returns `None`, all samples have been read if and
+    /// only if this returns `None` too.
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+}
+
+impl<R: io::Read> Iterator for WavSource<R> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.error.is_some() {
+            return None;
+        }
+        let sample = match self.spec.sample_format {
+            SampleFormat::Int => {
+                let bits = self.spec.bits_per_sample;
+                iter_next::<R, i32>(&mut self.reader).map(|r| r.map(|x| {
+                    scale_int(x, bits, 16) as i16
+                }))
+            }
+            SampleFormat::Float => {
+                iter_next::<R, f32>(&mut self.reader).map(|r| r.map(|x| {
+                    (x.max(-1.0).min(1.0) * 32767.0) as i16
+                }))
+            }
+        };
+        match sample {
+            Some(Ok(x)) => Some(x),
+            Some(Err(err)) => {
+                self.error = Some(err);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.error.is_some() {
+            (0, Some(0))
+        } else {
+            // An error can end iteration early, so the length is an upper bound.
+            (0, iter_size_hint(&self.reader).1)
+        }
+    }
+}
+
+impl<'wr, R, S> Iterator for TimedSamples<'wr, R, S>
+    where R: io::Read,
+          S: Sample
+{
+    type Item = Result<(f64, S)>;
+
+    fn next(&mut self) -> Option<Result<(f64, S)>> {
+        let time = self.samples.frame_position() as f64 / self.sample_rate;
+        self.samples.next().map(|sample| sample.map(|s| (time, s)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl<'wr, R, S> ExactSizeIterator for TimedSamples<'wr, R, S>
+    where R: io::Read,
+          S: Sample
+{}
+
+impl<'wr, R, S> Iterator for GainSamples<'wr, R, S>
+    where R: io::Read,
+          S: GainSample
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        let gain = self.gain;
+        self.samples.next().map(|sample| sample.map(|s| s.apply_gain(gain)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl<'wr, R, S> ExactSizeIterator for GainSamples<'wr, R, S>
+    where R: io::Read,
+          S: GainSample
+{}
+
+impl<'wr, R, S> ExactSizeIterator for WavSamples<'wr, R, S>
+    where R: io::Read,
+          S: Sample
+{
+}
+
+impl<R, S> Iterator for WavIntoSamples<R, S>
+    where R: io::Read,
+          S: Sample
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        iter_next(&mut self.reader)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        iter_size_hint(&self.reader)
+    }
+}
+
+impl<R, S> ExactSizeIterator for WavIntoSamples<R, S>
+    where R: io::Read,
+          S: Sample
+{
+}
+
+impl<'wr, R, S> Iterator for ResyncSamples<'wr, R, S>
+    where R: io::Read,
+          S: Sample
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        if self.stopped {
+            return None;
+        }
+        let data = self.reader.data_state.expect("reader not in data chunk");
+        let sample_start = data.chunk.len - data.chunk.remaining;
+        let result = iter_next(&mut self.reader);
+        if let Some(Err(..)) = result {
+            // Skip to the first frame that starts after the failed sample.
+            let frame_len = data.spec_ex.bytes_per_sample as u64
+                          * data.spec_ex.spec.channels as u64;
+            let next_frame = cmp::min((sample_start / frame_len + 1) * frame_len,
+                                      data.chunk.len);
+            let data = self.reader.data_state.expect("reader not in data chunk");
+            let position = data.chunk.len - data.chunk.remaining;
+            if next_frame > position {
+                let n = next_frame - position;
+                let skipped = io::copy(&mut io::Read::take(&mut *self.reader, n),
+                                       &mut io::sink());
+                match skipped {
+                    Ok(k) if k == n => {}
+                    _ => self.stopped = true,
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<R1, R2, S> Iterator for ChainedSamples<R1, R2, S>
+    where R1: io::Read,
+          R2: io::Read,
+          S: Sample
+{
+    type Item = Result<S>;
+
+    fn next(&mut self) -> Option<Result<S>> {
+        match self.first.next() {
+            Some(sample) => Some(sample),
+            None => self.second.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (first, first_upper) = self.first.size_hint();
+        let (second, second_upper) = self.second.size_hint();
+        let upper = match (first_upper, second_upper) {
+            (Some(a), Some(b)) => Some(a + b),
+            _ => None,
+        };
+        (first + second, upper)
+    }
+}
+
+impl<R1, R2, S> ExactSizeIterator for ChainedSamples<R1, R2, S>
+    where R1: io::Read,
+          R2: io::Read,
+          S: Sample
+{
+}
+
+/// Mixes two files, by summing their samples.
+///
+/// The specs of both files must be compatible (see
+/// `WavSpec::compatible_with()`), otherwise `Error::IncompatibleSpec` is
+/// returned up front. The files must contain integer samples. The iterator
+/// yields the sum of every pair of samples as an `i32`, without scaling, so
+/// for files of up to 24 bits per sample the sum cannot overflow, but it may
+/// exceed the range of the bits per sample; reducing the level is left to
+/// the caller. For 32-bit files, a sum that does not fit in an `i32` yields
+/// `Error::TooWide`. If one file is shorter than the other, it is padded with
+/// zeros.
+pub fn mix<R1, R2>(a: WavReader<R1>, b: WavReader<R2>) -> Result<MixedSamples<R1, R2>>
+    where R1: io::Read,
+          R2: io::Read
+{
+    if !a.spec().compatible_with(&b.spec()) {
+        return Err(Error::IncompatibleSpec);
+    }
+    Ok(MixedSamples {
+        a: a.into_samples(),
+        b: b.into_samples(),
+    })
+}
+
+impl<R1, R2> Iterator for MixedSamples<R1, R2>
+    where R1: io::Read,
+          R2: io::Read
+{
+    type Item = Result<i32>;
+
+    fn next(&mut self) -> Option<Result<i32>> {
+        match (self.a.next(), self.b.next()) {
+            (None, None) => None,
+            (Some(x), None) => Some(x),
+            (None, Some(y)) => Some(y),
+            (Some(x), Some(y)) => Some(x.and_then(|x| y.and_then(|y| {
+                x.checked_add(y).ok_or(Error::TooWide)
+            }))),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, a_upper) = self.a.size_hint();
+        let (b, b_upper) = self.b.size_hint();
+        let upper = match (a_upper, b_upper) {
+            (Some(a), Some(b)) => Some(cmp::max(a, b)),
+            _ => None,
+        };
+        (cmp::max(a, b), upper)
+    }
+}
+
+impl<R1, R2> ExactSizeIterator for MixedSamples<R1, R2>
+    where R1: io::Read,
+          R2: io::Read
+{
+}
+
+/// Returns the body of a `fmt ` chunk with the 16 bytes of a `PCMWAVEFORMAT`.
+///
+/// The byte rate follows from the sample rate and the block align. Fields of
+/// an extension can be appended to the result.
+#[cfg(test)]
+fn fmt_chunk(format_tag: u16, channels: u16, sample_rate: u32, block_align: u16,
+             bits_per_sample: u16) -> Vec<u8> {
+    let mut fmt = Vec::with_capacity(16);
+    fmt.write_le_u16(format_tag).unwrap();
+    fmt.write_le_u16(channels).unwrap();
+    fmt.write_le_u32(sample_rate).unwrap();
+    fmt.write_le_u32(sample_rate.wrapping_mul(block_align as u32)).unwrap();
+    fmt.write_le_u16(block_align).unwrap();
+    fmt.write_le_u16(bits_per_sample).unwrap();
+    fmt
+}
+
+/// Builds a WAVE file that consists of the given chunks, in order.
+///
+/// Every chunk gets a header with the length of its body, and a padding byte
+/// if that length is odd; the RIFF size covers all of them. Tests for
+/// malformed files start from the result, and then break it on purpose.
+#[cfg(test)]
+fn wave_file(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+    for &(id, body) in chunks {
+        bytes.extend_from_slice(id);
+        bytes.write_le_u32(body.len() as u32).unwrap();
+        bytes.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            bytes.push(0);
+        }
+    }
+    let riff_len = bytes.len() as u32 - 8;
+    (&mut bytes[4..8]).write_le_u32(riff_len).unwrap();
+    bytes
+}
+
+#[test]
+fn duration_and_len_agree() {
+    let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
+                  "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav"];
+
+    for fname in files {
+        let reader = WavReader::open(fname).unwrap();
+        assert_eq!(reader.spec().channels as u32 * reader.duration(),
+                   reader.len());
+    }
+}
+
+#[test]
+fn read_spec_only_equals_spec() {
+    let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
+                  "testsamples/waveformatex-16bit-44100Hz-mono-extra.wav",
+                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav",
+                  "testsamples/nonstandard-01.wav"];
+
+    for fname in files {
+        let mut file = fs::File::open(fname).unwrap();
+        let spec = WavReader::read_spec_only(&mut file).unwrap();
+        assert_eq!(spec, WavReader::open(fname).unwrap().spec());
+    }
+}
+
+/// Tests reading a wave file with the PCMWAVEFORMAT struct.
+#[test]
+fn read_wav_pcm_wave_format_pcm() {
+    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().sample_rate, 44100);
+    assert_eq!(wav_reader.spec().bits_per_sample, 16);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i16> = wav_reader.samples()
+        .map(|r| r.unwrap())
+        .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[2, -3, 5, -7]);
+}
+
+#[test]
+fn read_wav_skips_unknown_chunks() {
+    // The test samples are the same as without the -extra suffix, but ffmpeg
+    // has kindly added some useless chunks in between the fmt and data chunk.
+    let files = ["testsamples/pcmwaveformat-16bit-44100Hz-mono-extra.wav",
+                 "testsamples/waveformatex-16bit-44100Hz-mono-extra.wav"];
+
+    for file in &files {
+        let mut wav_reader = WavReader::open(file).unwrap();
+
+        assert_eq!(wav_reader.spec().channels, 1);
+        assert_eq!(wav_reader.spec().sample_rate, 44100);
+        assert_eq!(wav_reader.spec().bits_per_sample, 16);
+        assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+        let sample = wav_reader.samples::<i16>().next().unwrap().unwrap();
+        assert_eq!(sample, 2);
+    }
+}
+
+#[test]
+fn read_skips_odd_sized_list_chunk_before_data() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(5_i16).unwrap();
+    writer.write_sample(-6_i16).unwrap();
+    let wav = writer.finalize_into_bytes().unwrap();
+
+    // An INFO list with an odd size, followed by a padding byte.
+    let mut bytes = wav[..72].to_vec();
+    bytes.extend_from_slice(b"LIST");
+    bytes.extend_from_slice(&[17, 0, 0, 0]);
+    bytes.extend_from_slice(b"INFOINAM");
+    bytes.extend_from_slice(&[5, 0, 0, 0]);
+    bytes.extend_from_slice(b"junk\0\0");
+    bytes.extend_from_slice(&wav[72..]);
+
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.spec(), spec);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [5, -6]);
+
+    // A data chunk without a preceding fmt chunk is an error.
+    let mut bytes = wav[..12].to_vec();
+    bytes.extend_from_slice(&wav[72..]);
+    bytes.extend_from_slice(&wav[48..72]);
+    match WavReader::new(&bytes[..]) {
+        Err(Error::FormatError("missing fmt chunk")) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
+}
+
+#[test]
+fn read_wav_0_valid_bits_fallback() {
+    let mut wav_reader = WavReader::open("testsamples/nonstandard-02.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 2);
+    assert_eq!(wav_reader.spec().sample_rate, 48000);
+    assert_eq!(wav_reader.spec().bits_per_sample, 32);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+        .map(|r| r.unwrap())
+        .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[19, -229373, 33587161, -2147483497]);
+}
+
+#[test]
+fn len_and_size_hint_are_correct() {
+    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.len(), 4);
+
+    {
+        let mut samples = wav_reader.samples::<i16>();
+
+        assert_eq!(samples.size_hint(), (4, Some(4)));
+        samples.next();
+        assert_eq!(samples.size_hint(), (3, Some(3)));
+    }
+
+    // Reading should not affect the initial length.
+    assert_eq!(wav_reader.len(), 4);
+
+    // Creating a new iterator resumes where the previous iterator stopped.
+    {
+        let mut samples = wav_reader.samples::<i16>();
+
+        assert_eq!(samples.size_hint(), (3, Some(3)));
+        samples.next();
+        assert_eq!(samples.size_hint(), (2, Some(2)));
+    }
+}
+
+#[test]
+fn len_and_duration_match_samples_read() {
+    let mut reader = WavReader::open("testsamples/pop.wav").unwrap();
+    let channels = reader.spec().channels as u32;
+    let len = reader.len();
+    assert_eq!(reader.duration() * channels, len);
+    assert_eq!(reader.samples::<i16>().count() as u32, len);
+
+    let mut reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap();
+    assert_eq!(reader.duration() * 2, reader.len());
+    assert_eq!(reader.samples::<i16>().count() as u32, reader.len());
+}
+
+#[test]
+fn size_hint_is_exact() {
+    let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
+                  "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav"];
+
+    for fname in files {
+        let mut reader = WavReader::open(fname).unwrap();
+        let len = reader.len();
+        let mut iter = reader.samples::<i32>();
+        for i in 0..len {
+            let remaining = (len - i) as usize;
+            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+            assert!(iter.next().is_some());
+        }
+        assert!(iter.next().is_none());
+
+        // The owning iterator reports the same lengths.
+        let mut iter = WavReader::open(fname).unwrap().into_samples::<i32>();
+        assert_eq!(iter.len(), len as usize);
+        iter.next().unwrap().unwrap();
+        assert_eq!(iter.size_hint(), (len as usize - 1, Some(len as usize - 1)));
+    }
+}
+
+#[test]
+fn chain_yields_samples_of_both_readers() {
+    let first = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap();
+    let second = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap();
+    let mut samples = first.chain::<_, i16>(second).unwrap();
+    assert_eq!(samples.len(), 16);
+    samples.next();
+    assert_eq!(samples.len(), 15);
+
+    let rest: Vec<i16> = samples.map(|r| r.unwrap()).collect();
+    assert_eq!(&rest[..], &[-3, 5, -7, 11, -13, 17, -19,
+                            2, -3, 5, -7, 11, -13, 17, -19]);
+}
+
+#[test]
+fn chain_rejects_incompatible_specs() {
+    let mono = WavReader::open("testsamples/waveformatex-16bit-44100Hz-mono.wav").unwrap();
+    let stereo = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap();
+    match mono.chain::<_, i16>(stereo) {
+        Err(Error::IncompatibleSpec) => {}
+        _ => panic!("IncompatibleSpec error should have been returned."),
+    }
+}
+
+#[test]
+fn samples_equals_into_samples() {
+    let wav_reader_val = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
+    let mut wav_reader_ref = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
+
+    let samples_val: Vec<i16> = wav_reader_val.into_samples()
+                                              .map(|r| r.unwrap())
+                                              .collect();
+
+    let samples_ref: Vec<i16> = wav_reader_ref.samples()
+                                              .map(|r| r.unwrap())
+                                              .collect();
+
+    assert_eq!(samples_val, samples_ref);
+}
+
+/// Tests reading a wave file with the WAVEFORMATEX struct.
+#[test]
+fn read_wav_wave_format_ex_pcm() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-mono.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().sample_rate, 44100);
+    assert_eq!(wav_reader.spec().bits_per_sample, 16);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i16> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[2, -3, 5, -7]);
+}
+
+#[test]
+fn read_wav_wave_format_ex_ieee_float() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().sample_rate, 44100);
+    assert_eq!(wav_reader.spec().bits_per_sample, 32);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Float);
+
+    let samples: Vec<f32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[2.0, 3.0, -16411.0, 1019.0]);
+}
+
+#[test]
+fn read_wav_stereo() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 2);
+    assert_eq!(wav_reader.spec().sample_rate, 44100);
+    assert_eq!(wav_reader.spec().bits_per_sample, 16);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i16> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact eight samples.
+    assert_eq!(&samples[..], &[2, -3, 5, -7, 11, -13, 17, -19]);
+
+}
+
+#[test]
+fn read_wav_pcm_wave_format_8bit() {
+    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
+                                   .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().bits_per_sample, 8);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i16> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[19, -53, 89, -127]);
+}
+
+/// Test reading 24 bit samples in a 4 byte container using the pcmwaveformat header. This is
+/// technically a non-compliant wave file, but it is the sort of file generated by
+/// 'arecord -f S24_LE -r 48000 -c 2 input.wav' so it should be supported.
+#[test]
+fn read_wav_pcm_wave_format_24bit_4byte() {
+    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-24bit-4byte-48kHz-stereo.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 2);
+    assert_eq!(wav_reader.spec().sample_rate, 48_000);
+    assert_eq!(wav_reader.spec().bits_per_sample, 24);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[-96, 23_052, 8_388_607, -8_360_672]);
+}
+
+/// Regression test for a real-world wav file encountered in Quake.
+#[test]
+fn read_wav_wave_format_ex_8bit() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatex-8bit-11025Hz-mono.wav").unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().bits_per_sample, 8);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The audio data has been zeroed out, but for 8-bit files, a zero means a
+    // sample value of 128.
+    assert_eq!(&samples[..], &[-128, -128, -128, -128]);
+}
+
+/// This test sample tests both reading the WAVEFORMATEXTENSIBLE header, and 24-bit samples.
+#[test]
+fn read_wav_wave_format_extensible_pcm_24bit() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().sample_rate, 192_000);
+    assert_eq!(wav_reader.spec().bits_per_sample, 24);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[-17, 4_194_319, -6_291_437, 8_355_817]);
+}
+
+/// This test sample tests both reading the WAVEFORMATEXTENSIBLE header, and 24-bit samples with a
+/// 4 byte container size.
+#[test]
+fn read_wav_wave_format_extensible_pcm_24bit_4byte() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatextensible-24bit-4byte-48kHz-stereo.wav")
+        .unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 2);
+    assert_eq!(wav_reader.spec().sample_rate, 48_000);
+    assert_eq!(wav_reader.spec().bits_per_sample, 24);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[-96, 23_052, 8_388_607, -8_360_672]);
+}
+
+/// Tests a synthetic WAVEFORMATEXTENSIBLE header with more than two channels,
+/// and 24 valid bits in a 4 byte container.
+#[test]
+fn read_synthetic_extensible_24bit_in_32bit_container() {
+    use std::io::Cursor;
+
+    // Extensible, three channels at 48 kHz, 24 valid bits in 32.
+    let mut fmt = fmt_chunk(0xfffe, 3, 48_000, 12, 32);
+    fmt.extend_from_slice(&[22, 0, 24, 0]); // cbSize, valid bits.
+    fmt.extend_from_slice(&[0x07, 0, 0, 0]); // Left, right, center.
+    fmt.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_PCM);
+    // The sample is in the low three bytes, the top byte is ignored.
+    let data = [0xff, 0xff, 0x7f, 0x00,
+                0x00, 0x00, 0x80, 0xff,
+                0xfe, 0xff, 0xff, 0x00];
+    let bytes = wave_file(&[(b"fmt ", &fmt), (b"data", &data)]);
+
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.format_tag(), 0xfffe);
+    assert_eq!(reader.spec(), WavSpec {
+        channels: 3,
+        sample_rate: 48_000,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    });
+    assert_eq!(reader.len(), 3);
+    let samples: Vec<i32> = reader.samples().map(|r| r.unwrap()).collect();
+    assert_eq!(&samples[..], &[8_388_607, -8_388_608, -2]);
+}
+
+#[test]
+fn read_wav_32bit() {
+    let mut wav_reader = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav")
+                                   .unwrap();
+
+    assert_eq!(wav_reader.spec().bits_per_sample, 32);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[19, -229_373, 33_587_161, -2_147_483_497]);
+}
+
+#[test]
+fn read_wav_wave_format_extensible_ieee_float() {
+    let mut wav_reader =
+        WavReader::open("testsamples/waveformatextensible-ieeefloat-44100Hz-mono.wav").unwrap();
+
+    assert_eq!(wav_reader.spec().channels, 1);
+    assert_eq!(wav_reader.spec().sample_rate, 44100);
+    assert_eq!(wav_reader.spec().bits_per_sample, 32);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Float);
+
+    let samples: Vec<f32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    // The test file has been prepared with these exact four samples.
+    assert_eq!(&samples[..], &[2.0, 3.0, -16411.0, 1019.0]);
+}
+
+#[test]
+fn read_wav_nonstandard_01() {
+    // The test sample here is adapted from a file encountered in the wild (data
+    // chunk replaced with two zero samples, some metadata dropped, and the file
+    // length in the header fixed). It is not a valid file according to the
+    // standard, but many players can deal with it nonetheless. (The file even
+    // contains some metadata; open it in a hex editor if you would like to know
+    // which program created it.) The file contains a regular PCM format tag,
+    // but the size of the fmt chunk is one that would be expected of a
+    // WAVEFORMATEXTENSIBLE chunk. The bits per sample is 24, which is invalid
+    // for WAVEFORMATEX, but we can read it nonetheless.
+    let mut wav_reader = WavReader::open("testsamples/nonstandard-01.wav").unwrap();
+
+    assert_eq!(wav_reader.spec().bits_per_sample, 24);
+    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+
+    let samples: Vec<i32> = wav_reader.samples()
+                                      .map(|r| r.unwrap())
+                                      .collect();
+
+    assert_eq!(&samples[..], &[0, 0]);
+}
+
+#[test]
+fn wide_read_should_signal_error() {
+    let mut reader24 = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
+        .unwrap();
+
+    // Even though we know the first value is 17, and it should fit in an `i8`,
+    // a general 24-bit sample will not fit in an `i8`, so this should fail.
+    // 16-bit is still not wide enough, but 32-bit should do the trick.
+    assert!(reader24.samples::<i8>().next().unwrap().is_err());
+    assert!(reader24.samples::<i16>().next().unwrap().is_err());
+    assert!(reader24.samples::<i32>().next().unwrap().is_ok());
+    assert!(reader24.samples::<f32>().next().unwrap().is_ok());
+
+    let mut reader32 = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav")
+        .unwrap();
+
+    // In general, 32-bit samples will not fit in anything but an `i32`.
+    assert!(reader32.samples::<i8>().next().unwrap().is_err());
+    assert!(reader32.samples::<i16>().next().unwrap().is_err());
+    assert!(reader32.samples::<i32>().next().unwrap().is_ok());
+    assert!(reader32.samples::<f32>().next().unwrap().is_err());
+}
+
+#[test]
+fn sample_format_mismatch_should_signal_error() {
+    let mut reader_f32 = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav")
+        .unwrap();
+
+    assert!(reader_f32.samples::<i8>().next().unwrap().is_err());
+    assert!(reader_f32.samples::<i16>().next().unwrap().is_err());
+    assert!(reader_f32.samples::<i32>().next().unwrap().is_err());
+    assert!(reader_f32.samples::<f32>().next().unwrap().is_ok());
+
+    let mut reader_i8 = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
+
+    assert!(reader_i8.samples::<i8>().next().unwrap().is_ok());
+    assert!(reader_i8.samples::<i16>().next().unwrap().is_ok());
+    assert!(reader_i8.samples::<i32>().next().unwrap().is_ok());
+    assert!(reader_i8.samples::<f32>().next().unwrap().is_ok());
+}
+
+#[test]
+fn read_as_i32_should_equal_read_as_f32() {
+    let samples_i32: Vec<i32> = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
+        .unwrap()
+        .samples::<i32>()
+        .map(Result::unwrap)
+        .collect();
+    let samples_f32: Vec<f32> = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
+        .unwrap()
+        .samples::<f32>()
+        .map(Result::unwrap)
+        .collect();
+    for (&sample_i32, &sample_f32) in samples_i32.iter().zip(&samples_f32) {
+        assert_eq!(sample_i32, sample_f32 as i32);
+        assert_eq!(sample_i32 as f32, sample_f32);
+    }
+
+    let samples_i32: Vec<i32> = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
+        .unwrap()
+        .samples::<i32>()
+        .map(Result::unwrap)
+        .collect();
+    let samples_f32: Vec<f32> = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
+        .unwrap()
+        .samples::<f32>()
+        .map(Result::unwrap)
+        .collect();
+    for (&sample_i32, &sample_f32) in samples_i32.iter().zip(&samples_f32) {
+        assert_eq!(sample_i32, sample_f32 as i32);
+        assert_eq!(sample_i32 as f32, sample_f32);
+    }
+
+    let samples_i32: Vec<i32> = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
+        .unwrap()
+        .samples::<i32>()
+        .map(Result::unwrap)
+        .collect();
+    let samples_f32: Vec<f32> = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
+        .unwrap()
+        .samples::<f32>()
+        .map(Result::unwrap)
+        .collect();
+    for (&sample_i32, &sample_f32) in samples_i32.iter().zip(&samples_f32) {
+        assert_eq!(sample_i32, sample_f32 as i32);
+        assert_eq!(sample_i32 as f32, sample_f32);
+    }
+}
+
+#[test]
+fn fuzz_crashes_should_be_fixed() {
+    use std::fs;
+    use std::ffi::OsStr;
+
+    // This is a regression test: all crashes and other issues found through
+    // fuzzing should not cause a crash.
+    let dir = fs::read_dir("testsamples/fuzz").ok()
+                 .expect("failed to enumerate fuzz test corpus");
+    for path in dir {
+        let path = path.ok().expect("failed to obtain path info").path();
+        let is_file = fs::metadata(&path).unwrap().file_type().is_file();
+        if is_file && path.extension() == Some(OsStr::new("wav")) {
+            println!("    testing {} ...", path.to_str()
+                                               .expect("unsupported filename"));
+            let mut reader = match WavReader::open(path) {
+                Ok(r) => r,
+                Err(..) => continue,
+            };
+            match reader.spec().sample_format {
+                SampleFormat::Int => {
+                    for sample in reader.samples::<i32>() {
+                        match sample {
+                            Ok(..) => { }
+                            Err(..) => break,
+                        }
+                    }
+                }
+                SampleFormat::Float => {
+                    for sample in reader.samples::<f32>() {
+                        match sample {
+                            Ok(..) => { }
+                            Err(..) => break,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn seek_is_consistent() {
+    let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
+                  "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav"];
+    for fname in files {
+        let mut reader = WavReader::open(fname).unwrap();
+        // Seeking back to the start should "reset" the reader.
+        let count = reader.samples::<i32>().count();
+        reader.seek(0).unwrap();
+        assert_eq!(count, reader.samples::<i32>().count());
+
+        // Seek to the last sample.
+        let last_time = reader.duration() - 1;
+        let channels = reader.spec().channels;
+        reader.seek(last_time).unwrap();
+        {
+            let mut samples = reader.samples::<i32>();
+            for _ in 0..channels {
+                assert!(samples.next().is_some());
+            }
+            assert!(samples.next().is_none());
+        }
+
+        // Seeking beyond the audio data produces no samples.
+        let num_samples = reader.len();
+        reader.seek(num_samples).unwrap();
+        assert!(reader.samples::<i32>().next().is_none());
+        reader.seek(::std::u32::MAX / channels as u32).unwrap();
+        assert!(reader.samples::<i32>().next().is_none());
+        reader.seek_u64(::std::u64::MAX / 2).unwrap();
+        assert!(reader.samples::<i32>().next().is_none());
+        reader.seek_u64(::std::u64::MAX).unwrap();
+        assert!(reader.samples::<i32>().next().is_none());
+        assert_eq!(reader.len(), num_samples);
+
+        // After seeking to a frame, the samples are those of sequential reading.
+        reader.seek(0).unwrap();
+        let all: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+        for frame in 0..reader.duration() {
+            reader.seek(frame).unwrap();
+            let sample = reader.samples::<i32>().next().unwrap().unwrap();
+            assert_eq!(sample, all[(frame * channels as u32) as usize]);
+        }
+    }
+}
+
+#[test]
+fn fmt_extra_bytes_contains_extension() {
+    let reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    assert_eq!(reader.format_tag(), 0x0001);
+    assert_eq!(reader.fmt_extra_bytes(), &[]);
+
+    let reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-mono-extra.wav").unwrap();
+    assert_eq!(reader.format_tag(), 0x0001);
+    assert_eq!(reader.fmt_extra_bytes(), &[]);
+
+    // The extension of WAVEFORMATEXTENSIBLE is the number of valid bits, the
+    // channel mask, and the subformat GUID.
+    let reader = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav").unwrap();
+    assert_eq!(reader.format_tag(), 0xfffe);
+    let extra = reader.fmt_extra_bytes();
+    assert_eq!(extra.len(), 22);
+    assert_eq!(&extra[0..2], &[32, 0]);
+    assert_eq!(&extra[2..6], &[3, 0, 0, 0]);
+    assert_eq!(&extra[6..], &super::KSDATAFORMAT_SUBTYPE_PCM[..]);
+}
+
+#[test]
+fn extension_bytes_is_none_without_cb_size() {
+    let reader = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav").unwrap();
+    assert_eq!(reader.extension_bytes().map(|extra| extra.len()), Some(22));
+
+    // WAVEFORMATEX with a cbSize of 0.
+    let reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap();
+    assert_eq!(reader.extension_bytes(), Some(&[][..]));
+
+    let reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    assert_eq!(reader.fmt_extra_bytes(), &[]);
+    assert_eq!(reader.extension_bytes(), None);
+}
+
+#[test]
+fn for_each_frame_yields_all_frames() {
+    let fname = "testsamples/waveformatextensible-32bit-48kHz-stereo.wav";
+    let samples: Vec<i32> = WavReader::open(fname).unwrap()
+        .into_samples().map(|s| s.unwrap()).collect();
+
+    let mut frames = Vec::new();
+    WavReader::open(fname).unwrap().for_each_frame(|frame: &[i32]| {
+        assert_eq!(frame.len(), 2);
+        frames.extend_from_slice(frame);
+    }).unwrap();
+    assert_eq!(frames, samples);
+}
+
+#[test]
+fn samples_track_frame_position() {
+    let mut reader = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav")
+        .unwrap();
+    let num_samples = reader.len();
+    let mut samples = reader.samples::<i32>();
+    for i in 0..num_samples {
+        assert_eq!(samples.sample_position(), i);
+        assert_eq!(samples.frame_position(), i / 2);
+        samples.next().unwrap().unwrap();
+    }
+    assert_eq!(samples.sample_position(), num_samples);
+    assert!(samples.next().is_none());
+}
+
+#[test]
+fn read_truncated_stream_signals_truncated() {
+    use std::io::{Cursor, Read};
+
+    let mut bytes = Vec::new();
+    fs::File::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap()
+        .read_to_end(&mut bytes).unwrap();
+
+    // Cut the stream in the middle of the last sample.
+    let len = bytes.len();
+    let mut reader = WavReader::new(Cursor::new(&bytes[..len - 1])).unwrap();
+    let mut samples = reader.samples::<i16>();
+    for _ in 0..3 {
+        assert!(samples.next().unwrap().is_ok());
+    }
+    match samples.next().unwrap() {
+        Err(Error::Truncated) => {}
+        _ => panic!("expected Error::Truncated"),
     }
 }
 
-impl<R, S> ExactSizeIterator for WavIntoSamples<R, S>
-    where R: io::Read,
-          S: Sample
-{
+#[test]
+fn clamp_len_to_stream_ignores_missing_data() {
+    use std::io::Cursor;
+
+    // A stereo file that claims 40000 bytes of data, but contains only one
+    // frame, and half of the next one.
+    let mut data = vec![0; 40000];
+    data[..6].copy_from_slice(&[1, 0, 2, 0, 3, 0]);
+    let mut bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 2, 44100, 4, 16)), (b"data", &data)]);
+    bytes.truncate(44 + 6);
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.len(), 20000);
+    reader.clamp_len_to_stream().unwrap();
+    assert_eq!(reader.len(), 2);
+    assert_eq!(reader.duration(), 1);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 2]);
+
+    // The constructors for seekable readers clamp automatically.
+    let mut reader = WavReader::from_bytes(&bytes).unwrap();
+    assert_eq!(reader.len(), 2);
+    assert_eq!(reader.samples::<i16>().size_hint(), (2, Some(2)));
+    let reader = WavReader::new_at(Cursor::new(&bytes[..]), 0).unwrap();
+    assert_eq!(reader.len(), 2);
+
+    // Without seeking, the iterator ends with one error at the end of the stream.
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<_> = reader.samples::<i16>().collect();
+    assert_eq!(samples.len(), 4);
+    match samples[3] {
+        Err(Error::Truncated) => {}
+        _ => panic!("expected Error::Truncated"),
+    }
 }
 
 #[test]
-fn duration_and_len_agree() {
-    let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
-                  "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
-                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav"];
+fn read_ignores_partial_sample_in_odd_sized_data_chunk() {
+    use std::io::Cursor;
+
+    // Three 16-bit samples and one stray byte, then the padding byte, and an
+    // INFO list that must be found at the right offset.
+    let bytes = wave_file(&[
+        (b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)),
+        (b"data", &[1, 0, 2, 0, 3, 0, 0x7f]),
+        (b"LIST", b"INFOINAM\x00\0\0\0"),
+    ]);
+    assert_eq!(bytes.len(), 72);
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.len(), 3);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 2, 3]);
+    let mut chunks = reader.reader;
+    chunks.read_remaining_chunks().unwrap();
+    assert_eq!(chunks.metadata().title, Some(String::new()));
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.collect_samples::<i16>().unwrap(), [1, 2, 3]);
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    let mut buf = [0i16; 4];
+    assert_eq!(reader.read_into(&mut buf).unwrap(), 3);
+    assert_eq!(reader.read_into(&mut buf).unwrap(), 0);
+
+    // The padding byte after an odd-sized chunk that is consumed while
+    // reading the header is skipped too.
+    let bytes = wave_file(&[
+        (b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)),
+        (b"fact", &[3, 0, 0, 0, 0]),
+        (b"data", &[1, 0, 2, 0, 3, 0]),
+    ]);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.len(), 3);
+    assert_eq!(reader.collect_samples::<i16>().unwrap(), [1, 2, 3]);
+
+    // In stereo, a whole sample that does not complete a frame is ignored too.
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    writer.write_all(vec![1_i16, 2, 3, 4, 5]).unwrap();
+    assert!(writer.finalize_into_bytes().is_err());
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    writer.write_all(vec![1_i16, 2, 3, 4]).unwrap();
+    let mut bytes = writer.finalize_into_bytes().unwrap();
+    bytes.extend_from_slice(&[5, 0]);
+    bytes[4] += 2;
+    bytes[76] += 2;
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.len(), 4);
+    assert_eq!(reader.duration(), 2);
+    assert_eq!(reader.samples::<i16>().len(), 4);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 2, 3, 4]);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.collect_samples::<i16>().unwrap(), [1, 2, 3, 4]);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    let mut buf = [0i16; 6];
+    assert_eq!(reader.read_into(&mut buf).unwrap(), 4);
+    assert_eq!(reader.read_into(&mut buf).unwrap(), 0);
+}
 
-    for fname in files {
-        let reader = WavReader::open(fname).unwrap();
-        assert_eq!(reader.spec().channels as u32 * reader.duration(),
-                   reader.len());
+#[test]
+fn read_opaque_payload_of_unknown_format() {
+    use std::io::Cursor;
+
+    let payload = [0x7f, 0xfe, 0x80, 0x01, 0xfc, 0x3c, 0x00, 0x11];
+    // Format tag 0x2001 is DTS.
+    let bytes = wave_file(&[(b"fmt ", &fmt_chunk(0x2001, 2, 48_000, 4, 16)), (b"data", &payload)]);
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.format_tag(), 0x2001);
+    match reader.samples::<i16>().next() {
+        Some(Err(Error::Unsupported)) => {}
+        _ => panic!("samples of an unknown format should not be decoded"),
     }
+    assert_eq!(&reader.raw_data().unwrap()[..], &payload[..]);
 }
 
-/// Tests reading a wave file with the PCMWAVEFORMAT struct.
 #[test]
-fn read_wav_pcm_wave_format_pcm() {
-    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
-        .unwrap();
-
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().sample_rate, 44100);
-    assert_eq!(wav_reader.spec().bits_per_sample, 16);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+fn read_g711_expands_to_16_bit_samples() {
+    use std::io::Cursor;
 
-    let samples: Vec<i16> = wav_reader.samples()
-        .map(|r| r.unwrap())
-        .collect();
+    fn g711_file(format_tag: u16, codewords: &[u8]) -> Vec<u8> {
+        wave_file(&[(b"fmt ", &fmt_chunk(format_tag, 1, 8000, 1, 8)), (b"data", codewords)])
+    }
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[2, -3, 5, -7]);
+    // Reference values are those of the G.711 reference implementation.
+    let mulaw = [0x00, 0x0f, 0x10, 0x7e, 0x7f, 0x80, 0x8f, 0xc3, 0xfe, 0xff];
+    let expected = [-32124, -16764, -15996, -8, 0, 32124, 16764, 1692, 8, 0];
+    let bytes = g711_file(7, &mulaw);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.spec().bits_per_sample, 16);
+    assert_eq!(reader.len(), 10);
+    let samples: Vec<i16> = reader.samples().map(|r| r.unwrap()).collect();
+    assert_eq!(&samples[..], &expected[..]);
+
+    let alaw = [0x00, 0x0f, 0x10, 0x55, 0x54, 0xd5, 0x80, 0xaa, 0x7f, 0xff];
+    let expected = [-5504, -6784, -2752, -8, -24, 8, 5504, 32256, -848, 848];
+    let bytes = g711_file(6, &alaw);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    let mut samples = [0i16; 10];
+    assert_eq!(reader.read_into(&mut samples[..]).unwrap(), 10);
+    assert_eq!(&samples[..], &expected[..]);
+
+    // Like any other 16-bit sample, they do not fit in an i8.
+    let bytes = g711_file(7, &mulaw);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    match reader.samples::<i8>().next() {
+        Some(Err(Error::TooWide)) => {}
+        _ => panic!("expanded samples should be too wide for i8"),
+    }
 }
 
 #[test]
-fn read_wav_skips_unknown_chunks() {
-    // The test samples are the same as without the -extra suffix, but ffmpeg
-    // has kindly added some useless chunks in between the fmt and data chunk.
-    let files = ["testsamples/pcmwaveformat-16bit-44100Hz-mono-extra.wav",
-                 "testsamples/waveformatex-16bit-44100Hz-mono-extra.wav"];
-
-    for file in &files {
-        let mut wav_reader = WavReader::open(file).unwrap();
-
-        assert_eq!(wav_reader.spec().channels, 1);
-        assert_eq!(wav_reader.spec().sample_rate, 44100);
-        assert_eq!(wav_reader.spec().bits_per_sample, 16);
-        assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
-
-        let sample = wav_reader.samples::<i16>().next().unwrap().unwrap();
-        assert_eq!(sample, 2);
+fn read_info_list_ignores_unknown_sub_chunks() {
+    use std::io::Cursor;
+
+    let mut list = Vec::new();
+    list.extend_from_slice(b"INFO");
+    list.extend_from_slice(b"ICRD\x0b\0\0\02026-10-14\0\0");
+    list.extend_from_slice(b"ICMT\x02\0\0\0hi"); // No null terminator.
+    list.extend_from_slice(b"IART\x09\0\0\0Somebody"); // Truncated.
+
+    let bytes = wave_file(&[
+        (b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)),
+        (b"LIST", &list),
+        (b"data", &[7, 0]),
+    ]);
+
+    for &preserve in &[false, true] {
+        let options = ReadOptions { preserve_chunks: preserve, ..ReadOptions::default() };
+        let mut reader = WavReader::new_with_options(Cursor::new(&bytes[..]), options).unwrap();
+        let metadata = reader.metadata().clone();
+        assert_eq!(metadata.comment, Some("hi".to_string()));
+        assert_eq!(metadata.title, None);
+        assert_eq!(metadata.artist, None);
+        assert_eq!(reader.samples::<i16>().next().unwrap().unwrap(), 7);
+        assert_eq!(reader.preserved_chunks().len(), if preserve { 3 } else { 0 });
     }
 }
 
 #[test]
-fn read_wav_0_valid_bits_fallback() {
-    let mut wav_reader = WavReader::open("testsamples/nonstandard-02.wav")
-        .unwrap();
-
-    assert_eq!(wav_reader.spec().channels, 2);
-    assert_eq!(wav_reader.spec().sample_rate, 48000);
-    assert_eq!(wav_reader.spec().bits_per_sample, 32);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
-
-    let samples: Vec<i32> = wav_reader.samples()
-        .map(|r| r.unwrap())
-        .collect();
+fn read_rejects_fmt_chunk_over_max_metadata_bytes() {
+    let fname = "testsamples/waveformatextensible-32bit-48kHz-stereo.wav";
+    let options = ReadOptions { max_metadata_bytes: 39, ..ReadOptions::default() };
+    match WavReader::new_with_options(fs::File::open(fname).unwrap(), options) {
+        Err(Error::FormatError(_)) => {}
+        _ => panic!("a 40-byte fmt chunk should exceed the limit"),
+    }
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[19, -229373, 33587161, -2147483497]);
+    let options = ReadOptions { max_metadata_bytes: 40, ..ReadOptions::default() };
+    assert!(WavReader::new_with_options(fs::File::open(fname).unwrap(), options).is_ok());
 }
 
 #[test]
-fn len_and_size_hint_are_correct() {
-    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
-        .unwrap();
-
-    assert_eq!(wav_reader.len(), 4);
+fn raw_data_is_not_limited_by_max_preallocate() {
+    let fname = "testsamples/pcmwaveformat-16bit-44100Hz-mono.wav";
+    let options = ReadOptions { max_preallocate: 1, ..ReadOptions::default() };
+    let mut reader = WavReader::new_with_options(fs::File::open(fname).unwrap(), options).unwrap();
+    assert_eq!(reader.raw_data().unwrap().len(), 8);
+}
 
-    {
-        let mut samples = wav_reader.samples::<i16>();
+#[test]
+fn from_bytes_reads_writer_output() {
+    use write::WavWriter;
 
-        assert_eq!(samples.size_hint(), (4, Some(4)));
-        samples.next();
-        assert_eq!(samples.size_hint(), (3, Some(3)));
+    let spec = WavSpec::new_pcm(2, 22050, 24);
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for i in 0..8_i32 {
+        writer.write_sample(i * 100_000 - 400_000).unwrap();
     }
+    let bytes = writer.finalize_into_bytes().unwrap();
 
-    // Reading should not affect the initial length.
-    assert_eq!(wav_reader.len(), 4);
+    let mut reader = WavReader::from_bytes(&bytes).unwrap();
+    assert_eq!(reader.spec(), spec);
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, (0..8).map(|i| i * 100_000 - 400_000).collect::<Vec<i32>>());
 
-    // Creating a new iterator resumes where the previous iterator stopped.
-    {
-        let mut samples = wav_reader.samples::<i16>();
+    // The reader can seek within the borrowed bytes.
+    reader.seek(3).unwrap();
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [200_000, 300_000]);
+}
 
-        assert_eq!(samples.size_hint(), (3, Some(3)));
-        samples.next();
-        assert_eq!(samples.size_hint(), (2, Some(2)));
+#[test]
+fn as_i16_slice_equals_samples() {
+    use std::io::Read;
+
+    let fname = "testsamples/waveformatex-16bit-44100Hz-stereo.wav";
+    let mut bytes = Vec::new();
+    fs::File::open(fname).unwrap().read_to_end(&mut bytes).unwrap();
+    let samples: Vec<i16> = WavReader::open(fname).unwrap()
+        .into_samples().map(|s| s.unwrap()).collect();
+
+    let reader = WavReader::new(&bytes[..]).unwrap();
+    if cfg!(target_endian = "little") {
+        assert_eq!(reader.as_i16_slice(), Some(&samples[..]));
+    } else {
+        assert_eq!(reader.as_i16_slice(), None);
     }
+
+    // Other formats cannot be viewed as i16.
+    let mut bytes = Vec::new();
+    fs::File::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav").unwrap()
+        .read_to_end(&mut bytes).unwrap();
+    let reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.as_i16_slice(), None);
 }
 
 #[test]
-fn size_hint_is_exact() {
+fn probe_equals_spec_and_duration() {
     let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
                   "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
-                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav"];
+                  "testsamples/waveformatextensible-24bit-4byte-48kHz-stereo.wav",
+                  "testsamples/nonstandard-02.wav"];
 
     for fname in files {
-        let mut reader = WavReader::open(fname).unwrap();
-        let len = reader.len();
-        let mut iter = reader.samples::<i32>();
-        for i in 0..len {
-            let remaining = (len - i) as usize;
-            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
-            assert!(iter.next().is_some());
-        }
-        assert!(iter.next().is_none());
+        let reader = WavReader::open(fname).unwrap();
+        assert_eq!(probe(fname).unwrap(), (reader.spec(), reader.duration()));
     }
 }
 
 #[test]
-fn samples_equals_into_samples() {
-    let wav_reader_val = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
-    let mut wav_reader_ref = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
-
-    let samples_val: Vec<i16> = wav_reader_val.into_samples()
-                                              .map(|r| r.unwrap())
-                                              .collect();
-
-    let samples_ref: Vec<i16> = wav_reader_ref.samples()
-                                              .map(|r| r.unwrap())
-                                              .collect();
+fn read_skips_extra_bytes_in_fact_chunk() {
+    use std::io::Cursor;
+
+    let bytes = wave_file(&[
+        (b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)),
+        (b"fact", &[2, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef]),
+        (b"data", &[1, 0, 0xff, 0xff]),
+    ]);
+
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, -1]);
+}
 
-    assert_eq!(samples_val, samples_ref);
+#[test]
+fn samples_resync_skips_to_next_frame_after_error() {
+    use std::io::{Cursor, Read};
+
+    // A stereo file of 16-bit samples, decoded as i8 for which all samples
+    // are too wide. There should be one error for every frame.
+    let mut bytes = Vec::new();
+    fs::File::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap()
+        .read_to_end(&mut bytes).unwrap();
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    let duration = reader.duration() as usize;
+    let results: Vec<_> = reader.samples_resync::<i8>().collect();
+    assert_eq!(results.len(), duration);
+    assert!(results.iter().all(|r| r.is_err()));
+
+    // When decoding succeeds, all samples are yielded.
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    let len = reader.len() as usize;
+    let samples: Vec<i16> = reader.samples_resync().map(|s| s.unwrap()).collect();
+    assert_eq!(samples.len(), len);
 }
 
-/// Tests reading a wave file with the WAVEFORMATEX struct.
 #[test]
-fn read_wav_wave_format_ex_pcm() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-mono.wav")
+fn spec_accessors_equal_spec() {
+    let mut reader = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
         .unwrap();
+    let spec = reader.spec();
+    // The spec is returned by value, so it does not borrow the reader.
+    let _ = reader.samples::<i32>().next();
+    assert_eq!(reader.channels(), spec.channels);
+    assert_eq!(reader.sample_rate(), spec.sample_rate);
+    assert_eq!(reader.bits_per_sample(), spec.bits_per_sample);
+}
 
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().sample_rate, 44100);
-    assert_eq!(wav_reader.spec().bits_per_sample, 16);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+#[test]
+fn read_pcm_with_18_byte_fmt_chunk() {
+    use std::io::Cursor;
 
-    let samples: Vec<i16> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+    let mut fmt = fmt_chunk(1, 1, 44100, 2, 16);
+    fmt.extend_from_slice(&[0, 0]); // cbSize.
+    let bytes = wave_file(&[(b"fmt ", &fmt), (b"data", &[1, 0, 0xff, 0xff, 0x00, 0x80])]);
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[2, -3, 5, -7]);
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.fmt_extra_bytes(), &[]);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, -1, -32768]);
 }
 
 #[test]
-fn read_wav_wave_format_ex_ieee_float() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav")
-        .unwrap();
-
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().sample_rate, 44100);
-    assert_eq!(wav_reader.spec().bits_per_sample, 32);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Float);
-
-    let samples: Vec<f32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+fn into_channels_splits_interleaved_frames() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let spec = WavSpec::new_pcm(2, 44100, 16);
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for i in 0..5_i16 {
+        writer.write_sample(i).unwrap();
+        writer.write_sample(-100 - i).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[2.0, 3.0, -16411.0, 1019.0]);
+    let reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    let channels = reader.into_channels::<i16>().unwrap();
+    assert_eq!(channels, vec![vec![0, 1, 2, 3, 4], vec![-100, -101, -102, -103, -104]]);
 }
 
 #[test]
-fn read_wav_stereo() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav")
-        .unwrap();
+fn read_planar_deinterleaves_channels() {
+    let fname = "testsamples/waveformatex-16bit-44100Hz-stereo.wav";
+    let samples: Vec<i16> = WavReader::open(fname).unwrap()
+        .into_samples().map(|s| s.unwrap()).collect();
+
+    let mut reader = WavReader::open(fname).unwrap();
+    let duration = reader.duration() as usize;
+    let planar = reader.read_planar::<i16>().unwrap();
+    assert_eq!(planar.len(), 2);
+    for (c, channel) in planar.iter().enumerate() {
+        assert_eq!(channel.len(), duration);
+        for (i, &s) in channel.iter().enumerate() {
+            assert_eq!(s, samples[i * 2 + c]);
+        }
+    }
 
-    assert_eq!(wav_reader.spec().channels, 2);
-    assert_eq!(wav_reader.spec().sample_rate, 44100);
-    assert_eq!(wav_reader.spec().bits_per_sample, 16);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+    let mut reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    let planar = reader.read_planar::<i16>().unwrap();
+    assert_eq!(planar.len(), 1);
+    assert_eq!(planar[0].len(), 4);
 
-    let samples: Vec<i16> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+    // A stereo file with three samples ends in a partial frame, which is ignored.
+    let bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 2, 44100, 4, 16)), (b"data", &[1, 0, 2, 0, 3, 0])]);
+    let mut reader = WavReader::new(io::Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.read_planar::<i16>().unwrap(), [[1], [2]]);
+}
 
-    // The test file has been prepared with these exact eight samples.
-    assert_eq!(&samples[..], &[2, -3, 5, -7, 11, -13, 17, -19]);
+#[test]
+fn mix_sums_samples_and_pads_shorter_file() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let make_file = |samples: &[i16]| {
+        let mut writer = WavWriter::new_in_memory(spec).unwrap();
+        for &s in samples {
+            writer.write_sample(s).unwrap();
+        }
+        WavReader::new(Cursor::new(writer.finalize_into_bytes().unwrap())).unwrap()
+    };
+
+    let a = make_file(&[32767, -32768, 5]);
+    let b = make_file(&[32767, -32768, -5, 7]);
+    let mixed = mix(a, b).unwrap();
+    assert_eq!(mixed.len(), 4);
+    let mixed: Vec<i32> = mixed.map(|s| s.unwrap()).collect();
+    assert_eq!(mixed, [65534, -65536, 0, 7]);
+
+    let stereo = WavReader::open("testsamples/waveformatex-16bit-44100Hz-stereo.wav").unwrap();
+    match mix(make_file(&[1]), stereo) {
+        Err(Error::IncompatibleSpec) => {}
+        _ => panic!("mixing mono and stereo should fail"),
+    }
+}
 
+#[test]
+fn data_chunk_range_reports_actual_offset() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    assert_eq!(reader.data_chunk_range(), 44..52);
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut buffer = Cursor::new(Vec::new());
+    {
+        // This writer puts a JUNK chunk before the fmt chunk.
+        let mut writer = WavWriter::new(&mut buffer, spec).unwrap();
+        writer.write_sample(1_i16).unwrap();
+        writer.finalize().unwrap();
+    }
+    buffer.set_position(0);
+    let reader = WavReader::new(buffer).unwrap();
+    let range = reader.data_chunk_range();
+    assert!(range.start > 44);
+    assert_eq!(range, 80..82);
 }
 
 #[test]
-fn read_wav_pcm_wave_format_8bit() {
-    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
-                                   .unwrap();
+fn validate_sample_range_detects_bits_outside_valid_range() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let fname = "testsamples/waveformatex-16bit-44100Hz-stereo.wav";
+    let samples: Vec<i16> = WavReader::open(fname).unwrap()
+        .into_samples().map(|s| s.unwrap()).collect();
+    let mut reader = WavReader::open(fname).unwrap();
+    let report = reader.validate_sample_range().unwrap();
+    assert_eq!(report.num_samples as usize, samples.len());
+    assert_eq!(report.min, *samples.iter().min().unwrap() as i32);
+    assert_eq!(report.max, *samples.iter().max().unwrap() as i32);
+    assert!(!report.exceeds_valid_bits);
+
+    // Write 24-bit samples in a 4-byte container, but set the top byte of
+    // one of them, as if the file was really 32-bit.
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let spec_ex = WavSpecEx { spec: spec, bytes_per_sample: 4 };
+    let mut buffer = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new_with_spec_ex(&mut buffer, spec_ex).unwrap();
+        writer.write_raw(&[0x00, 0x00, 0x80, 0xff]).unwrap(); // -2^23, valid.
+        writer.write_raw(&[0x00, 0x00, 0x00, 0x01]).unwrap(); // 2^24, invalid.
+        writer.finalize().unwrap();
+    }
+    buffer.set_position(0);
+    let mut reader = WavReader::new(buffer).unwrap();
+    let report = reader.validate_sample_range().unwrap();
+    assert_eq!(report, ValidationReport {
+        num_samples: 2,
+        min: -(1 << 23),
+        max: 1 << 24,
+        exceeds_valid_bits: true,
+    });
+}
 
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().bits_per_sample, 8);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+#[test]
+fn display_title_reads_disp_chunk() {
+    use std::io::Cursor;
+
+    let reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    assert_eq!(reader.display_title(), None);
+
+    // An odd-sized DISP chunk of type CF_TEXT, followed by a padding byte.
+    let bytes = wave_file(&[
+        (b"DISP", b"\x01\0\0\0Hi\0"),
+        (b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)),
+        (b"data", &[7, 0]),
+    ]);
+
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.display_title(), Some("Hi"));
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [7]);
+}
 
-    let samples: Vec<i16> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+#[test]
+fn many_consumed_chunks_do_not_overflow_the_stack() {
+    use std::io::Cursor;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(7i16).unwrap();
+    let wav = writer.finalize_into_bytes().unwrap();
+
+    // Each of these DISP chunks holds a bitmap format, which is skipped.
+    let mut bytes = wav[..72].to_vec();
+    for _ in 0..200_000 {
+        bytes.extend_from_slice(b"DISP");
+        bytes.extend_from_slice(&[4, 0, 0, 0]);
+        bytes.extend_from_slice(&[2, 0, 0, 0]);
+    }
+    bytes.extend_from_slice(&wav[72..]);
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[19, -53, 89, -127]);
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [7]);
 }
 
-/// Test reading 24 bit samples in a 4 byte container using the pcmwaveformat header. This is
-/// technically a non-compliant wave file, but it is the sort of file generated by
-/// 'arecord -f S24_LE -r 48000 -c 2 input.wav' so it should be supported.
 #[test]
-fn read_wav_pcm_wave_format_24bit_4byte() {
-    let mut wav_reader = WavReader::open("testsamples/pcmwaveformat-24bit-4byte-48kHz-stereo.wav")
-        .unwrap();
-
-    assert_eq!(wav_reader.spec().channels, 2);
-    assert_eq!(wav_reader.spec().sample_rate, 48_000);
-    assert_eq!(wav_reader.spec().bits_per_sample, 24);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+fn open_split_continues_data_in_next_files() {
+    use std::env;
+    use std::io::Write;
+    use write::WavWriter;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for s in 0..5_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    let mut bytes = writer.finalize_into_bytes().unwrap();
+    // The first file only describes its own part of the data: 2 samples.
+    bytes[40] = 4;
 
-    let samples: Vec<i32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+    let dir = env::temp_dir();
+    let paths: Vec<path::PathBuf> = ["wav", "w01", "w02"].iter()
+        .map(|ext| dir.join(format!("hound-open-split-{}.{}", ::std::process::id(), ext)))
+        .collect();
+    // Split the data at an odd offset, in the middle of a sample.
+    let parts = [&bytes[..48], &bytes[48..51], &bytes[51..]];
+    for (path, part) in paths.iter().zip(parts.iter()) {
+        fs::File::create(path).unwrap().write_all(part).unwrap();
+    }
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[-96, 23_052, 8_388_607, -8_360_672]);
+    let result = WavReader::open_split(&paths).and_then(|mut reader| {
+        assert_eq!(reader.spec(), spec);
+        assert_eq!(reader.len(), 5);
+        reader.samples::<i16>().collect::<Result<Vec<i16>>>()
+    });
+    for path in &paths {
+        fs::remove_file(path).unwrap();
+    }
+    assert_eq!(result.unwrap(), [0, 1, 2, 3, 4]);
 }
 
-/// Regression test for a real-world wav file encountered in Quake.
 #[test]
-fn read_wav_wave_format_ex_8bit() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatex-8bit-11025Hz-mono.wav").unwrap();
-
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().bits_per_sample, 8);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+fn open_split_fails_without_paths() {
+    match WavReader::open_split(&[]) {
+        Err(Error::IoError(ref e)) => assert_eq!(e.kind(), io::ErrorKind::InvalidInput),
+        _ => panic!("expected an io error"),
+    }
+}
 
-    let samples: Vec<i32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+#[test]
+fn collect_samples_matches_samples_collect() {
+    for fname in &["testsamples/pcmwaveformat-8bit-44100Hz-mono.wav",
+                   "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                   "testsamples/waveformatextensible-24bit-192kHz-mono.wav",
+                   "testsamples/waveformatex-ieeefloat-44100Hz-mono.wav"] {
+        let mut reader = WavReader::open(fname).unwrap();
+        if reader.spec().sample_format == SampleFormat::Float {
+            let expected: Vec<f32> = reader.samples().map(|s| s.unwrap()).collect();
+            let mut reader = WavReader::open(fname).unwrap();
+            assert_eq!(reader.collect_samples::<f32>().unwrap(), expected);
+        } else {
+            let expected: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+            let mut reader = WavReader::open(fname).unwrap();
+            assert_eq!(reader.collect_samples::<i32>().unwrap(), expected);
+            assert_eq!(reader.collect_samples::<i32>().unwrap(), []);
+        }
+    }
+}
 
-    // The audio data has been zeroed out, but for 8-bit files, a zero means a
-    // sample value of 128.
-    assert_eq!(&samples[..], &[-128, -128, -128, -128]);
+#[test]
+fn collect_samples_returns_first_error() {
+    use std::io::Cursor;
+
+    // A 16-bit file that announces 3 samples, but contains only 2.
+    let data = [1, 0, 2, 0, 3, 0];
+    let mut bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)), (b"data", &data)]);
+    bytes.truncate(44 + 4);
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    match reader.collect_samples::<i16>() {
+        Err(Error::Truncated) => {}
+        _ => panic!("expected Error::Truncated"),
+    }
+    // A 16-bit sample does not fit in an i8.
+    let mut reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    match reader.collect_samples::<i8>() {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide"),
+    }
 }
 
-/// This test sample tests both reading the WAVEFORMATEXTENSIBLE header, and 24-bit samples.
 #[test]
-fn read_wav_wave_format_extensible_pcm_24bit() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
-        .unwrap();
+fn read_rejects_zero_and_implausible_sample_rates() {
+    use std::io::Cursor;
 
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().sample_rate, 192_000);
-    assert_eq!(wav_reader.spec().bits_per_sample, 24);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+    let header_with_rate = |rate: u32| {
+        wave_file(&[(b"fmt ", &fmt_chunk(1, 1, rate, 2, 16)), (b"data", &[1, 0])])
+    };
 
-    let samples: Vec<i32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+    match WavReader::new(Cursor::new(header_with_rate(0))) {
+        Err(Error::FormatError(..)) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
+    match WavReader::new(Cursor::new(header_with_rate(48_000_000))) {
+        Err(Error::FormatError(..)) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[-17, 4_194_319, -6_291_437, 8_355_817]);
+    let lenient = ReadOptions { lenient: true, ..ReadOptions::default() };
+    let reader = WavReader::new_with_options(Cursor::new(header_with_rate(48_000_000)),
+                                             lenient).unwrap();
+    assert_eq!(reader.sample_rate(), 48_000_000);
+    match WavReader::new_with_options(Cursor::new(header_with_rate(0)), lenient) {
+        Err(Error::FormatError(..)) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
+    assert!(WavReader::new(Cursor::new(header_with_rate(4_000_000))).is_ok());
 }
 
-/// This test sample tests both reading the WAVEFORMATEXTENSIBLE header, and 24-bit samples with a
-/// 4 byte container size.
 #[test]
-fn read_wav_wave_format_extensible_pcm_24bit_4byte() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatextensible-24bit-4byte-48kHz-stereo.wav")
-        .unwrap();
+fn read_all_boxed_converts_any_bit_depth() {
+    let reader = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
+    let expected: Vec<i32> = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
+        .unwrap().samples().map(|s| s.unwrap()).collect();
+    let buffer = reader.read_all_boxed().unwrap();
+    assert_eq!(buffer.len(), expected.len());
+    assert_eq!(buffer.as_i32_vec(), expected);
+    let expected_f32: Vec<f32> = expected.iter().map(|&x| x as f32 / 128.0).collect();
+    assert_eq!(buffer.as_f32_vec(), expected_f32);
+
+    let fname = "testsamples/waveformatextensible-24bit-192kHz-mono.wav";
+    let expected: Vec<i32> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+    let buffer = WavReader::open(fname).unwrap().read_all_boxed().unwrap();
+    assert_eq!(buffer.as_i32_vec(), expected);
+    let expected_f32: Vec<f32> = expected.iter().map(|&x| x as f32 / 8388608.0).collect();
+    assert_eq!(buffer.as_f32_vec(), expected_f32);
+
+    let fname = "testsamples/waveformatex-ieeefloat-44100Hz-mono.wav";
+    let expected: Vec<f32> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+    let buffer = WavReader::open(fname).unwrap().read_all_boxed().unwrap();
+    assert_eq!(buffer.as_f32_vec(), expected);
+    assert!(!buffer.is_empty());
+}
 
-    assert_eq!(wav_reader.spec().channels, 2);
-    assert_eq!(wav_reader.spec().sample_rate, 48_000);
-    assert_eq!(wav_reader.spec().bits_per_sample, 24);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+#[test]
+fn new_at_reads_embedded_file() {
+    use std::io::{Cursor, Read};
 
-    let samples: Vec<i32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+    let mut embedded = b"PACKHEADER".to_vec();
+    let offset = embedded.len() as u64;
+    let mut file = fs::File::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    file.read_to_end(&mut embedded).unwrap();
+    let file_len = embedded.len() as u64 - offset;
+    embedded.extend_from_slice(b"NEXTFILE");
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[-96, 23_052, 8_388_607, -8_360_672]);
+    let expected_reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
+        .unwrap();
+    let expected_range = expected_reader.data_chunk_range();
+    let expected: Vec<i16> = expected_reader.into_samples().map(|s| s.unwrap()).collect();
+
+    let mut reader = WavReader::new_at(Cursor::new(&embedded[..]), offset).unwrap();
+    assert_eq!(reader.data_chunk_range(), expected_range);
+    assert!(reader.data_chunk_range().end <= file_len);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, expected);
+
+    reader.seek(1).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, &expected[1..]);
 }
 
 #[test]
-fn read_wav_32bit() {
-    let mut wav_reader = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav")
-                                   .unwrap();
-
-    assert_eq!(wav_reader.spec().bits_per_sample, 32);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+fn samples_with_gain_scales_and_clips() {
+    let fname = "testsamples/pcmwaveformat-16bit-44100Hz-mono.wav";
+    let expected: Vec<i16> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+
+    let mut reader = WavReader::open(fname).unwrap();
+    let unchanged: Vec<i16> = reader.samples_with_gain(0.0).map(|s| s.unwrap()).collect();
+    assert_eq!(unchanged, expected);
+
+    // About -6 dB halves the amplitude.
+    let mut reader = WavReader::open(fname).unwrap();
+    let gain = 0.5_f64.log10() * 20.0;
+    let samples = reader.samples_with_gain::<i16>(gain);
+    assert_eq!(samples.len(), expected.len());
+    let halved: Vec<i16> = samples.map(|s| s.unwrap()).collect();
+    let expected_halved: Vec<i16> = expected.iter()
+        .map(|&x| (x as f64 * 0.5).round() as i16).collect();
+    assert_eq!(halved, expected_halved);
+
+    // At a very high gain, everything but silence clips.
+    let mut reader = WavReader::open(fname).unwrap();
+    let clipped: Vec<i16> = reader.samples_with_gain(120.0).map(|s| s.unwrap()).collect();
+    for (&x, &y) in expected.iter().zip(clipped.iter()) {
+        let expected_clipped = if x > 0 { 32767 } else if x < 0 { -32768 } else { 0 };
+        assert_eq!(y, expected_clipped);
+    }
+}
 
-    let samples: Vec<i32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+#[test]
+fn read_data_chunk_with_placeholder_size() {
+    use std::io::Cursor;
+
+    // A live stream sets both the RIFF size and the data size to the placeholder.
+    let data = [1, 0, 2, 0, 3, 0, 4, 0];
+    let mut bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 2, 44100, 4, 16)), (b"data", &data)]);
+    (&mut bytes[4..8]).write_le_u32(0xffff_ffff).unwrap();
+    (&mut bytes[40..44]).write_le_u32(0xffff_ffff).unwrap();
+
+    // A slice does not implement `Seek`, so the samples are read until EOF.
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.try_len(), None);
+    assert_eq!(reader.samples::<i16>().size_hint(), (0, None));
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 2, 3, 4]);
+    assert_eq!(reader.try_len(), Some(4));
+
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.collect_samples::<i16>().unwrap(), [1, 2, 3, 4]);
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.read_planar::<i16>().unwrap(), [[1, 3], [2, 4]]);
+
+    // With `Seek`, the length follows from the length of the stream.
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    reader.resolve_unknown_len().unwrap();
+    assert_eq!(reader.try_len(), Some(4));
+    assert_eq!(reader.duration(), 2);
+    let reader = WavReader::new_at(Cursor::new(&bytes[..]), 0).unwrap();
+    assert_eq!(reader.try_len(), Some(4));
+    let mut reader = WavReader::new_seekable(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.try_len(), Some(4));
+    assert_eq!(reader.samples::<i16>().size_hint(), (4, Some(4)));
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    reader.seek(1).unwrap();
+    assert_eq!(reader.try_len(), Some(4));
+    assert_eq!(reader.samples::<i16>().len(), 2);
+
+    // A partial sample at the end is still an error.
+    bytes.push(5);
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let mut samples = reader.samples::<i16>();
+    for _ in 0..4 {
+        samples.next().unwrap().unwrap();
+    }
+    match samples.next() {
+        Some(Err(Error::Truncated)) => {}
+        _ => panic!("expected Error::Truncated"),
+    }
+}
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[19, -229_373, 33_587_161, -2_147_483_497]);
+#[test]
+fn channel_peaks_are_per_channel() {
+    let spec = WavSpec {
+        channels: 3,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::write::WavWriter::new_in_memory(spec).unwrap();
+    for &s in &[1_i32, -20, 300, -4, 50, -8_388_608, 7, 0, 6] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.channel_peaks().unwrap(), [7, 50, 8_388_608]);
+
+    let mut reader = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav").unwrap();
+    match reader.channel_peaks() {
+        Err(Error::InvalidSampleFormat) => {}
+        _ => panic!("expected Error::InvalidSampleFormat"),
+    }
 }
 
 #[test]
-fn read_wav_wave_format_extensible_ieee_float() {
-    let mut wav_reader =
-        WavReader::open("testsamples/waveformatextensible-ieeefloat-44100Hz-mono.wav").unwrap();
+fn dc_offset_is_mean_per_channel() {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::write::WavWriter::new_in_memory(spec).unwrap();
+    // Sums that overflow an `i32` must not matter.
+    for &s in &[i32::max_value(), -3, i32::max_value(), 1, 1, 0, 1, 0] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let expected = (i32::max_value() as f64 + 1.0) / 2.0;
+    assert_eq!(reader.dc_offset().unwrap(), [expected, -0.5]);
+
+    let float_spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let mut writer = ::write::WavWriter::new_in_memory(float_spec).unwrap();
+    for &s in &[0.25_f32, 0.5, -0.25, 0.5] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.dc_offset().unwrap(), [0.25]);
+
+    // Without samples, there is no offset.
+    let writer = ::write::WavWriter::new_in_memory(spec).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.dc_offset().unwrap(), [0.0, 0.0]);
+}
 
-    assert_eq!(wav_reader.spec().channels, 1);
-    assert_eq!(wav_reader.spec().sample_rate, 44100);
-    assert_eq!(wav_reader.spec().bits_per_sample, 32);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Float);
+#[test]
+fn read_into_equals_samples_iterator() {
+    let files = ["testsamples/pop.wav",
+                 "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                 "testsamples/waveformatextensible-24bit-192kHz-mono.wav"];
+    for fname in &files {
+        let expected: Vec<i32> = WavReader::open(fname).unwrap()
+            .samples().map(|s| s.unwrap()).collect();
 
-    let samples: Vec<f32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+        let mut reader = WavReader::open(fname).unwrap();
+        let mut buf = [0_i32; 7];
+        let mut samples = Vec::new();
+        loop {
+            let n = reader.read_into(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            samples.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(samples, expected);
+    }
 
-    // The test file has been prepared with these exact four samples.
-    assert_eq!(&samples[..], &[2.0, 3.0, -16411.0, 1019.0]);
+    // A buffer that spans several of the blocks that are decoded at once,
+    // with 3-byte samples, whose size does not divide the block size.
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let expected: Vec<i32> = (0..5000).map(|x| x * 1000 - 2_500_000).collect();
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    writer.write_all(expected.iter().cloned()).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let mut all = vec![0_i32; expected.len() + 1];
+    assert_eq!(reader.read_into(&mut all).unwrap(), expected.len());
+    assert_eq!(&all[..expected.len()], &expected[..]);
+
+    // Samples that do not fit are detected before anything is read.
+    let mut reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    match reader.read_into(&mut [0_i8; 4]) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide"),
+    }
+    let mut buf = [0_i16; 8];
+    assert_eq!(reader.read_into(&mut buf).unwrap(), 4);
+    assert_eq!(&buf[..4], &[2, -3, 5, -7]);
 }
 
 #[test]
-fn read_wav_nonstandard_01() {
-    // The test sample here is adapted from a file encountered in the wild (data
-    // chunk replaced with two zero samples, some metadata dropped, and the file
-    // length in the header fixed). It is not a valid file according to the
-    // standard, but many players can deal with it nonetheless. (The file even
-    // contains some metadata; open it in a hex editor if you would like to know
-    // which program created it.) The file contains a regular PCM format tag,
-    // but the size of the fmt chunk is one that would be expected of a
-    // WAVEFORMATEXTENSIBLE chunk. The bits per sample is 24, which is invalid
-    // for WAVEFORMATEX, but we can read it nonetheless.
-    let mut wav_reader = WavReader::open("testsamples/nonstandard-01.wav").unwrap();
+fn samples_normalized_divides_by_full_scale() {
+    let files = ["testsamples/pcmwaveformat-8bit-44100Hz-mono.wav",
+                 "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                 "testsamples/waveformatextensible-24bit-192kHz-mono.wav"];
+    for fname in &files {
+        let mut reader = WavReader::open(fname).unwrap();
+        let full_scale = (1_i64 << (reader.bits_per_sample() - 1)) as f32;
+        let expected: Vec<f32> = reader.samples::<i32>()
+            .map(|s| s.unwrap() as f32 / full_scale).collect();
+        let mut reader = WavReader::open(fname).unwrap();
+        let normalized = reader.samples_normalized();
+        assert_eq!(normalized.len(), expected.len());
+        let samples: Vec<f32> = normalized.map(|s| s.unwrap()).collect();
+        assert_eq!(samples, expected);
+    }
 
-    assert_eq!(wav_reader.spec().bits_per_sample, 24);
-    assert_eq!(wav_reader.spec().sample_format, SampleFormat::Int);
+    // The extremes map to -1.0 and just below 1.0.
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    for &s in &[-32768_i16, 32767, 0, 16384] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<f32> = reader.samples_normalized().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [-1.0, 32767.0 / 32768.0, 0.0, 0.5]);
+
+    // Float samples are passed through.
+    let mut reader = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav").unwrap();
+    let expected: Vec<f32> = reader.samples().map(|s| s.unwrap()).collect();
+    let mut reader = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav").unwrap();
+    let samples: Vec<f32> = reader.samples_normalized().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, expected);
+
+    // A format that is not decoded may have any number of bits per sample.
+    let bytes = wave_file(&[(b"fmt ", &fmt_chunk(0x55, 1, 44100, 1, 0)), (b"data", &[1, 2])]);
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    match reader.samples_normalized().next() {
+        Some(Err(Error::Unsupported)) => {}
+        _ => panic!("expected Error::Unsupported"),
+    }
+}
 
-    let samples: Vec<i32> = wav_reader.samples()
-                                      .map(|r| r.unwrap())
-                                      .collect();
+#[test]
+fn refill_reuses_buffer_and_ends_on_frames() {
+    let fname = "testsamples/waveformatex-16bit-44100Hz-stereo.wav";
+    let expected: Vec<i16> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+    assert!(expected.len() > 6);
+
+    let mut reader = WavReader::open(fname).unwrap();
+    let mut buf = Vec::with_capacity(6);
+    let mut all = Vec::new();
+    loop {
+        let capacity = buf.capacity();
+        let frames = reader.refill(&mut buf, 3).unwrap();
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(buf.len(), frames * 2);
+        all.extend_from_slice(&buf);
+        if frames == 0 {
+            break;
+        }
+    }
+    assert_eq!(all, expected);
+
+    // A trailing partial frame is ignored.
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::write::WavWriter::new_in_memory(spec).unwrap();
+    for s in 0..4_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    let mut bytes = writer.finalize_into_bytes().unwrap();
+    // Drop the last sample, so the data ends in the middle of a frame.
+    bytes.truncate(80 + 6);
+    bytes[76] = 6;
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.refill::<i16>(&mut buf, 4).unwrap(), 1);
+    assert_eq!(buf, [0, 1]);
+    assert_eq!(reader.refill::<i16>(&mut buf, 4).unwrap(), 0);
+}
 
-    assert_eq!(&samples[..], &[0, 0]);
+#[test]
+fn read_rejects_unsupported_bits_per_sample() {
+    use std::io::Cursor;
+
+    // A WAVEFORMATEXTENSIBLE header with the given container and valid bits.
+    let header_with_bits = |container_bits: u16, valid_bits: u16, float: bool| {
+        let block_align = i32::sample_bytes(container_bits);
+        let mut fmt = fmt_chunk(0xfffe, 1, 44100, block_align, container_bits);
+        fmt.extend_from_slice(&[22, 0, valid_bits as u8, 0]); // cbSize, valid bits.
+        fmt.extend_from_slice(&[4, 0, 0, 0]); // Center.
+        if float {
+            fmt.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_IEEE_FLOAT);
+        } else {
+            fmt.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_PCM);
+        }
+        wave_file(&[(b"fmt ", &fmt), (b"data", &[])])
+    };
+
+    for &(container_bits, valid_bits, float) in &[(8, 1, false),
+                                                  (8, 4, false),
+                                                  (16, 10, false),
+                                                  (24, 17, false),
+                                                  (32, 31, false),
+                                                  (32, 24, true)] {
+        let bytes = header_with_bits(container_bits, valid_bits, float);
+        match WavReader::new(Cursor::new(bytes)) {
+            Err(Error::UnsupportedBitsPerSample(bits)) => assert_eq!(bits, valid_bits),
+            _ => panic!("expected Error::UnsupportedBitsPerSample({})", valid_bits),
+        }
+    }
+    for &(container_bits, valid_bits) in &[(8, 8), (16, 12), (24, 20), (32, 24)] {
+        let bytes = header_with_bits(container_bits, valid_bits, false);
+        let reader = WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.bits_per_sample(), valid_bits);
+    }
+
+    // A plain PCM header with one bit per sample.
+    let bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 1, 44100, 1, 1)), (b"data", &[])]);
+    match WavReader::new(Cursor::new(bytes)) {
+        Err(err @ Error::UnsupportedBitsPerSample(1)) => {
+            assert_eq!(err.to_string(), "Unsupported number of bits per sample: 1.");
+        }
+        _ => panic!("expected Error::UnsupportedBitsPerSample(1)"),
+    }
 }
 
 #[test]
-fn wide_read_should_signal_error() {
-    let mut reader24 = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
-        .unwrap();
+fn raw_only_formats_are_opened_but_not_decoded() {
+    use std::io::Cursor;
+
+    for &(sample_format, bits) in RAW_ONLY_READ_FORMATS {
+        assert!(!::supported_read_formats().contains(&(sample_format, bits)));
+        // Bit depths that are not a multiple of 8 need WAVEFORMATEXTENSIBLE.
+        let block_align = (bits + 7) / 8;
+        let data = vec![0x55; block_align as usize * 2];
+        let mut fmt = fmt_chunk(0xfffe, 1, 44100, block_align, block_align * 8);
+        fmt.extend_from_slice(&[22, 0, bits as u8, 0]); // cbSize, valid bits.
+        fmt.extend_from_slice(&[4, 0, 0, 0]); // Center.
+        match sample_format {
+            SampleFormat::Int => fmt.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_PCM),
+            SampleFormat::Float => fmt.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_IEEE_FLOAT),
+        }
+        let bytes = wave_file(&[(b"fmt ", &fmt), (b"data", &data)]);
+
+        let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+        assert_eq!(reader.raw_data().unwrap(), data);
+        let reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+        match reader.read_all_boxed() {
+            Err(Error::Unsupported) => {}
+            _ => panic!("expected Error::Unsupported for {} bits", bits),
+        }
+    }
+}
 
-    // Even though we know the first value is 17, and it should fit in an `i8`,
-    // a general 24-bit sample will not fit in an `i8`, so this should fail.
-    // 16-bit is still not wide enough, but 32-bit should do the trick.
-    assert!(reader24.samples::<i8>().next().unwrap().is_err());
-    assert!(reader24.samples::<i16>().next().unwrap().is_err());
-    assert!(reader24.samples::<i32>().next().unwrap().is_ok());
-    assert!(reader24.samples::<f32>().next().unwrap().is_ok());
+#[test]
+fn samples_timed_yields_frame_times() {
+    let fname = "testsamples/waveformatex-16bit-44100Hz-stereo.wav";
+    let expected: Vec<i16> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+
+    let mut reader = WavReader::open(fname).unwrap();
+    let timed: Vec<(f64, i16)> = reader.samples_timed().map(|s| s.unwrap()).collect();
+    assert_eq!(timed.len(), expected.len());
+    for (i, &(time, sample)) in timed.iter().enumerate() {
+        assert_eq!(time, (i / 2) as f64 / 44100.0);
+        assert_eq!(sample, expected[i]);
+    }
+}
 
-    let mut reader32 = WavReader::open("testsamples/waveformatextensible-32bit-48kHz-stereo.wav")
-        .unwrap();
+#[test]
+fn samples_as_coerces_wide_samples() {
+    let fname = "testsamples/waveformatextensible-24bit-192kHz-mono.wav";
+    let wide: Vec<i32> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+    let mut reader = WavReader::open(fname).unwrap();
+    let narrow: Vec<i16> = reader.samples_as().map(|s| s.unwrap()).collect();
+    let expected: Vec<i16> = wide.iter().map(|&x| (x >> 8) as i16).collect();
+    assert_eq!(narrow, expected);
+
+    // Samples that fit are the same as with `samples()`.
+    let fname = "testsamples/pcmwaveformat-8bit-44100Hz-mono.wav";
+    let expected: Vec<i16> = WavReader::open(fname).unwrap()
+        .samples().map(|s| s.unwrap()).collect();
+    let mut reader = WavReader::open(fname).unwrap();
+    let samples: Vec<i16> = reader.samples_as().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, expected);
+
+    let mut reader = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav").unwrap();
+    match reader.samples_as::<i16>().next() {
+        Some(Err(Error::InvalidSampleFormat)) => {}
+        _ => panic!("expected Error::InvalidSampleFormat"),
+    }
+}
 
-    // In general, 32-bit samples will not fit in anything but an `i32`.
-    assert!(reader32.samples::<i8>().next().unwrap().is_err());
-    assert!(reader32.samples::<i16>().next().unwrap().is_err());
-    assert!(reader32.samples::<i32>().next().unwrap().is_ok());
-    assert!(reader32.samples::<f32>().next().unwrap().is_err());
+#[test]
+fn read_wave_list_expands_silence() {
+    use std::io::Cursor;
+
+    // A `LIST` chunk of type `wavl` with data, three frames of silence, and
+    // more data. The first data chunk has an odd size, and is padded.
+    let wave_list_file = |bits: u8, samples: &[&[u8]]| {
+        let bytes_per_sample = bits / 8;
+        let mut list = Vec::new();
+        list.extend_from_slice(b"wavl");
+        list.extend_from_slice(b"data");
+        list.extend_from_slice(&[samples[0].len() as u8, 0, 0, 0]);
+        list.extend_from_slice(samples[0]);
+        if samples[0].len() % 2 == 1 {
+            list.push(0);
+        }
+        list.extend_from_slice(b"slnt");
+        list.extend_from_slice(&[4, 0, 0, 0, 3, 0, 0, 0]);
+        list.extend_from_slice(b"data");
+        list.extend_from_slice(&[samples[1].len() as u8, 0, 0, 0]);
+        list.extend_from_slice(samples[1]);
+
+        let fmt = fmt_chunk(1, 1, 44100, bytes_per_sample as u16, bits as u16);
+        wave_file(&[(b"fmt ", &fmt), (b"LIST", &list)])
+    };
+
+    let bytes = wave_list_file(16, &[&[1, 0, 2, 0], &[5, 0]]);
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.len(), 6);
+    assert_eq!(reader.duration(), 6);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 2, 0, 0, 0, 5]);
+    reader.seek(4).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [0, 5]);
+
+    // For 8-bit samples, silence is stored as 0x80.
+    let bytes = wave_list_file(8, &[&[0x81], &[0x7f, 0x90]]);
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<i8> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 0, 0, 0, -1, 16]);
+
+    assert!(WavReader::new(&bytes[..]).unwrap().as_i16_slice().is_none());
+
+    // A silence chunk that is too short to hold the number of frames.
+    let mut bytes = wave_list_file(16, &[&[1, 0], &[5, 0]]);
+    let slnt = bytes.windows(4).position(|w| w == b"slnt").unwrap();
+    bytes[slnt + 4] = 2;
+    match WavReader::new(&bytes[..]) {
+        Err(Error::FormatError(_)) => {}
+        _ => panic!("expected Error::FormatError for a short slnt chunk"),
+    }
 }
 
 #[test]
-fn sample_format_mismatch_should_signal_error() {
-    let mut reader_f32 = WavReader::open("testsamples/waveformatex-ieeefloat-44100Hz-mono.wav")
-        .unwrap();
+fn read_le_i24_extends_sign_bit() {
+    // Little-endian bytes, and the value they represent.
+    let cases: &[([u8; 3], i32)] = &[
+        ([0x00, 0x00, 0x00], 0),
+        ([0x01, 0x00, 0x00], 1),
+        ([0xff, 0xff, 0xff], -1),
+        ([0xfe, 0xff, 0xff], -2),
+        ([0xff, 0xff, 0x7f], 8_388_607),
+        ([0x00, 0x00, 0x80], -8_388_608),
+        ([0x01, 0x00, 0x80], -8_388_607),
+        ([0x00, 0x00, 0x01], 65_536),
+        ([0x56, 0x34, 0x12], 0x12_34_56),
+        ([0xaa, 0xcb, 0xed], -0x12_34_56),
+        ([0xff, 0xff, 0x00], 65_535),
+        ([0x00, 0x00, 0xff], -65_536),
+    ];
+    for &(bytes, expected) in cases {
+        assert_eq!((&bytes[..]).read_le_i24().unwrap(), expected);
+
+        // In a 4-byte container, the top byte must be ignored.
+        for &top in &[0x00, 0x7f, 0x80, 0xff] {
+            let bytes_4 = [bytes[0], bytes[1], bytes[2], top];
+            assert_eq!((&bytes_4[..]).read_le_i24_4().unwrap(), expected);
+        }
+    }
 
-    assert!(reader_f32.samples::<i8>().next().unwrap().is_err());
-    assert!(reader_f32.samples::<i16>().next().unwrap().is_err());
-    assert!(reader_f32.samples::<i32>().next().unwrap().is_err());
-    assert!(reader_f32.samples::<f32>().next().unwrap().is_ok());
+    // Fewer than three bytes must be an error, not a partial value.
+    assert!((&[0xff, 0xff][..]).read_le_i24().is_err());
 
-    let mut reader_i8 = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav").unwrap();
+    // The same values must come out of a 24-bit file.
+    let mut data = Vec::new();
+    for &(sample_bytes, _) in cases {
+        data.extend_from_slice(&sample_bytes);
+    }
+    let bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 1, 44100, 3, 24)), (b"data", &data)]);
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    let expected: Vec<i32> = cases.iter().map(|&(_, x)| x).collect();
+    assert_eq!(samples, expected);
+}
 
-    assert!(reader_i8.samples::<i8>().next().unwrap().is_ok());
-    assert!(reader_i8.samples::<i16>().next().unwrap().is_ok());
-    assert!(reader_i8.samples::<i32>().next().unwrap().is_ok());
-    assert!(reader_i8.samples::<f32>().next().unwrap().is_ok());
+#[test]
+fn into_source_yields_full_scale_i16() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let mut spec = WavSpec {
+        channels: 2,
+        sample_rate: 22050,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for &s in &[0x7f_ffff_i32, -0x80_0000, 0x12_3456, 0] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut source = WavReader::new(Cursor::new(bytes)).unwrap().into_source();
+    assert_eq!(source.channels(), 2);
+    assert_eq!(source.sample_rate(), 22050);
+    assert_eq!(source.size_hint(), (0, Some(4)));
+    let samples: Vec<i16> = source.by_ref().collect();
+    assert_eq!(samples, [0x7fff, -0x8000, 0x1234, 0]);
+    assert!(source.take_error().is_none());
+
+    spec.bits_per_sample = 8;
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for &s in &[127_i8, -128] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let samples: Vec<i16> = WavReader::new(Cursor::new(bytes)).unwrap().into_source().collect();
+    assert_eq!(samples, [0x7f00, -0x8000]);
+
+    spec.bits_per_sample = 32;
+    spec.sample_format = SampleFormat::Float;
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for &s in &[1.0_f32, -1.0, 0.5, 2.0] {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let samples: Vec<i16> = WavReader::new(Cursor::new(bytes)).unwrap().into_source().collect();
+    assert_eq!(samples, [32767, -32767, 16383, 32767]);
 }
 
 #[test]
-fn read_as_i32_should_equal_read_as_f32() {
-    let samples_i32: Vec<i32> = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
-        .unwrap()
-        .samples::<i32>()
-        .map(Result::unwrap)
-        .collect();
-    let samples_f32: Vec<f32> = WavReader::open("testsamples/pcmwaveformat-8bit-44100Hz-mono.wav")
-        .unwrap()
-        .samples::<f32>()
-        .map(Result::unwrap)
-        .collect();
-    for (&sample_i32, &sample_f32) in samples_i32.iter().zip(&samples_f32) {
-        assert_eq!(sample_i32, sample_f32 as i32);
-        assert_eq!(sample_i32 as f32, sample_f32);
+fn into_source_stops_at_error() {
+    // A truncated data chunk: the header claims four samples, there are 1.5.
+    let data = [1, 0, 2, 0, 3, 0, 4, 0];
+    let mut bytes = wave_file(&[(b"fmt ", &fmt_chunk(1, 1, 44100, 2, 16)), (b"data", &data)]);
+    bytes.truncate(44 + 3);
+    let mut source = WavReader::new(&bytes[..]).unwrap().into_source();
+    assert_eq!(source.next(), Some(1));
+    assert_eq!(source.next(), None);
+    assert_eq!(source.next(), None);
+    assert!(source.take_error().is_some());
+}
+
+#[test]
+fn new_raw_reads_headerless_samples() {
+    use std::io::Cursor;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    // Three bytes of garbage, two frames, and a trailing byte that is not
+    // part of the data.
+    let bytes = [0xde, 0xad, 0xbe, 1, 0, 0xff, 0xff, 3, 0, 4, 0, 0x99];
+    let mut reader = WavReader::new_raw(Cursor::new(&bytes[..]), spec, 3, 8).unwrap();
+    assert_eq!(reader.spec(), spec);
+    assert_eq!(reader.duration(), 2);
+    assert_eq!(reader.data_chunk_range(), 3..11);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, -1, 3, 4]);
+    reader.seek(1).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [3, 4]);
+
+    match WavReader::new_raw(&bytes[..], spec, 20, 8) {
+        Err(Error::Truncated) => {}
+        _ => panic!("expected Error::Truncated"),
     }
+    let spec_zero = WavSpec { channels: 0, ..spec };
+    match WavReader::new_raw(&bytes[..], spec_zero, 0, 8) {
+        Err(Error::FormatError(_)) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
+    let spec_7 = WavSpec { bits_per_sample: 7, ..spec };
+    match WavReader::new_raw(&bytes[..], spec_7, 0, 8) {
+        Err(Error::UnsupportedBitsPerSample(7)) => {}
+        _ => panic!("expected Error::UnsupportedBitsPerSample"),
+    }
+}
 
-    let samples_i32: Vec<i32> = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
-        .unwrap()
-        .samples::<i32>()
-        .map(Result::unwrap)
-        .collect();
-    let samples_f32: Vec<f32> = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav")
-        .unwrap()
-        .samples::<f32>()
-        .map(Result::unwrap)
-        .collect();
-    for (&sample_i32, &sample_f32) in samples_i32.iter().zip(&samples_f32) {
-        assert_eq!(sample_i32, sample_f32 as i32);
-        assert_eq!(sample_i32 as f32, sample_f32);
+#[test]
+fn read_ignores_wrong_riff_size() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.set_display_title("before the data").unwrap();
+    for s in 0..100_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    writer.set_display_title("after the data").unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let correct_len = bytes.len() as u32 - 8;
+
+    // Sizes that are a bit too small or too large, one that ends before the
+    // data chunk, and the zero that some streaming encoders write.
+    for &riff_len in &[correct_len - 3, correct_len + 5, 20, 0, 0xffff_ffff] {
+        let mut bytes = bytes.clone();
+        bytes[4] = riff_len as u8;
+        bytes[5] = (riff_len >> 8) as u8;
+        bytes[6] = (riff_len >> 16) as u8;
+        bytes[7] = (riff_len >> 24) as u8;
+        let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.len(), 100);
+        assert_eq!(reader.display_title(), Some("before the data"));
+        let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, (0..100).collect::<Vec<i16>>());
+
+        // The chunks after the data chunk are read as well.
+        let mut chunks = reader.reader;
+        chunks.read_remaining_chunks().unwrap();
+        assert_eq!(chunks.display_title(), Some("after the data"));
     }
+}
 
-    let samples_i32: Vec<i32> = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
-        .unwrap()
-        .samples::<i32>()
-        .map(Result::unwrap)
-        .collect();
-    let samples_f32: Vec<f32> = WavReader::open("testsamples/waveformatextensible-24bit-192kHz-mono.wav")
-        .unwrap()
-        .samples::<f32>()
-        .map(Result::unwrap)
-        .collect();
-    for (&sample_i32, &sample_f32) in samples_i32.iter().zip(&samples_f32) {
-        assert_eq!(sample_i32, sample_f32 as i32);
-        assert_eq!(sample_i32 as f32, sample_f32);
+#[test]
+fn samples_rev_reverses_frames_but_not_channels() {
+    use std::io::Cursor;
+    use write::WavWriter;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    // Enough frames to span several blocks.
+    let num_frames = 2500;
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for i in 0..num_frames * 2 {
+        writer.write_sample(i as i16).unwrap();
+    }
+    let mut bytes = writer.finalize_into_bytes().unwrap();
+    // Add a partial frame at the end of the data chunk.
+    let data_len = (num_frames * 4 + 2) as u32;
+    bytes[40] = data_len as u8;
+    bytes[41] = (data_len >> 8) as u8;
+    bytes.extend_from_slice(&[0xff, 0x7f]);
+
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    // Read a few samples first, reversing must still start at the end.
+    reader.samples::<i16>().next().unwrap().unwrap();
+
+    let samples_rev = reader.samples_rev::<i16>();
+    assert_eq!(samples_rev.len(), num_frames * 2);
+    let samples: Vec<i16> = samples_rev.map(|s| s.unwrap()).collect();
+    assert_eq!(samples.len(), num_frames * 2);
+    for (i, frame) in samples.chunks(2).enumerate() {
+        let left = ((num_frames - 1 - i) * 2) as i16;
+        assert_eq!(frame, &[left, left + 1]);
     }
+
+    // Forward reading continues after seeking.
+    reader.seek(1).unwrap();
+    assert_eq!(reader.samples::<i16>().next().unwrap().unwrap(), 2);
 }
 
 #[test]
-fn fuzz_crashes_should_be_fixed() {
-    use std::fs;
-    use std::ffi::OsStr;
+fn samples_between_reads_time_window() {
+    use std::io::Cursor;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 10,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    for i in 0..30_i16 {
+        writer.write_sample(i).unwrap();
+        writer.write_sample(-i).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+
+    // Frames 5 up to but not including 12.
+    let window: Vec<i16> = reader.samples_between(0.5, 1.2).unwrap()
+                                 .map(|s| s.unwrap()).collect();
+    let expected: Vec<i16> = (5..12_i16).flat_map(|i| vec![i, -i]).collect();
+    assert_eq!(window, expected);
+
+    // The end is clamped to the duration of the file.
+    let tail: Vec<i16> = reader.samples_between(2.8, 100.0).unwrap()
+                               .map(|s| s.unwrap()).collect();
+    assert_eq!(tail, [28, -28, 29, -29]);
+
+    // An empty or reversed window yields nothing.
+    assert_eq!(reader.samples_between::<i16>(1.0, 1.0).unwrap().count(), 0);
+    assert_eq!(reader.samples_between::<i16>(2.0, 1.0).unwrap().count(), 0);
+    assert_eq!(reader.samples_between::<i16>(-1.0, 0.2).unwrap().count(), 4);
+}
 
-    // This is a regression test: all crashes and other issues found through
-    // fuzzing should not cause a crash.
-    let dir = fs::read_dir("testsamples/fuzz").ok()
-                 .expect("failed to enumerate fuzz test corpus");
-    for path in dir {
-        let path = path.ok().expect("failed to obtain path info").path();
-        let is_file = fs::metadata(&path).unwrap().file_type().is_file();
-        if is_file && path.extension() == Some(OsStr::new("wav")) {
-            println!("    testing {} ...", path.to_str()
-                                               .expect("unsupported filename"));
-            let mut reader = match WavReader::open(path) {
-                Ok(r) => r,
-                Err(..) => continue,
-            };
-            match reader.spec().sample_format {
-                SampleFormat::Int => {
-                    for sample in reader.samples::<i32>() {
-                        match sample {
-                            Ok(..) => { }
-                            Err(..) => break,
-                        }
-                    }
-                }
-                SampleFormat::Float => {
-                    for sample in reader.samples::<f32>() {
-                        match sample {
-                            Ok(..) => { }
-                            Err(..) => break,
-                        }
-                    }
-                }
-            }
+#[test]
+fn verify_checksum_chunk_checks_data_crc() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    for &s in &[1_i16, -2, 3, -4] {
+        writer.write_sample(s).unwrap();
+    }
+    let wav = writer.finalize_into_bytes().unwrap();
+    let mut crc = Crc32::new();
+    crc.update(&wav[80..]);
+    let mut cksm = b"cksm\x04\x00\x00\x00".to_vec();
+    cksm.write_le_u32(crc.value()).unwrap();
+
+    // The checksum chunk may follow or precede the data chunk.
+    let mut after = wav.clone();
+    after.extend_from_slice(&cksm);
+    let mut before = wav[..72].to_vec();
+    before.extend_from_slice(&cksm);
+    before.extend_from_slice(&wav[72..]);
+
+    let options = ReadOptions { verify_checksum_chunk: true, ..ReadOptions::default() };
+    for bytes in &[after, before] {
+        let mut reader = WavReader::new_with_options(&bytes[..], options).unwrap();
+        let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, [1, -2, 3, -4]);
+
+        // A changed sample is detected at the end of the data.
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1 - if bytes.ends_with(&cksm) { 12 } else { 0 };
+        corrupted[last] ^= 1;
+        let mut reader = WavReader::new_with_options(&corrupted[..], options).unwrap();
+        let mut samples = reader.samples::<i16>();
+        for _ in 0..4 {
+            samples.next().unwrap().unwrap();
         }
+        match samples.next() {
+            Some(Err(Error::FormatError("checksum mismatch"))) => {}
+            _ => panic!("expected checksum mismatch"),
+        }
+        assert!(samples.next().is_none());
+    }
+
+    let mut reader = WavReader::new_with_options(&wav[..], options).unwrap();
+    match reader.samples::<i16>().collect::<Result<Vec<_>>>() {
+        Err(Error::FormatError("checksum chunk not found")) => {}
+        _ => panic!("expected missing checksum chunk"),
     }
+
+    // Without the option, the chunk is ignored.
+    let mut corrupted = wav.clone();
+    corrupted[44] ^= 1;
+    corrupted.extend_from_slice(&cksm);
+    let mut reader = WavReader::new(&corrupted[..]).unwrap();
+    assert_eq!(reader.samples::<i16>().count(), 4);
 }
 
 #[test]
-fn seek_is_consistent() {
-    let files = &["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
-                  "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
-                  "testsamples/waveformatextensible-32bit-48kHz-stereo.wav"];
-    for fname in files {
-        let mut reader = WavReader::open(fname).unwrap();
-        // Seeking back to the start should "reset" the reader.
-        let count = reader.samples::<i32>().count();
-        reader.seek(0).unwrap();
-        assert_eq!(count, reader.samples::<i32>().count());
+fn fact_samples_are_exposed() {
+    let reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    assert_eq!(reader.fact_samples(), None);
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    for s in 0..8_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    let wav = writer.finalize_into_bytes().unwrap();
+
+    // The data holds four frames, but the last one is padding.
+    let mut bytes = wav[..72].to_vec();
+    bytes.extend_from_slice(b"fact");
+    bytes.extend_from_slice(&[4, 0, 0, 0]);
+    bytes.extend_from_slice(&[3, 0, 0, 0]);
+    bytes.extend_from_slice(&wav[72..]);
+
+    let reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.fact_samples(), Some(3));
+    assert_eq!(reader.duration(), 4);
+    assert_eq!(reader.len(), 8);
+}
 
-        // Seek to the last sample.
-        let last_time = reader.duration() - 1;
-        let channels = reader.spec().channels;
-        reader.seek(last_time).unwrap();
-        {
-            let mut samples = reader.samples::<i32>();
-            for _ in 0..channels {
-                assert!(samples.next().is_some());
-            }
-            assert!(samples.next().is_none());
+#[test]
+fn slices_are_read_from_strc_chunk() {
+    use std::io::Cursor;
+
+    let reader = WavReader::open("testsamples/pcmwaveformat-16bit-44100Hz-mono.wav").unwrap();
+    assert_eq!(reader.slices(), &[]);
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = ::WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(7_i16).unwrap();
+    let wav = writer.finalize_into_bytes().unwrap();
+
+    let positions = [0_u64, 11025, 0x1_0000_0000];
+    for &entry_len in &[32, 24] {
+        let mut strc = Vec::new();
+        strc.write_le_u32(28).unwrap();
+        strc.write_le_u32(positions.len() as u32).unwrap();
+        strc.extend_from_slice(&[0; 20]);
+        for &position in &positions {
+            let mut entry = vec![0u8; entry_len];
+            (&mut entry[8..]).write_le_u32(position as u32).unwrap();
+            (&mut entry[12..]).write_le_u32((position >> 32) as u32).unwrap();
+            strc.extend_from_slice(&entry);
         }
 
-        // Seeking beyond the audio data produces no samples.
-        let num_samples = reader.len();
-        reader.seek(num_samples).unwrap();
-        assert!(reader.samples::<i32>().next().is_none());
-        reader.seek(::std::u32::MAX / channels as u32).unwrap();
-        assert!(reader.samples::<i32>().next().is_none());
+        let mut bytes = wav[..72].to_vec();
+        bytes.extend_from_slice(b"strc");
+        bytes.write_le_u32(strc.len() as u32).unwrap();
+        bytes.extend_from_slice(&strc);
+        bytes.extend_from_slice(&wav[72..]);
+
+        let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+        let found: Vec<u64> = reader.slices().iter().map(|s| s.position).collect();
+        assert_eq!(found, positions);
+        let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, [7]);
     }
+
+    // A layout that is not understood yields no slices.
+    assert_eq!(parse_strc(&[0; 12]), []);
+    let mut strc = vec![0u8; 28 + 8];
+    strc[4] = 1;
+    assert_eq!(parse_strc(&strc), []);
 }
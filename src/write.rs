@@ -10,13 +10,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp;
 use std::fs;
 use std::io;
 use std::mem;
 use std::io::{Seek, Write};
 use std::mem::MaybeUninit;
 use std::path;
-use super::{Error, Result, Sample, SampleFormat, WavSpec};
+use super::{Error, Metadata, RawChunk, Result, Sample, SampleFormat, WavSpec, SUPPORTED_FORMATS};
+use crc::Crc32;
 use ::read;
 use read::{WavSpecEx};
 
@@ -54,6 +56,9 @@ pub trait WriteExt: io::Write {
     /// Writes an unsigned 32-bit integer in little endian format.
     fn write_le_u32(&mut self, x: u32) -> io::Result<()>;
 
+    /// Writes an unsigned 64-bit integer in little endian format.
+    fn write_le_u64(&mut self, x: u64) -> io::Result<()>;
+
     /// Writes an IEEE float in little endian format.
     fn write_le_f32(&mut self, x: f32) -> io::Result<()>;
 }
@@ -114,10 +119,17 @@ impl<W> WriteExt for W
         self.write_all(&buf)
     }
 
+    #[inline(always)]
+    fn write_le_u64(&mut self, x: u64) -> io::Result<()> {
+        try!(self.write_le_u32((x & 0xffff_ffff) as u32));
+        self.write_le_u32((x >> 32) as u32)
+    }
+
     #[inline(always)]
     fn write_le_f32(&mut self, x: f32) -> io::Result<()> {
-        let u = unsafe { mem::transmute::<f32, u32>(x) };
-        self.write_le_u32(u)
+        // The bit pattern does not depend on the byte order of the host, the
+        // bytes are then written in little endian order explicitly.
+        self.write_le_u32(x.to_bits())
     }
 }
 
@@ -204,32 +216,163 @@ impl<'w, W: 'w + io::Write + io::Seek> Drop for EmbeddedWriter<'w, W> {
     }
 }
 
+/// The size of the body of a `ds64` chunk without a table.
+///
+/// The chunk holds three 64-bit sizes (RIFF size, data size, and sample count)
+/// followed by a 32-bit table length.
+const DS64_LEN: u32 = 28;
+
 #[derive(Copy,Clone)]
 pub struct ChunkWritingState {
-    pub len: u32
+    pub len: u64
 }
 
 impl ChunkWritingState {
     pub fn write<W: io::Write + io::Seek>(&mut self, writer: &mut W, buf:&[u8]) -> io::Result<usize> {
         let written = try!(writer.write(buf));
-        self.len += written as u32;
+        self.len += written as u64;
         Ok(written)
     }
 
     pub fn update_header<W: io::Write + io::Seek>(&mut self, writer: &mut W) -> io::Result<()> {
+        if self.len > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::Other, "chunk too large for a 32-bit size"));
+        }
+        self.write_size_field(writer, self.len as u32)
+    }
+
+    /// Writes `size` into the size field of the chunk.
+    ///
+    /// The writer must be positioned at the end of the chunk body, and it is
+    /// positioned there again afterwards.
+    fn write_size_field<W: io::Write + io::Seek>(&self, writer: &mut W, size: u32) -> io::Result<()> {
         try!(writer.seek(io::SeekFrom::Current(-(self.len as i64 + 4))));
-        try!(writer.write_le_u32(self.len));
+        try!(writer.write_le_u32(size));
         try!(writer.seek(io::SeekFrom::Current(self.len as i64)));
         Ok(())
     }
 
-    pub fn finalize_chunk<W: io::Write + io::Seek>(&mut self, writer: &mut W) -> io::Result<()> {
-        try!(self.update_header(writer));
+    /// Writes the padding byte that follows a chunk of odd size.
+    fn write_padding<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
         if self.len % 2 == 1 {
             try!(writer.write_u8(0));
         }
         Ok(())
     }
+
+    pub fn finalize_chunk<W: io::Write + io::Seek>(&mut self, writer: &mut W) -> io::Result<()> {
+        try!(self.update_header(writer));
+        self.write_padding(writer)
+    }
+}
+
+/// Writes a chunk header: the identifier, followed by the size of the body.
+fn write_chunk_header<W: io::Write>(writer: &mut W, fourcc: [u8; 4], size: u32) -> io::Result<()> {
+    try!(writer.write_all(&fourcc));
+    try!(writer.write_le_u32(size));
+    Ok(())
+}
+
+/// Writes the header of a RIFF file, `tag` is `RIFF` or `RF64`.
+fn write_riff_header<W: io::Write>(writer: &mut W, tag: [u8; 4], size: u32, form_type: [u8; 4])
+                                   -> io::Result<()> {
+    try!(write_chunk_header(writer, tag, size));
+    try!(writer.write_all(&form_type));
+    Ok(())
+}
+
+/// Writes a `ds64` chunk with the 64-bit sizes of an RF64 file.
+fn write_ds64_chunk<W: io::Write>(writer: &mut W, riff_len: u64, data_len: u64, num_frames: u64)
+                                  -> io::Result<()> {
+    try!(write_chunk_header(writer, *b"ds64", DS64_LEN));
+    try!(writer.write_le_u64(riff_len));
+    try!(writer.write_le_u64(data_len));
+    try!(writer.write_le_u64(num_frames));
+    // The table length; there are no table entries.
+    try!(writer.write_le_u32(0));
+    Ok(())
+}
+
+/// A writer for RIFF files of any form type, such as AVI or ANI.
+///
+/// The writer starts a `RIFF` chunk of the given form type. Chunks can then be
+/// started with `start_chunk()` and `start_list()`, and they can be nested: a
+/// chunk started while another one is open becomes part of it. Bytes written
+/// through the `io::Write` implementation go into the innermost open chunk.
+/// When a chunk is ended, its size field is patched, and a padding byte is
+/// added if the size is odd. The chunk headers and sizes are written the same
+/// way as by `ChunksWriter`, which `WavWriter` is built on. For WAVE files,
+/// prefer `WavWriter`.
+pub struct RiffWriter<W: io::Write + io::Seek> {
+    /// underlying writer
+    writer: W,
+    /// offsets of the headers of all open chunks, outermost first
+    open_chunks: Vec<u64>,
+}
+
+impl<W: io::Write + io::Seek> RiffWriter<W> {
+    /// Starts writing a RIFF file with the given form type, such as `*b"AVI "`.
+    pub fn new(mut writer: W, form_type: [u8; 4]) -> Result<RiffWriter<W>> {
+        let offset = try!(writer.seek(io::SeekFrom::Current(0)));
+        try!(write_riff_header(&mut writer, *b"RIFF", 0, form_type));
+        Ok(RiffWriter {
+            writer: writer,
+            open_chunks: vec![offset],
+        })
+    }
+
+    /// Starts a chunk with the given identifier, inside the innermost open chunk.
+    pub fn start_chunk(&mut self, fourcc: [u8; 4]) -> Result<()> {
+        let offset = try!(self.writer.seek(io::SeekFrom::Current(0)));
+        try!(write_chunk_header(&mut self.writer, fourcc, 0));
+        self.open_chunks.push(offset);
+        Ok(())
+    }
+
+    /// Starts a `LIST` chunk with the given list type, such as `*b"INFO"`.
+    pub fn start_list(&mut self, list_type: [u8; 4]) -> Result<()> {
+        try!(self.start_chunk(*b"LIST"));
+        try!(self.writer.write_all(&list_type));
+        Ok(())
+    }
+
+    /// Ends the innermost open chunk, and writes its size.
+    ///
+    /// The outermost `RIFF` chunk is ended by `finalize()`, calling this
+    /// method when only that chunk is open returns an error.
+    pub fn end_chunk(&mut self) -> Result<()> {
+        if self.open_chunks.len() < 2 {
+            return Err(Error::FormatError("no chunk to end"));
+        }
+        self.end_innermost_chunk()
+    }
+
+    fn end_innermost_chunk(&mut self) -> Result<()> {
+        let offset = self.open_chunks.pop().expect("there is an open chunk");
+        let end = try!(self.writer.seek(io::SeekFrom::Current(0)));
+        let mut state = ChunkWritingState { len: end - offset - 8 };
+        try!(state.finalize_chunk(&mut self.writer));
+        Ok(())
+    }
+
+    /// Ends all open chunks, including the `RIFF` chunk, and returns the underlying writer.
+    pub fn finalize(mut self) -> Result<W> {
+        while !self.open_chunks.is_empty() {
+            try!(self.end_innermost_chunk());
+        }
+        try!(self.writer.flush());
+        Ok(self.writer)
+    }
+}
+
+impl<W: io::Write + io::Seek> io::Write for RiffWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 /// A Riff chunk Wave writer, allowing to write arbitrary chunks to a file.
@@ -244,6 +387,12 @@ pub struct ChunksWriter<W: io::Write + io::Seek> {
     pub data_state: Option<ChunkWritingState>,
     dirty: bool,
     sample_writer_buffer: Vec<MaybeUninit<u8>>,
+    /// whether a `JUNK` chunk was reserved that can be turned into `ds64`
+    rf64_capable: bool,
+    /// length of the data chunk as of the last header update
+    data_len: u64,
+    /// checksum of the data chunk, if it is being computed
+    data_crc: Option<Crc32>,
 }
 
 impl<W: io::Write + io::Seek> ChunksWriter<W> {
@@ -252,25 +401,72 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
     /// Write the RIFF header (including a len placeholder). The writer
     /// is then ready to start writing the first chunk.
     pub fn new(mut writer: W) -> Result<ChunksWriter<W>> {
-        try!(writer.write_all(b"RIFF\0\0\0\0WAVE"));
+        try!(write_riff_header(&mut writer, *b"RIFF", 0, *b"WAVE"));
+        Ok(ChunksWriter {
+            writer: Some(writer),
+            spec_ex: None,
+            dirty: false,
+            data_state: None,
+            sample_writer_buffer: vec!(),
+            rf64_capable: false,
+            data_len: 0,
+            data_crc: None,
+        })
+    }
+
+    /// Creates a ChunksWriter that switches to RF64 for files over 4 GiB.
+    ///
+    /// Write the RIFF header, followed by a `JUNK` chunk that reserves space
+    /// for a `ds64` chunk. When the headers are updated and the file has grown
+    /// beyond what 32-bit sizes can express, the RIFF header is rewritten to
+    /// an RF64 header, and the `JUNK` chunk is replaced by a `ds64` chunk that
+    /// holds the 64-bit sizes. Smaller files remain standard RIFF files, with
+    /// an unused `JUNK` chunk.
+    pub fn new_rf64_capable(mut writer: W) -> Result<ChunksWriter<W>> {
+        try!(write_riff_header(&mut writer, *b"RIFF", 0, *b"WAVE"));
+        try!(write_chunk_header(&mut writer, *b"JUNK", DS64_LEN));
+        try!(writer.write_all(&[0u8; DS64_LEN as usize]));
         Ok(ChunksWriter {
             writer: Some(writer),
             spec_ex: None,
             dirty: false,
             data_state: None,
             sample_writer_buffer: vec!(),
+            rf64_capable: true,
+            data_len: 0,
+            data_crc: None,
         })
     }
 
     /// Update the file length field in the RIFF header.
     ///
+    /// If the file is too large for a 32-bit size and space for a `ds64`
+    /// chunk was reserved, the header is upgraded to RF64.
+    ///
     /// The writer is then repositioned at end of file.
-    fn update_riff_header(&mut self) -> io::Result<()> {
+    fn update_riff_header(&mut self) -> Result<()> {
+        let spec_ex = self.spec_ex;
+        let data_len = self.data_len;
+        let rf64_capable = self.rf64_capable;
         if let Some(writer) = &mut self.writer {
             let full_len = try!(writer.seek(io::SeekFrom::Current(0)));
-            try!(writer.seek(io::SeekFrom::Start(4)));
-            try!(writer.write_le_u32(full_len as u32 - 8));
-            try!(writer.seek(io::SeekFrom::Current(full_len as i64 - 8)));
+            let riff_len = full_len - 8;
+            if riff_len <= u32::MAX as u64 {
+                let riff = ChunkWritingState { len: riff_len };
+                try!(riff.write_size_field(writer, riff_len as u32));
+            } else if rf64_capable {
+                let num_frames = match spec_ex {
+                    Some(spec_ex) => data_len / (spec_ex.bytes_per_sample as u64
+                                                 * spec_ex.spec.channels as u64),
+                    None => 0,
+                };
+                try!(writer.seek(io::SeekFrom::Start(0)));
+                try!(write_riff_header(writer, *b"RF64", 0xffff_ffff, *b"WAVE"));
+                try!(write_ds64_chunk(writer, riff_len, data_len, num_frames));
+            } else {
+                return Err(Error::FormatError("file too large for RIFF, RF64 is required"));
+            }
+            try!(writer.seek(io::SeekFrom::Start(full_len)));
         } else {
             panic!("Writer is not available");
         }
@@ -290,20 +486,32 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
     fn update_data_chunk_header(&mut self) -> Result<()> {
         let data_state = self.data_state.expect("Should only be called in data chunk");
         let spec_ex = self.spec_ex.expect("Data chunk implies known format");
+
+        // For RF64, the real size is stored in the ds64 chunk, and the size
+        // field of the data chunk is set to the maximum value.
+        let size_field = if data_state.len <= u32::MAX as u64 {
+            data_state.len as u32
+        } else if self.rf64_capable {
+            0xffff_ffff
+        } else {
+            return Err(Error::FormatError("data chunk too large for RIFF, RF64 is required"));
+        };
         if let Some(writer) = &mut self.writer {
-            try!(writer.seek(io::SeekFrom::End(-(data_state.len as i64 + 4))));
-            try!(writer.write_le_u32(data_state.len));
-            try!(writer.seek(io::SeekFrom::End(0)));
+            try!(data_state.write_size_field(writer, size_field));
         } else {
             panic!("Writer is not available");
         }
+        self.data_len = data_state.len;
 
         // Signal error if the last sample was not finished, but do so after
         // everything has been written, so that no data is lost, even though
         // the file is now ill-formed.
-        if (data_state.len / spec_ex.bytes_per_sample as u32)
-            % spec_ex.spec.channels as u32 != 0 {
-            Err(Error::UnfinishedSample)
+        let num_samples = data_state.len / spec_ex.bytes_per_sample as u64;
+        if num_samples % spec_ex.spec.channels as u64 != 0 {
+            Err(Error::UnfinishedSample {
+                samples: num_samples,
+                channels: spec_ex.spec.channels,
+            })
         } else {
             Ok(())
         }
@@ -314,13 +522,22 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
     /// The function returns an `EmbeddedWriter` that must be used to write
     /// the chunk content. It will take care of maintaining the chunk length in
     /// the chunk header.
+    ///
+    /// If the data chunk was being written, its header is updated first, and
+    /// no more samples can be written afterwards.
     pub fn start_chunk(&mut self, fourcc: [u8; 4]) -> Result<EmbeddedWriter<W>> {
+        if let Some(data_state) = self.data_state {
+            try!(self.update_data_chunk_header());
+            // Chunks must start at an even offset.
+            if let Some(writer) = &mut self.writer {
+                try!(data_state.write_padding(writer));
+            }
+        }
         self.data_state = None;
         self.dirty = true;
     
         if let Some(writer) = &mut self.writer {
-            try!(writer.write_all(&fourcc));
-            try!(writer.write_le_u32(0));
+            try!(write_chunk_header(writer, fourcc, 0));
         } else {
             panic!("Writer is not available");
         }
@@ -338,8 +555,7 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
             panic!("Format must be written before data");
         }
         if let Some(writer) = &mut self.writer {
-            try!(writer.write_all(b"data"));
-            try!(writer.write_le_u32(0));
+            try!(write_chunk_header(writer, *b"data", 0));
         } else {
             panic!("Writer is not available");
         }
@@ -349,6 +565,26 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
         Ok(())
     }
 
+    /// Writes a chunk in front of the data chunk, which must still be empty.
+    ///
+    /// The header of the data chunk is overwritten by the new chunk, and then
+    /// written again after it. This is how metadata that readers look for
+    /// before the samples can be added after the data chunk was started.
+    fn insert_chunk_before_data(&mut self, chunk: &RawChunk) -> Result<()> {
+        assert_eq!(self.data_state.map(|state| state.len), Some(0),
+                   "Chunks can only be inserted before an empty data chunk.");
+        if let Some(writer) = &mut self.writer {
+            try!(writer.seek(io::SeekFrom::Current(-8)));
+            try!(write_chunk_header(writer, chunk.id, chunk.data.len() as u32));
+            try!(writer.write_all(&chunk.data));
+            // Chunks are padded to an even size.
+            try!(ChunkWritingState { len: chunk.data.len() as u64 }.write_padding(writer));
+        } else {
+            panic!("Writer is not available");
+        }
+        self.start_data_chunk()
+    }
+
     /// Update RIFF and data chunk header
     pub fn update_headers(&mut self) -> Result<()> {
         if self.data_state.is_some() {
@@ -385,14 +621,24 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
         // the writer is dropped: for a buffered writer, the write to the buffer
         // may succeed, but the write to the underlying writer may fail. So
         // flush explicitly.
-        try!(self.flush());
-        Ok(())
+        let result = self.flush();
+        // Drop the underlying writer now, so the destructor does not update
+        // the headers a second time, not even when flushing failed.
+        self.writer.take();
+        result
     }
 
     /// Encode and write the provided spec as a format header in the stream.
+    ///
+    /// The spec is checked with `WavSpec::validate()` first.
     pub fn write_fmt(&mut self, spec_ex: WavSpecEx) -> Result<()> {
         let spec = spec_ex.spec;
 
+        // Hound can only write some formats. If something else was requested,
+        // fail early, rather than writing a header but then failing at the
+        // first sample.
+        try!(spec.validate());
+
         // Write the older PCMWAVEFORMAT structure if possible, because it is
         // more widely supported. For more than two channels or more than 16
         // bits per sample, the newer WAVEFORMATEXTENSIBLE is required. See also
@@ -403,21 +649,6 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
             FmtKind::PcmWaveFormat
         };
 
-        // Hound can only write those bit depths. If something else was
-        // requested, fail early, rather than writing a header but then failing
-        // at the first sample.
-        let supported = match spec.bits_per_sample {
-            8 => true,
-            16 => true,
-            24 => true,
-            32 => true,
-            _ => false,
-        };
-
-        if !supported {
-            return Err(Error::Unsupported)
-        }
-
         let mut header = [0u8; 48];
         if let Some(writer) = &mut self.writer {
             try!(writer.write(b"fmt "));
@@ -559,17 +790,72 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
     /// sample does not fit in the number of bits specified in the `WavSpec`.
     #[inline]
     pub fn write_sample<S: Sample>(&mut self, sample: S) -> Result<()> {
+        self.write_sample_impl(sample, false)
+    }
+
+    /// Writes a single sample, scaled from the full range of `S` to the full
+    /// range of the bits per sample of the file.
+    ///
+    /// See `WavWriter::with_scaling()` for more info.
+    #[inline]
+    pub fn write_sample_scaled<S: Sample>(&mut self, sample: S) -> Result<()> {
+        self.write_sample_impl(sample, true)
+    }
+
+    #[inline(always)]
+    fn write_sample_impl<S: Sample>(&mut self, sample: S, scaled: bool) -> Result<()> {
         let spec_ex = self.spec_ex.expect("Format should have written before this call");
         if let Some(writer) = &mut self.writer {
-            try!(sample.write_padded(
-                writer,
-                spec_ex.spec.bits_per_sample,
-                spec_ex.bytes_per_sample
-            ));
+            let bits = spec_ex.spec.bits_per_sample;
+            let byte_width = spec_ex.bytes_per_sample;
+            if let Some(ref mut crc) = self.data_crc {
+                // Encode to a buffer first, so the bytes can be checksummed.
+                let mut buffer = [0u8; 8];
+                {
+                    let mut slice = &mut buffer[..];
+                    if scaled {
+                        try!(sample.write_scaled(&mut slice, bits, byte_width));
+                    } else {
+                        try!(sample.write_padded(&mut slice, bits, byte_width));
+                    }
+                }
+                let bytes = &buffer[..byte_width as usize];
+                crc.update(bytes);
+                try!(writer.write_all(bytes));
+            } else if scaled {
+                try!(sample.write_scaled(writer, bits, byte_width));
+            } else {
+                try!(sample.write_padded(writer, bits, byte_width));
+            }
         } else {
             panic!("Writer is not available");
         }
-        let written = spec_ex.bytes_per_sample as u32;
+        let written = spec_ex.bytes_per_sample as u64;
+        self.data_state.as_mut().expect("Can only be called positioned in data chunk").len += written;
+        Ok(())
+    }
+
+    /// Writes raw, already encoded samples to the data chunk.
+    ///
+    /// The bytes are written as-is, so they must be little-endian samples in
+    /// the layout described by the spec (including the unsigned
+    /// representation of 8-bit samples). This will return an error if `bytes`
+    /// does not contain a whole number of samples, in which case nothing is
+    /// written.
+    pub fn write_raw(&mut self, bytes: &[u8]) -> Result<()> {
+        let spec_ex = self.spec_ex.expect("Format should have written before this call");
+        if bytes.len() % spec_ex.bytes_per_sample as usize != 0 {
+            return Err(Error::FormatError("raw data is not a whole number of samples"));
+        }
+        if let Some(writer) = &mut self.writer {
+            try!(writer.write_all(bytes));
+        } else {
+            panic!("Writer is not available");
+        }
+        if let Some(ref mut crc) = self.data_crc {
+            crc.update(bytes);
+        }
+        let written = bytes.len() as u64;
         self.data_state.as_mut().expect("Can only be called positioned in data chunk").len += written;
         Ok(())
     }
@@ -620,6 +906,7 @@ impl<W: io::Write + io::Seek> ChunksWriter<W> {
                 buffer: &mut self.sample_writer_buffer[..num_bytes],
                 data_bytes_written:
                     &mut self.data_state.as_mut().expect("Can only be called positioned in data chunk").len,
+                data_crc: self.data_crc.as_mut(),
                 index: 0,
             }
         } else {
@@ -662,6 +949,30 @@ pub struct WavWriter<W>
 {
     /// The writer that will be written to.
     writer: ChunksWriter<W>,
+    /// Whether samples are scaled to the bit depth of the file.
+    scale_samples: bool,
+    /// Whether out-of-range normalized samples are an error, rather than clamped.
+    strict_normalized: bool,
+    /// Labeled regions to write in `cue ` and `LIST` chunks on finalize.
+    regions: Vec<Region>,
+    /// The title to write in a `DISP` chunk on finalize, if it was set after samples.
+    display_title: Option<String>,
+    /// The text metadata to write in a `LIST` chunk of type `INFO` on
+    /// finalize, if it was set after samples.
+    metadata: Option<Metadata>,
+    /// Whether to write a `fact` chunk on finalize.
+    write_fact: bool,
+    /// Chunks to write as-is after the data chunk on finalize.
+    trailing_chunks: Vec<RawChunk>,
+    /// The number of bytes that the data chunk length is padded to a multiple of.
+    data_alignment: u64,
+}
+
+/// A labeled range of samples, written as a cue point with `ltxt` and `labl` entries.
+struct Region {
+    start: u32,
+    length: u32,
+    label: String,
 }
 
 impl<W> WavWriter<W>
@@ -675,11 +986,22 @@ impl<W> WavWriter<W>
     /// does this automatically.
     ///
     /// This writes parts of the header immediately, hence a `Result` is
-    /// returned.
+    /// returned. A spec that hound cannot write is rejected before anything
+    /// is written, see `WavSpec::validate()`.
+    ///
+    /// The size fields in a standard RIFF header are 32 bits wide, which
+    /// limits files to 4 GiB. The RF64 (or BW64) format stores 64-bit sizes in
+    /// a `ds64` chunk instead. The writer reserves space for that chunk with a
+    /// `JUNK` chunk at the start of the file. If the file turns out to be
+    /// larger than 4 GiB when it is finalized, the header is upgraded to RF64.
+    /// Otherwise the file remains a standard RIFF file, in which the unused
+    /// `JUNK` chunk precedes the `fmt ` chunk, so the data chunk does not
+    /// start at byte 44. For the minimal header that some software expects,
+    /// see `StreamingWavWriter` and `canonical_header()`.
     pub fn new(writer: W, spec: WavSpec) -> Result<WavWriter<W>> {
         let spec_ex = WavSpecEx {
             spec: spec,
-            bytes_per_sample: (spec.bits_per_sample + 7) / 8,
+            bytes_per_sample: i32::sample_bytes(spec.bits_per_sample),
         };
         Self::new_with_spec_ex(writer, spec_ex)
     }
@@ -692,12 +1014,80 @@ impl<W> WavWriter<W>
     /// does this automatically.
     ///
     /// This writes parts of the header immediately, hence a `Result` is
-    /// returned.
+    /// returned. Like `new()`, this switches to RF64 for files over 4 GiB.
     pub fn new_with_spec_ex(writer: W, spec: WavSpecEx) -> Result<WavWriter<W>> {
-        let mut chunks_writer = try!(ChunksWriter::new(writer));
+        try!(spec.spec.validate());
+        let mut chunks_writer = try!(ChunksWriter::new_rf64_capable(writer));
         try!(chunks_writer.write_fmt(spec));
         try!(chunks_writer.start_data_chunk());
-        Ok(WavWriter { writer: chunks_writer })
+        Ok(WavWriter { writer: chunks_writer, scale_samples: false,
+                       strict_normalized: false, regions: Vec::new(),
+                       display_title: None, metadata: None, write_fact: false,
+                       trailing_chunks: Vec::new(), data_alignment: 1 })
+    }
+
+    /// Creates a writer that writes the given chunks, with new samples for the data chunk.
+    ///
+    /// The chunks are written as-is and in order, so together with
+    /// `ReadOptions::preserve_chunks`, this can write back a file unchanged,
+    /// apart from the chunks or samples that were modified. The chunks before
+    /// the data chunk marker (see `RawChunk`) are written immediately, then
+    /// the data chunk is started, and the chunks after the marker are written
+    /// by `finalize()`, before any metadata added to this writer. If there is
+    /// no `fmt ` chunk before the marker, one is written for `spec` first.
+    /// Otherwise, the `fmt ` chunk must describe `spec`, or else
+    /// `Error::IncompatibleSpec` is returned. A `fact` chunk holds the number
+    /// of samples, so it is not copied; a new one is written after the data
+    /// chunk instead, as with `always_write_fact()`.
+    ///
+    /// For a file that was read with `ReadOptions::preserve_chunks`, and
+    /// written with all of its samples, the result is identical to the
+    /// original, with a few exceptions: RF64 files are written as standard
+    /// RIFF files, a `wavl` wave list is written as a data chunk, a `fact`
+    /// chunk is moved after the other chunks, and when the data chunk is the
+    /// last chunk and has an odd size, it is written without a padding byte.
+    pub fn new_with_chunks(writer: W, spec: WavSpec, chunks: &[RawChunk]) -> Result<WavWriter<W>> {
+        let mut spec_ex = WavSpecEx {
+            spec: spec,
+            bytes_per_sample: i32::sample_bytes(spec.bits_per_sample),
+        };
+        let data_index = chunks.iter().position(|c| &c.id == b"data").unwrap_or(chunks.len());
+        let (leading, trailing) = chunks.split_at(data_index);
+
+        try!(spec.validate());
+        let fmt_chunk = leading.iter().find(|c| &c.id == b"fmt ");
+        if let Some(chunk) = fmt_chunk {
+            // The container size may differ from the one that would be
+            // derived from the bits per sample, so take it from the chunk.
+            spec_ex = try!(read::parse_fmt_chunk(&chunk.data));
+            if spec_ex.spec != spec {
+                return Err(Error::IncompatibleSpec);
+            }
+        }
+
+        let mut chunks_writer = try!(ChunksWriter::new(writer));
+        if fmt_chunk.is_none() {
+            try!(chunks_writer.write_fmt(spec_ex));
+        }
+        // The sample count in a `fact` chunk would be stale, so it is not
+        // copied, but written anew after the data.
+        let write_fact = chunks.iter().any(|c| &c.id == b"fact");
+        for chunk in leading.iter().filter(|c| &c.id != b"fact") {
+            if &chunk.id == b"fmt " {
+                chunks_writer.spec_ex = Some(spec_ex);
+            }
+            let mut embedded = try!(chunks_writer.start_chunk(chunk.id));
+            try!(embedded.write_all(&chunk.data));
+            try!(embedded.finalize());
+        }
+        try!(chunks_writer.start_data_chunk());
+
+        // Skip the data chunk marker itself.
+        let trailing = trailing.iter().skip(1).filter(|c| &c.id != b"fact").cloned().collect();
+        Ok(WavWriter { writer: chunks_writer, scale_samples: false,
+                       strict_normalized: false, regions: Vec::new(),
+                       display_title: None, metadata: None, write_fact: write_fact,
+                       trailing_chunks: trailing, data_alignment: 1 })
     }
 
     /// Writes a single sample for one channel.
@@ -707,7 +1097,223 @@ impl<W> WavWriter<W>
     /// sample does not fit in the number of bits specified in the `WavSpec`.
     #[inline]
     pub fn write_sample<S: Sample>(&mut self, sample: S) -> Result<()> {
-        self.writer.write_sample(sample)
+        if self.scale_samples {
+            self.writer.write_sample_scaled(sample)
+        } else {
+            self.writer.write_sample(sample)
+        }
+    }
+
+    /// Makes the writer scale samples to the bits per sample of the file.
+    ///
+    /// By default, `write_sample()` stores the value of a sample as-is: an
+    /// `i16` with value 1000 is stored as 1000, also in a 24-bit file, and a
+    /// sample that does not fit in the bits per sample is an error. With
+    /// scaling enabled, integer samples are instead mapped from the full range
+    /// of the sample type to the full range of the file, by shifting: an
+    /// `i16` of `i16::MAX` is stored as `0x7fff00` in a 24-bit file, which is
+    /// full scale. When the file has fewer bits than the sample type, the low
+    /// bits are truncated. Float samples are not affected.
+    ///
+    /// Scaling does not apply to `write_raw()`, nor to the writer returned by
+    /// `get_i16_writer()`.
+    pub fn with_scaling(mut self) -> WavWriter<W> {
+        self.scale_samples = true;
+        self
+    }
+
+    /// Writes a single sample in the range [-1.0, 1.0], mapped to the full range of the file.
+    ///
+    /// For integer files, 1.0 maps to the largest value of the bits per
+    /// sample, and -1.0 to the smallest: for 16 bits, that is `i16::MAX` and
+    /// `i16::MIN`. Positive and negative values are scaled separately, so 0.0
+    /// maps to 0, and the result is rounded to the nearest integer. Values
+    /// outside of the range are clamped to the nearest bound, and NaN is
+    /// written as 0, unless `with_strict_normalized()` is used, in which case
+    /// `Error::TooWide` is returned for such values. For float files, the
+    /// value is written as-is, apart from that check in strict mode.
+    pub fn write_sample_normalized(&mut self, x: f32) -> Result<()> {
+        let in_range = x >= -1.0 && x <= 1.0;
+        if self.strict_normalized && !in_range {
+            return Err(Error::TooWide);
+        }
+        let spec = self.spec();
+        if spec.sample_format == SampleFormat::Float {
+            return self.writer.write_sample(x);
+        }
+        let x = if x.is_nan() { 0.0 } else { x.max(-1.0).min(1.0) as f64 };
+        let bits = spec.bits_per_sample as i32;
+        let value = if x >= 0.0 {
+            (x * ((1_i64 << (bits - 1)) - 1) as f64).round()
+        } else {
+            (x * (1_i64 << (bits - 1)) as f64).round()
+        };
+        self.writer.write_sample(value as i32)
+    }
+
+    /// Makes `write_sample_normalized()` return an error for values outside [-1.0, 1.0].
+    ///
+    /// By default, such values are clamped.
+    pub fn with_strict_normalized(mut self) -> WavWriter<W> {
+        self.strict_normalized = true;
+        self
+    }
+
+    /// Makes the writer compute a CRC-32 checksum of the data chunk.
+    ///
+    /// The checksum covers the samples as they are stored, so it is equal to
+    /// `WavReader::data_crc32()` of the finished file, and it does not depend
+    /// on metadata. It can be obtained with `data_crc32()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if samples have been written already, which includes the
+    /// samples of a file that is being appended to.
+    pub fn with_data_crc32(mut self) -> WavWriter<W> {
+        assert_eq!(self.len(), 0, "The data checksum must be enabled before writing samples.");
+        self.writer.data_crc = Some(Crc32::new());
+        self
+    }
+
+    /// Returns the CRC-32 checksum of the samples written so far.
+    ///
+    /// This is `None` unless `with_data_crc32()` was called.
+    pub fn data_crc32(&self) -> Option<u32> {
+        self.writer.data_crc.as_ref().map(|crc| crc.value())
+    }
+
+    /// Makes `finalize()` write a `fact` chunk with the number of samples per channel.
+    ///
+    /// The `fact` chunk is only required for compressed formats, so it is
+    /// usually omitted for the formats that Hound writes, but some strict
+    /// validators expect it. The chunk is written after the data chunk, and
+    /// contains the final `duration()`.
+    pub fn always_write_fact(mut self) -> WavWriter<W> {
+        self.write_fact = true;
+        self
+    }
+
+    /// Writes all samples that `samples` yields.
+    ///
+    /// The samples are encoded into a buffer, which is written in blocks, so
+    /// this is faster than calling `write_sample()` for every sample. If a
+    /// sample cannot be encoded, for instance because it does not fit, the
+    /// samples before it are written, and the error is returned. Writing stops
+    /// at the first IO error.
+    pub fn write_all<S, I>(&mut self, samples: I) -> Result<()>
+        where S: Sample,
+              I: IntoIterator<Item = S>
+    {
+        const BLOCK_SAMPLES: usize = 4096;
+        let spec_ex = self.writer.spec_ex.expect("ChunkWriter with no spec");
+        let bits = spec_ex.spec.bits_per_sample;
+        let byte_width = spec_ex.bytes_per_sample;
+        let block_len = BLOCK_SAMPLES * byte_width as usize;
+        let mut buffer = Vec::with_capacity(block_len);
+        for sample in samples {
+            let encoded = if self.scale_samples {
+                sample.write_scaled(&mut buffer, bits, byte_width)
+            } else {
+                sample.write_padded(&mut buffer, bits, byte_width)
+            };
+            if let Err(err) = encoded {
+                try!(self.writer.write_raw(&buffer));
+                return Err(err);
+            }
+            if buffer.len() >= block_len {
+                try!(self.writer.write_raw(&buffer));
+                buffer.clear();
+            }
+        }
+        self.writer.write_raw(&buffer)
+    }
+
+    /// Writes one frame: one sample for every channel.
+    ///
+    /// `frame` must contain exactly `spec().channels` samples, otherwise
+    /// `Error::ChannelCountMismatch` is returned. The samples written before
+    /// must form whole frames, otherwise `Error::UnfinishedSample` is returned
+    /// immediately, rather than when the writer is finalized. As with
+    /// `write_planar()`, nothing is written if an error occurs.
+    pub fn write_frame<S: Sample + Copy>(&mut self, frame: &[S]) -> Result<()> {
+        let spec_ex = self.writer.spec_ex.expect("ChunkWriter with no spec");
+        let channels = spec_ex.spec.channels as usize;
+        if self.len() as usize % channels != 0 {
+            return Err(Error::UnfinishedSample {
+                samples: self.len() as u64,
+                channels: channels as u16,
+            });
+        }
+        if frame.len() != channels {
+            return Err(Error::ChannelCountMismatch {
+                expected: channels as u16,
+                actual: frame.len(),
+            });
+        }
+
+        let bits = spec_ex.spec.bits_per_sample;
+        let byte_width = spec_ex.bytes_per_sample;
+        let mut buffer = Vec::with_capacity(channels * byte_width as usize);
+        for &sample in frame {
+            if self.scale_samples {
+                try!(sample.write_scaled(&mut buffer, bits, byte_width));
+            } else {
+                try!(sample.write_padded(&mut buffer, bits, byte_width));
+            }
+        }
+        self.writer.write_raw(&buffer)
+    }
+
+    /// Writes samples that are stored per channel, interleaving them.
+    ///
+    /// `channels` must contain one slice for every channel in the spec,
+    /// otherwise `Error::ChannelCountMismatch` is returned. All slices must
+    /// have the same length, otherwise `Error::UnfinishedSample` is returned.
+    /// The samples are encoded before
+    /// anything is written, so if an error occurs (also when a sample does not
+    /// fit), no samples are written at all.
+    pub fn write_planar<S: Sample + Copy>(&mut self, channels: &[&[S]]) -> Result<()> {
+        let spec_ex = self.writer.spec_ex.expect("ChunkWriter with no spec");
+        if channels.len() != spec_ex.spec.channels as usize {
+            return Err(Error::ChannelCountMismatch {
+                expected: spec_ex.spec.channels,
+                actual: channels.len(),
+            });
+        }
+        let num_frames = channels.first().map_or(0, |ch| ch.len());
+        if channels.iter().any(|ch| ch.len() != num_frames) {
+            return Err(Error::UnfinishedSample {
+                samples: channels.iter().map(|ch| ch.len() as u64).sum(),
+                channels: spec_ex.spec.channels,
+            });
+        }
+
+        let bits = spec_ex.spec.bits_per_sample;
+        let byte_width = spec_ex.bytes_per_sample;
+        let mut buffer = Vec::with_capacity(num_frames * channels.len() * byte_width as usize);
+        for i in 0..num_frames {
+            for channel in channels {
+                if self.scale_samples {
+                    try!(channel[i].write_scaled(&mut buffer, bits, byte_width));
+                } else {
+                    try!(channel[i].write_padded(&mut buffer, bits, byte_width));
+                }
+            }
+        }
+        self.writer.write_raw(&buffer)
+    }
+
+    /// Writes raw, already encoded samples.
+    ///
+    /// This is the fastest way to write audio data that is already in the
+    /// right format, for instance when copying the data chunk of another file.
+    /// The bytes are appended to the data chunk as-is, they must be
+    /// little-endian samples in the layout described by the spec. The number
+    /// of samples written is advanced by `bytes.len()` divided by the number of
+    /// bytes per sample, and an error is returned if `bytes` does not contain
+    /// a whole number of samples.
+    pub fn write_raw(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_raw(bytes)
     }
 
     /// Create an efficient writer that writes 16-bit integer samples only.
@@ -757,7 +1363,25 @@ impl<W> WavWriter<W>
     /// This method must be called after all samples have been written. If it
     /// is not called, the destructor will finalize the file, but any errors
     /// that occur in the process cannot be observed in that manner.
-    pub fn finalize(self) -> Result<()> {
+    ///
+    /// Finalizing consumes the writer, so it is not possible to write more
+    /// samples afterwards, or to finalize twice:
+    ///
+    /// ```compile_fail
+    /// # use std::io::Cursor;
+    /// # let spec = hound::WavSpec {
+    /// #     channels: 1,
+    /// #     sample_rate: 44100,
+    /// #     bits_per_sample: 16,
+    /// #     sample_format: hound::SampleFormat::Int,
+    /// # };
+    /// let mut buffer = Cursor::new(Vec::new());
+    /// let mut writer = hound::WavWriter::new(&mut buffer, spec).unwrap();
+    /// writer.finalize().unwrap();
+    /// writer.write_sample(0_i16).unwrap();
+    /// ```
+    pub fn finalize(mut self) -> Result<()> {
+        try!(self.write_trailing_chunks());
         // We need to perform a flush here to truly capture all errors before
         // the writer is dropped: for a buffered writer, the write to the buffer
         // may succeed, but the write to the underlying writer may fail. So
@@ -765,6 +1389,197 @@ impl<W> WavWriter<W>
         self.writer.finalize()
     }
 
+    /// Adds a labeled region, that starts at sample `start` and spans `length` samples.
+    ///
+    /// The positions are measured in samples per channel, like `duration()`.
+    /// Regions are stored as cue points in a `cue ` chunk, with a `labl`
+    /// entry for the label and an `ltxt` entry for the length in an `adtl`
+    /// list, which is how other applications store them too. The chunks are
+    /// written after the data chunk by `finalize()`, so regions are not
+    /// written if the writer is dropped without finalizing it.
+    pub fn add_region(&mut self, start: u32, length: u32, label: &str) {
+        self.regions.push(Region {
+            start: start,
+            length: length,
+            label: label.to_string(),
+        });
+    }
+
+    /// Sets the title to store in a `DISP` chunk.
+    ///
+    /// The title is written as `CF_TEXT`. When no samples have been written
+    /// yet, the chunk is written immediately, before the data chunk, where
+    /// `WavReader::display_title()` finds it. Otherwise it is written by
+    /// `finalize()`, after the data chunk, and like all metadata there, it is
+    /// not written if the writer is dropped without finalizing it.
+    pub fn set_display_title(&mut self, title: &str) -> Result<()> {
+        if self.writer.data_state.map(|state| state.len) == Some(0) {
+            self.writer.insert_chunk_before_data(&disp_chunk(title))
+        } else {
+            self.display_title = Some(title.to_string());
+            Ok(())
+        }
+    }
+
+    /// Sets the text metadata to store in a `LIST` chunk of type `INFO`.
+    ///
+    /// Like the display title, the list is written immediately, before the
+    /// data chunk, when no samples have been written yet, so that
+    /// `WavReader::metadata()` finds it. Otherwise it is written by
+    /// `finalize()`, after the data chunk, where it is only found by readers
+    /// that read the chunks after the samples, such as
+    /// `ChunksReader::read_remaining_chunks()`.
+    pub fn set_metadata(&mut self, metadata: Metadata) -> Result<()> {
+        if self.writer.data_state.map(|state| state.len) == Some(0) {
+            self.writer.insert_chunk_before_data(&metadata.to_chunk())
+        } else {
+            self.metadata = Some(metadata);
+            Ok(())
+        }
+    }
+
+    /// Makes `finalize()` pad the data chunk with silence to a multiple of `bytes` bytes.
+    ///
+    /// The padding consists of whole frames of silence: zero, or mid-scale for
+    /// 8-bit samples. When `bytes` is not a multiple of the size of a frame,
+    /// the data chunk is padded to a multiple of both, so that it still holds
+    /// a whole number of frames. For example, with 512-byte alignment, 16-bit
+    /// stereo is padded to a multiple of 512 bytes, but 24-bit stereo, which
+    /// has 6-byte frames, is padded to a multiple of 1536 bytes. The duration
+    /// of the finalized file includes the padding, as does the `fact` chunk,
+    /// if one is written. Nothing is padded if the writer is dropped without
+    /// finalizing it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is zero.
+    pub fn set_data_alignment(&mut self, bytes: u32) {
+        assert!(bytes > 0, "The data alignment must be at least one byte.");
+        self.data_alignment = bytes as u64;
+    }
+
+    /// Pads the data chunk with silence, as configured by `set_data_alignment()`.
+    fn pad_data_chunk(&mut self) -> Result<()> {
+        let spec_ex = self.writer.spec_ex.expect("ChunkWriter with no spec");
+        let len = match self.writer.data_state {
+            Some(data_state) => data_state.len,
+            // Trailing chunks have been written already.
+            None => return Ok(()),
+        };
+        let frame_len = spec_ex.bytes_per_sample as u64 * spec_ex.spec.channels as u64;
+        if len % frame_len != 0 {
+            // Finalizing reports this as an unfinished sample, do not hide it.
+            return Ok(());
+        }
+        // The length must be a multiple of the least common multiple of the
+        // alignment and the frame length; compute the gcd first.
+        let mut a = self.data_alignment;
+        let mut b = frame_len;
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+        let unit = self.data_alignment / a * frame_len;
+        let mut padding = (unit - len % unit) % unit;
+
+        // Samples of 8 bits are unsigned, so silence is not zero for them.
+        let silence = if spec_ex.bytes_per_sample == 1 { 0x80 } else { 0 };
+        let block = vec![silence; cmp::min(padding, 4096 * frame_len) as usize];
+        while padding > 0 {
+            let n = cmp::min(padding, block.len() as u64);
+            try!(self.writer.write_raw(&block[..n as usize]));
+            padding -= n;
+        }
+        Ok(())
+    }
+
+    /// Writes the chunks that follow the data chunk, if there are any.
+    fn write_trailing_chunks(&mut self) -> Result<()> {
+        if self.data_alignment > 1 {
+            try!(self.pad_data_chunk());
+        }
+        for chunk in mem::replace(&mut self.trailing_chunks, Vec::new()) {
+            let mut embedded = try!(self.writer.start_chunk(chunk.id));
+            try!(embedded.write_all(&chunk.data));
+            try!(embedded.finalize());
+        }
+        if self.write_fact {
+            let duration = self.duration();
+            let mut chunk = try!(self.writer.start_chunk(*b"fact"));
+            try!(chunk.write_le_u32(duration));
+            try!(chunk.finalize());
+        }
+        if !self.regions.is_empty() {
+            try!(self.write_regions());
+        }
+        if let Some(title) = self.display_title.take() {
+            let disp = disp_chunk(&title);
+            let mut chunk = try!(self.writer.start_chunk(disp.id));
+            try!(chunk.write_all(&disp.data));
+            try!(chunk.finalize());
+        }
+        if let Some(metadata) = self.metadata.take() {
+            let list = metadata.to_chunk();
+            let mut chunk = try!(self.writer.start_chunk(list.id));
+            try!(chunk.write_all(&list.data));
+            try!(chunk.finalize());
+        }
+        Ok(())
+    }
+
+    /// Writes the `cue ` chunk and the `LIST` chunk of type `adtl` for the regions.
+    fn write_regions(&mut self) -> Result<()> {
+        // Cue point ids start at 1, the ids link the entries of both chunks.
+        let mut cue = Vec::new();
+        try!(cue.write_le_u32(self.regions.len() as u32));
+        for (i, region) in self.regions.iter().enumerate() {
+            try!(cue.write_le_u32(i as u32 + 1)); // Id.
+            try!(cue.write_le_u32(region.start)); // Position.
+            try!(cue.write_all(b"data")); // Chunk that the cue point refers to.
+            try!(cue.write_le_u32(0)); // Chunk start, 0 for the data chunk.
+            try!(cue.write_le_u32(0)); // Block start, 0 for uncompressed data.
+            try!(cue.write_le_u32(region.start)); // Sample offset.
+        }
+
+        let mut adtl = Vec::new();
+        try!(adtl.write_all(b"adtl"));
+        for (i, region) in self.regions.iter().enumerate() {
+            try!(adtl.write_all(b"ltxt"));
+            try!(adtl.write_le_u32(20));
+            try!(adtl.write_le_u32(i as u32 + 1));
+            try!(adtl.write_le_u32(region.length));
+            try!(adtl.write_all(b"rgn ")); // Purpose.
+            try!(adtl.write_le_u16(0)); // Country.
+            try!(adtl.write_le_u16(0)); // Language.
+            try!(adtl.write_le_u16(0)); // Dialect.
+            try!(adtl.write_le_u16(0)); // Code page.
+
+            // The label is a null-terminated string, padded to an even length.
+            let label = region.label.as_bytes();
+            try!(adtl.write_all(b"labl"));
+            try!(adtl.write_le_u32(4 + label.len() as u32 + 1));
+            try!(adtl.write_le_u32(i as u32 + 1));
+            try!(adtl.write_all(label));
+            try!(adtl.write_u8(0));
+            if label.len() % 2 == 0 {
+                try!(adtl.write_u8(0));
+            }
+        }
+
+        {
+            let mut chunk = try!(self.writer.start_chunk(*b"cue "));
+            try!(chunk.write_all(&cue));
+            try!(chunk.finalize());
+        }
+        {
+            let mut chunk = try!(self.writer.start_chunk(*b"LIST"));
+            try!(chunk.write_all(&adtl));
+            try!(chunk.finalize());
+        }
+        Ok(())
+    }
+
     /// Returns information about the WAVE file being written.
     ///
     /// This is the same spec that was passed to `WavWriter::new()`. For a
@@ -782,9 +1597,16 @@ impl<W> WavWriter<W>
     /// in units of samples. The duration in seconds can be obtained by
     /// dividing this number by the sample rate.
     pub fn duration(&self) -> u32 {
+        self.duration_u64() as u32
+    }
+
+    /// Returns the duration of the file written so far, in samples, as a `u64`.
+    ///
+    /// This is like `duration()`, but it does not overflow for RF64 files.
+    pub fn duration_u64(&self) -> u64 {
         let spec_ex = self.writer.spec_ex.expect("ChunkWriter with no spec");
         let writer_state = self.writer.data_state.expect("ChunkWriter in weird state");
-        writer_state.len / (spec_ex.bytes_per_sample as u32 * self.spec().channels as u32)
+        writer_state.len / (spec_ex.bytes_per_sample as u64 * self.spec().channels as u64)
     }
 
     /// Returns the number of samples in the file written so far.
@@ -792,25 +1614,68 @@ impl<W> WavWriter<W>
     /// The length of the file is its duration (in samples) times the number of
     /// channels.
     pub fn len(&self) -> u32 {
+        self.len_u64() as u32
+    }
+
+    /// Returns the number of samples in the file written so far, as a `u64`.
+    ///
+    /// This is like `len()`, but it does not overflow for RF64 files.
+    pub fn len_u64(&self) -> u64 {
         let spec_ex = self.writer.spec_ex.expect("ChunkWriter with no spec");
         let writer_state = self.writer.data_state.expect("ChunkWriter in weird state");
-        writer_state.len / spec_ex.bytes_per_sample as u32
+        writer_state.len / spec_ex.bytes_per_sample as u64
     }
 
-    /// Destroys the `WavWriter` and returns the underlying writer.
-    pub fn into_inner(self) -> W {
-        self.writer.into_inner()
+    /// Finalizes the file, and returns the underlying writer.
+    ///
+    /// This is like `finalize()`, but rather than dropping the writer, it is
+    /// returned, positioned at the end of the file. As for `finalize()`,
+    /// `Error::UnfinishedSample` is returned if the samples written do not
+    /// form whole frames.
+    pub fn into_inner(mut self) -> Result<W> {
+        try!(self.write_trailing_chunks());
+        try!(self.writer.flush());
+        Ok(self.writer.into_inner())
     }
 }
 
+/// Returns a `DISP` chunk that holds `title` as null-terminated `CF_TEXT`.
+fn disp_chunk(title: &str) -> RawChunk {
+    let mut data = Vec::with_capacity(title.len() + 5);
+    data.write_le_u32(1).unwrap(); // CF_TEXT.
+    data.extend_from_slice(title.as_bytes());
+    data.push(0);
+    RawChunk { id: *b"DISP", data: data }
+}
+
 /// Reads the relevant parts of the header required to support append.
 ///
-/// Returns (spec_ex, data_len, data_start).
-fn read_append<W: io::Read + io::Seek>(reader: &mut W) -> Result<(WavSpecEx, u32, u32)> {
+/// Returns (spec_ex, data_len, data_start, rf64_capable), where `rf64_capable`
+/// indicates that the file starts with the `JUNK` chunk that `WavWriter`
+/// reserves for a `ds64` chunk, so appending can grow it beyond 4 GiB.
+fn read_append<W: io::Read + io::Seek>(reader: &mut W) -> Result<(WavSpecEx, u32, u32, bool)> {
+    use read::ReadExt;
+
+    let mut header = [0u8; 20];
+    let rf64_capable = reader.read_into(&mut header).is_ok()
+        && &header[..4] == b"RIFF"
+        && &header[8..16] == b"WAVEJUNK"
+        && (&header[16..]).read_le_u32().ok() == Some(DS64_LEN);
+    try!(reader.seek(io::SeekFrom::Start(0)));
+
     let mut chunk_reader = try!(read::ChunksReader::new(reader));
     try!(chunk_reader.read_until_data());
     let spec_ex = try!(chunk_reader.spec_ex.ok_or(Error::FormatError("DATA found before fmt")));
+    // Samples can only be appended to formats that hound can write:
+    // PCM, IEEE float, and WAVEFORMATEXTENSIBLE.
+    match chunk_reader.format_tag() {
+        0x0001 | 0x0003 | 0xfffe => {}
+        _ => return Err(Error::Unsupported),
+    }
     let data_len = chunk_reader.data_state.expect("Invalid state, should be in DATA").chunk.len;
+    if data_len > u32::MAX as u64 {
+        return Err(Error::Unsupported);
+    }
     let data_start = try!(chunk_reader.into_inner().seek(io::SeekFrom::Current(0)));
 
     let num_samples = data_len / spec_ex.bytes_per_sample as u64;
@@ -824,13 +1689,9 @@ fn read_append<W: io::Read + io::Seek>(reader: &mut W) -> Result<(WavSpecEx, u32
 
     // Hound cannot read or write other bit depths than those, so rather
     // than refusing to write later, fail early.
-    let supported = match (spec_ex.bytes_per_sample, spec_ex.spec.bits_per_sample) {
-        (1, 8) => true,
-        (2, 16) => true,
-        (3, 24) => true,
-        (4, 32) => true,
-        _ => false,
-    };
+    let bits = spec_ex.spec.bits_per_sample;
+    let supported = SUPPORTED_FORMATS.contains(&(spec_ex.spec.sample_format, bits)) &&
+        spec_ex.bytes_per_sample == i32::sample_bytes(bits);
 
     if !supported {
         return Err(Error::Unsupported);
@@ -843,7 +1704,7 @@ fn read_append<W: io::Read + io::Seek>(reader: &mut W) -> Result<(WavSpecEx, u32
         return Err(Error::FormatError("invalid data chunk length"));
     }
 
-    Ok((spec_ex, data_len as u32, data_start as u32))
+    Ok((spec_ex, data_len as u32, data_start as u32, rf64_capable))
 }
 
 impl WavWriter<io::BufWriter<fs::File>> {
@@ -860,10 +1721,26 @@ impl WavWriter<io::BufWriter<fs::File>> {
         WavWriter::new(buf_writer, spec)
     }
 
-    /// Creates a writer that appends samples to an existing file.
+    /// Creates a writer that writes the WAVE format to a file, with a buffer of the given capacity.
     ///
-    /// This is a convenience constructor that opens the file in append mode,
-    /// reads its header using a buffered reader, and then constructs an
+    /// This is like `create()`, but the `BufWriter` holds up to `capacity`
+    /// bytes rather than the default of the standard library. A larger buffer
+    /// uses more memory, but it means fewer `write` calls on the file, which
+    /// pays off on storage where every call is expensive, such as a network
+    /// file system. A smaller buffer is rarely useful.
+    pub fn create_with_buffer_size<P: AsRef<path::Path>>(filename: P,
+                                                         spec: WavSpec,
+                                                         capacity: usize)
+                                                         -> Result<WavWriter<io::BufWriter<fs::File>>> {
+        let file = try!(fs::File::create(filename));
+        let buf_writer = io::BufWriter::with_capacity(capacity, file);
+        WavWriter::new(buf_writer, spec)
+    }
+
+    /// Creates a writer that appends samples to an existing file.
+    ///
+    /// This is a convenience constructor that opens the file in append mode,
+    /// reads its header using a buffered reader, and then constructs an
     /// appending `WavWriter` that writes to the file using a `BufWriter`.
     ///
     /// See `WavWriter::new_append()` for more details about append behavior.
@@ -873,7 +1750,7 @@ impl WavWriter<io::BufWriter<fs::File>> {
 
         // Read the header using a buffered reader.
         let mut buf_reader = io::BufReader::new(file);
-        let (spec_ex, data_len, data_start) = try!(read_append(&mut buf_reader));
+        let (spec_ex, data_len, data_start, rf64_capable) = try!(read_append(&mut buf_reader));
 
         let mut file = buf_reader.into_inner();
 
@@ -892,14 +1769,42 @@ impl WavWriter<io::BufWriter<fs::File>> {
                 writer: Some(buf_writer),
                 sample_writer_buffer: Vec::new(),
                 dirty: true,
-                data_state: Some(ChunkWritingState { len: data_len }),
-            }
+                data_state: Some(ChunkWritingState { len: data_len as u64 }),
+                rf64_capable: rf64_capable,
+                data_len: data_len as u64,
+                data_crc: None,
+            },
+            scale_samples: false,
+            strict_normalized: false,
+            regions: Vec::new(),
+            display_title: None,
+            metadata: None,
+            write_fact: false,
+            trailing_chunks: Vec::new(),
+            data_alignment: 1,
         };
 
         Ok(writer)
     }
 }
 
+impl WavWriter<io::Cursor<Vec<u8>>> {
+    /// Creates a writer that writes the WAVE format to a buffer in memory.
+    ///
+    /// Use `finalize_into_bytes()` to obtain the complete file afterwards.
+    pub fn new_in_memory(spec: WavSpec) -> Result<WavWriter<io::Cursor<Vec<u8>>>> {
+        WavWriter::new(io::Cursor::new(Vec::new()), spec)
+    }
+
+    /// Finalizes the file, and returns its bytes.
+    ///
+    /// This is like `finalize()`, but rather than dropping the buffer, the
+    /// complete WAVE file is returned.
+    pub fn finalize_into_bytes(self) -> Result<Vec<u8>> {
+        self.into_inner().map(|cursor| cursor.into_inner())
+    }
+}
+
 impl<W> WavWriter<W> where W: io::Read + io::Write + io::Seek {
     /// Creates a writer that appends samples to an existing file stream.
     ///
@@ -911,10 +1816,12 @@ impl<W> WavWriter<W> where W: io::Read + io::Write + io::Seek {
     ///
     /// If the existing file includes a fact chunk, it will not be updated after
     /// appending, and hence become outdated. For files produced by Hound this
-    /// is not an issue, because Hound never writes a fact chunk. For all the
-    /// formats that Hound can write, the fact chunk is redundant.
+    /// is not an issue, because Hound writes a fact chunk only when asked to
+    /// with `always_write_fact()`, and then after the data chunk, where it will
+    /// be overwritten. For all the formats that Hound can write, the fact chunk
+    /// is redundant.
     pub fn new_append(mut writer: W) -> Result<WavWriter<W>> {
-        let (spec_ex, data_len, _data_start) = try!(read_append(&mut writer));
+        let (spec_ex, data_len, _data_start, rf64_capable) = try!(read_append(&mut writer));
         try!(writer.seek(io::SeekFrom::Current(data_len as i64)));
         let writer = WavWriter {
             writer: ChunksWriter {
@@ -922,14 +1829,282 @@ impl<W> WavWriter<W> where W: io::Read + io::Write + io::Seek {
                 writer: Some(writer),
                 sample_writer_buffer: Vec::new(),
                 dirty: true,
-                data_state: Some(ChunkWritingState { len: data_len }),
-            }
+                data_state: Some(ChunkWritingState { len: data_len as u64 }),
+                rf64_capable: rf64_capable,
+                data_len: data_len as u64,
+                data_crc: None,
+            },
+            scale_samples: false,
+            strict_normalized: false,
+            regions: Vec::new(),
+            display_title: None,
+            metadata: None,
+            write_fact: false,
+            trailing_chunks: Vec::new(),
+            data_alignment: 1,
         };
 
         Ok(writer)
     }
 }
 
+/// An editor that overwrites samples of an existing WAVE file in place.
+///
+/// Unlike appending or writing a new file, editing does not change the size
+/// of the file: samples can only be written within the existing data chunk,
+/// so the header is never rewritten. As for `WavWriter::new_append()`, only
+/// files with 8, 16, 24, or 32 bits per sample in the PCM, IEEE float, or
+/// `WAVEFORMATEXTENSIBLE` format can be edited.
+///
+/// Like `WavWriter`, a `WavEditor` employs no buffering internally.
+pub struct WavEditor<F>
+    where F: io::Read + io::Write + io::Seek
+{
+    file: F,
+    spec_ex: WavSpecEx,
+    /// offset of the first byte of the data chunk
+    data_start: u64,
+    /// length of the data chunk in bytes, a whole number of frames
+    data_len: u64,
+}
+
+impl<F> WavEditor<F>
+    where F: io::Read + io::Write + io::Seek
+{
+    /// Creates an editor for the WAVE file in `file`.
+    ///
+    /// The file is assumed to be at offset 0. The header is read up to the
+    /// data chunk.
+    pub fn new(mut file: F) -> Result<WavEditor<F>> {
+        let (spec_ex, data_len, data_start, _rf64_capable) = try!(read_append(&mut file));
+        Ok(WavEditor {
+            file: file,
+            spec_ex: spec_ex,
+            data_start: data_start as u64,
+            data_len: data_len as u64,
+        })
+    }
+
+    /// Returns information about the WAVE file being edited.
+    pub fn spec(&self) -> WavSpec {
+        self.spec_ex.spec
+    }
+
+    /// Returns the duration of the file in samples, which editing does not change.
+    pub fn duration(&self) -> u32 {
+        let frame_len = self.spec_ex.bytes_per_sample as u64 * self.spec_ex.spec.channels as u64;
+        (self.data_len / frame_len) as u32
+    }
+
+    /// Overwrites the frames that start at frame `frame` with `samples`.
+    ///
+    /// `samples` holds interleaved samples, and must contain a whole number
+    /// of frames; otherwise `Error::UnfinishedSample` is returned. The frames
+    /// must lie within the data chunk, otherwise an `io::ErrorKind::InvalidInput`
+    /// error is returned. The samples are stored as-is, as by
+    /// `WavWriter::write_sample()`. All samples are encoded before anything is
+    /// written, so if an error occurs, the file is left unmodified.
+    pub fn write_frames_at<S: Sample + Copy>(&mut self, frame: u32, samples: &[S]) -> Result<()> {
+        let channels = self.spec_ex.spec.channels as usize;
+        if samples.len() % channels != 0 {
+            return Err(Error::UnfinishedSample {
+                samples: samples.len() as u64,
+                channels: channels as u16,
+            });
+        }
+        let bits = self.spec_ex.spec.bits_per_sample;
+        let byte_width = self.spec_ex.bytes_per_sample;
+        let start = frame as u64 * byte_width as u64 * channels as u64;
+        let len = samples.len() as u64 * byte_width as u64;
+        if start + len > self.data_len {
+            let msg = "frames to write extend beyond the end of the data chunk";
+            return Err(Error::IoError(io::Error::new(io::ErrorKind::InvalidInput, msg)));
+        }
+
+        let mut buffer = Vec::with_capacity(len as usize);
+        for &sample in samples {
+            try!(sample.write_padded(&mut buffer, bits, byte_width));
+        }
+        try!(self.file.seek(io::SeekFrom::Start(self.data_start + start)));
+        try!(self.file.write_all(&buffer));
+        Ok(())
+    }
+
+    /// Flushes the underlying file.
+    pub fn flush(&mut self) -> Result<()> {
+        try!(self.file.flush());
+        Ok(())
+    }
+
+    /// Destroys the editor and returns the underlying file.
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+}
+
+impl WavEditor<fs::File> {
+    /// Opens the file at `filename` for editing.
+    ///
+    /// The file is opened for reading and writing, and is not truncated.
+    pub fn open<P: AsRef<path::Path>>(filename: P) -> Result<WavEditor<fs::File>> {
+        let file = try!(fs::OpenOptions::new().read(true).write(true).open(filename));
+        WavEditor::new(file)
+    }
+}
+
+/// A writer that writes the WAVE format to a writer that cannot seek.
+///
+/// A `WavWriter` updates the header when it is finalized, which requires
+/// seeking back to the start of the file. A `StreamingWavWriter` instead
+/// requires the number of frames to be declared up front, so the header can
+/// be written with the final sizes immediately. This allows writing to pipes
+/// and sockets. If the declared size exceeds the 4 GiB limit of RIFF, an RF64
+/// file with a `ds64` chunk is written instead, otherwise the output has the
+/// same layout as that of a `WavWriter` that wrote the same samples, except
+/// for the `JUNK` chunk that a `WavWriter` reserves for RF64. An odd-sized
+/// data chunk is followed by a padding byte, as RIFF requires.
+///
+/// Like `WavWriter`, a `StreamingWavWriter` employs no buffering internally.
+pub struct StreamingWavWriter<W: io::Write> {
+    /// the underlying writer
+    writer: W,
+    /// the format of the samples
+    spec_ex: WavSpecEx,
+    /// number of bytes of the declared data that have not been written yet
+    remaining: u64,
+    /// whether the data chunk has an odd length, and needs a padding byte
+    pad: bool,
+}
+
+impl<W: io::Write> StreamingWavWriter<W> {
+    /// Creates a writer for a file with `num_frames` frames, and writes the header.
+    ///
+    /// Exactly `num_frames` frames must be written: writing more samples
+    /// returns an error, and so does finalizing the writer before all samples
+    /// have been written.
+    pub fn new(mut writer: W, spec: WavSpec, num_frames: u64) -> Result<StreamingWavWriter<W>> {
+        let spec_ex = WavSpecEx {
+            spec: spec,
+            bytes_per_sample: i32::sample_bytes(spec.bits_per_sample),
+        };
+        let data_len = try!(write_header_for_frames(&mut writer, spec_ex, num_frames));
+        let pad = data_len % 2 == 1;
+
+        Ok(StreamingWavWriter {
+            writer: writer,
+            spec_ex: spec_ex,
+            remaining: data_len,
+            pad: pad,
+        })
+    }
+
+    /// Writes a single sample for one channel.
+    ///
+    /// This returns an error if the sample does not fit in the number of bits
+    /// specified in the `WavSpec`, or if all declared samples have been
+    /// written already.
+    pub fn write_sample<S: Sample>(&mut self, sample: S) -> Result<()> {
+        let byte_width = self.spec_ex.bytes_per_sample;
+        if self.remaining < byte_width as u64 {
+            return Err(Error::FormatError("more samples written than declared"));
+        }
+        try!(sample.write_padded(&mut self.writer, self.spec_ex.spec.bits_per_sample, byte_width));
+        self.remaining -= byte_width as u64;
+        Ok(())
+    }
+
+    /// Returns the number of samples that remain to be written.
+    pub fn samples_remaining(&self) -> u64 {
+        self.remaining / self.spec_ex.bytes_per_sample as u64
+    }
+
+    /// Flushes the underlying writer, so the samples written so far are sent.
+    ///
+    /// Unlike `WavWriter::flush()`, this does not touch the header, which was
+    /// written with the final sizes already. It can be called any number of
+    /// times before `finalize()`, to push out samples periodically when the
+    /// writer is a buffered socket or pipe.
+    pub fn flush(&mut self) -> Result<()> {
+        try!(self.writer.flush());
+        Ok(())
+    }
+
+    /// Writes the padding byte if required, flushes, and returns the underlying writer.
+    ///
+    /// If fewer samples were written than declared, `Error::FormatError` is
+    /// returned, because the header has been written already, and it does not
+    /// match the data.
+    pub fn finalize(mut self) -> Result<W> {
+        if self.remaining > 0 {
+            return Err(Error::FormatError("fewer samples written than declared"));
+        }
+        if self.pad {
+            try!(self.writer.write_u8(0));
+        }
+        try!(self.writer.flush());
+        Ok(self.writer)
+    }
+}
+
+/// Writes the header for a file with `num_frames` frames, returns the length of the data.
+///
+/// The header is written as RF64 if the file would be too large for RIFF.
+fn write_header_for_frames<W: io::Write>(writer: &mut W,
+                                         spec_ex: WavSpecEx,
+                                         num_frames: u64)
+                                         -> Result<u64> {
+    let spec = spec_ex.spec;
+
+    // Encode the fmt chunk in memory, it follows the 12-byte RIFF header.
+    let mut fmt_writer = try!(ChunksWriter::new(io::Cursor::new(Vec::new())));
+    try!(fmt_writer.write_fmt(spec_ex));
+    let fmt_chunk = fmt_writer.into_inner().into_inner().split_off(12);
+
+    let data_len = match num_frames
+        .checked_mul(spec.channels as u64)
+        .and_then(|n| n.checked_mul(spec_ex.bytes_per_sample as u64)) {
+        Some(len) => len,
+        None => return Err(Error::FormatError("declared number of frames too large")),
+    };
+    let riff_len = 4 + fmt_chunk.len() as u64 + 8 + data_len + data_len % 2;
+
+    if riff_len <= u32::MAX as u64 {
+        try!(write_riff_header(writer, *b"RIFF", riff_len as u32, *b"WAVE"));
+        try!(writer.write_all(&fmt_chunk));
+        try!(write_chunk_header(writer, *b"data", data_len as u32));
+    } else {
+        // For RF64, the sizes are stored in the ds64 chunk, and the size
+        // fields of the RIFF header and data chunk are set to the maximum.
+        try!(write_riff_header(writer, *b"RF64", 0xffff_ffff, *b"WAVE"));
+        try!(write_ds64_chunk(writer, riff_len + 8 + DS64_LEN as u64, data_len, num_frames));
+        try!(writer.write_all(&fmt_chunk));
+        try!(write_chunk_header(writer, *b"data", 0xffff_ffff));
+    }
+
+    Ok(data_len)
+}
+
+/// Returns the header of a file with `total_frames` frames, up to the samples.
+///
+/// The header consists of the RIFF header, the `fmt ` chunk, and the header
+/// of the data chunk, with the sizes set for the given number of frames, so
+/// the samples can be written after it by other means. It is the header that
+/// a `StreamingWavWriter` writes. For up to two channels and up to 16 bits per
+/// sample the header is 44 bytes, otherwise a `WAVEFORMATEXTENSIBLE` header of
+/// 68 bytes is needed. If the data exceeds 4 GiB, an RF64 header is returned.
+///
+/// If the size of the data is odd, a padding byte must follow the samples; it
+/// is included in the size in the RIFF header. This returns an error if the
+/// spec cannot be written.
+pub fn canonical_header(spec: &WavSpec, total_frames: u32) -> Result<Vec<u8>> {
+    let spec_ex = WavSpecEx {
+        spec: *spec,
+        bytes_per_sample: i32::sample_bytes(spec.bits_per_sample),
+    };
+    let mut header = Vec::with_capacity(68);
+    try!(write_header_for_frames(&mut header, spec_ex, total_frames as u64));
+    Ok(header)
+}
 
 /// A writer that specifically only writes integer samples of 16 bits per sample.
 ///
@@ -955,7 +2130,10 @@ pub struct SampleWriter16<'parent, W> where W: io::Write + io::Seek + 'parent {
     buffer: &'parent mut [MaybeUninit<u8>],
 
     /// Reference to the `data_bytes_written` field of the writer.
-    data_bytes_written: &'parent mut u32,
+    data_bytes_written: &'parent mut u64,
+
+    /// The checksum of the data chunk, if the writer computes one.
+    data_crc: Option<&'parent mut Crc32>,
 
     /// The index into the buffer where the next bytes will be written.
     index: u32,
@@ -1027,8 +2205,11 @@ impl<'parent, W: io::Write + io::Seek> SampleWriter16<'parent, W> {
         let slice = unsafe { &*(self.buffer as *const [MaybeUninit<u8>] as *const [u8]) };
 
         try!(self.writer.write_all(slice));
+        if let Some(crc) = self.data_crc {
+            crc.update(slice);
+        }
 
-        *self.data_bytes_written += self.buffer.len() as u32;
+        *self.data_bytes_written += self.buffer.len() as u64;
         Ok(())
     }
 }
@@ -1054,9 +2235,11 @@ fn short_write_should_signal_error() {
     let error = writer.finalize().err().unwrap();
 
     match error {
-        Error::UnfinishedSample => {}
+        Error::UnfinishedSample { samples: 84, channels: 17 } => {}
         _ => panic!("UnfinishedSample error should have been returned."),
     }
+    assert_eq!(error.to_string(),
+               "The number of samples (84) is not a multiple of the number of channels (17).");
 }
 
 #[test]
@@ -1123,5 +2306,1284 @@ fn s24_wav_write() {
         .read_to_end(&mut expected)
         .unwrap();
 
-    assert_eq!(buffer.into_inner(), expected);
+    assert_eq!(without_rf64_reserve(buffer.into_inner()), expected);
+}
+
+#[test]
+fn write_raw_equals_write_sample() {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut buffer_samples = io::Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer_samples, spec).unwrap();
+        for &s in &[1_i16, -2, 300, -32768] {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut buffer_raw = io::Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer_raw, spec).unwrap();
+        writer.write_raw(&[0x01, 0x00, 0xfe, 0xff]).unwrap();
+        writer.write_raw(&[0x2c, 0x01, 0x00, 0x80]).unwrap();
+        assert_eq!(writer.len(), 4);
+        assert_eq!(writer.duration(), 2);
+
+        // Three bytes is not a whole number of 16-bit samples.
+        assert!(writer.write_raw(&[0x00, 0x00, 0x00]).is_err());
+        assert_eq!(writer.len(), 4);
+        writer.finalize().unwrap();
+    }
+
+    assert_eq!(buffer_samples.into_inner(), buffer_raw.into_inner());
+}
+
+/// Removes the `JUNK` chunk that `WavWriter` reserves for RF64 from a small file.
+///
+/// The result has the layout of a file without the reservation, as written by
+/// `StreamingWavWriter` and most other software.
+#[cfg(test)]
+fn without_rf64_reserve(mut bytes: Vec<u8>) -> Vec<u8> {
+    use read::ReadExt;
+
+    let reserve_len = 8 + DS64_LEN as usize;
+    assert_eq!(&bytes[12..16], b"JUNK");
+    bytes.drain(12..12 + reserve_len);
+    let riff_len = (&bytes[4..8]).read_le_u32().unwrap() - reserve_len as u32;
+    (&mut bytes[4..8]).write_le_u32(riff_len).unwrap();
+    bytes
+}
+
+/// A writer that discards everything except the first few bytes.
+///
+/// This allows testing files larger than 4 GiB without storing them.
+#[cfg(test)]
+struct SparseWriter {
+    head: Vec<u8>,
+    pos: u64,
+    len: u64,
+}
+
+#[cfg(test)]
+impl SparseWriter {
+    fn new() -> SparseWriter {
+        SparseWriter { head: vec![0; 128], pos: 0, len: 0 }
+    }
+}
+
+#[cfg(test)]
+impl io::Write for SparseWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (i, &x) in buf.iter().enumerate() {
+            let p = self.pos + i as u64;
+            if p >= self.head.len() as u64 { break }
+            self.head[p as usize] = x;
+        }
+        self.pos += buf.len() as u64;
+        if self.pos > self.len { self.len = self.pos }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl io::Read for SparseWriter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // The discarded bytes read as zeros.
+        let n = cmp::min(buf.len() as u64, self.len.saturating_sub(self.pos)) as usize;
+        for (i, x) in buf[..n].iter_mut().enumerate() {
+            let p = self.pos + i as u64;
+            *x = if p < self.head.len() as u64 { self.head[p as usize] } else { 0 };
+        }
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+impl io::Seek for SparseWriter {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.pos = match pos {
+            io::SeekFrom::Start(n) => n,
+            io::SeekFrom::Current(n) => (self.pos as i64 + n) as u64,
+            io::SeekFrom::End(n) => (self.len as i64 + n) as u64,
+        };
+        Ok(self.pos)
+    }
+}
+
+#[test]
+fn write_over_4_gib_produces_rf64() {
+    use read::ChunksReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 48000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let block = vec![0u8; 1 << 20];
+    let num_blocks = 4097;
+    let data_len = num_blocks as u64 * block.len() as u64;
+
+    let mut sink = SparseWriter::new();
+    {
+        let mut writer = WavWriter::new(&mut sink, spec).unwrap();
+        for _ in 0..num_blocks {
+            writer.write_raw(&block).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut head = &sink.head[..];
+    assert_eq!(&head[0..4], b"RF64");
+    assert_eq!(&head[4..8], &[0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(&head[8..12], b"WAVE");
+    assert_eq!(&head[12..16], b"ds64");
+
+    let mut chunks = ChunksReader::new(&mut head).unwrap();
+    assert!(chunks.read_until_data().unwrap());
+    assert_eq!(chunks.data_state.unwrap().chunk.len, data_len);
+    assert_eq!(sink.len, 12 + 36 + 8 + 16 + 8 + data_len);
+}
+
+#[test]
+fn append_over_4_gib_produces_rf64() {
+    use read::ChunksReader;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 8,
+        sample_format: SampleFormat::Int,
+    };
+    let block = vec![0u8; 1 << 20];
+    let num_blocks = 4097;
+    let data_len = num_blocks as u64 * block.len() as u64;
+
+    // The space for a `ds64` chunk that `new()` reserves is found by append.
+    let mut sink = SparseWriter::new();
+    {
+        let mut writer = WavWriter::new(&mut sink, spec).unwrap();
+        writer.write_raw(&block).unwrap();
+        writer.finalize().unwrap();
+    }
+    sink.seek(io::SeekFrom::Start(0)).unwrap();
+    {
+        let mut writer = WavWriter::new_append(&mut sink).unwrap();
+        for _ in 1..num_blocks {
+            writer.write_raw(&block).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    let mut head = &sink.head[..];
+    assert_eq!(&head[0..4], b"RF64");
+    let mut chunks = ChunksReader::new(&mut head).unwrap();
+    assert!(chunks.read_until_data().unwrap());
+    assert_eq!(chunks.data_state.unwrap().chunk.len, data_len);
+
+    // Without it, the file cannot grow beyond 4 GiB.
+    let mut sink = SparseWriter::new();
+    {
+        let mut writer = WavWriter::new_with_chunks(&mut sink, spec, &[]).unwrap();
+        writer.write_raw(&block).unwrap();
+        writer.finalize().unwrap();
+    }
+    sink.seek(io::SeekFrom::Start(0)).unwrap();
+    let mut writer = WavWriter::new_append(&mut sink).unwrap();
+    for _ in 1..num_blocks {
+        writer.write_raw(&block).unwrap();
+    }
+    match writer.finalize() {
+        Err(Error::FormatError(_)) => {}
+        _ => panic!("finalizing a file over 4 GiB should fail"),
+    }
+}
+
+#[test]
+fn len_u64_does_not_overflow_for_rf64() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 8,
+        sample_format: SampleFormat::Int,
+    };
+    let block = vec![0u8; 1 << 20];
+    let num_blocks = 4097;
+
+    let mut sink = SparseWriter::new();
+    let mut writer = WavWriter::new(&mut sink, spec).unwrap();
+    for _ in 0..num_blocks {
+        writer.write_raw(&block).unwrap();
+    }
+    assert_eq!(writer.len_u64(), num_blocks * (1 << 20));
+    assert_eq!(writer.duration_u64(), num_blocks * (1 << 20));
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn write_over_4_gib_without_rf64_signals_error() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 8,
+        sample_format: SampleFormat::Int,
+    };
+    let block = vec![0u8; 1 << 20];
+
+    // Preserved chunks are written without the space for a `ds64` chunk.
+    let mut sink = SparseWriter::new();
+    let mut writer = WavWriter::new_with_chunks(&mut sink, spec, &[]).unwrap();
+    for _ in 0..4097 {
+        writer.write_raw(&block).unwrap();
+    }
+    match writer.finalize() {
+        Err(Error::FormatError(_)) => {}
+        _ => panic!("finalizing a file over 4 GiB should fail"),
+    }
+}
+
+#[test]
+fn write_small_file_remains_riff() {
+    use std::io::Cursor;
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut buffer = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer, spec).unwrap();
+        for s in -10i16..10 {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let bytes = buffer.into_inner();
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(&bytes[12..16], b"JUNK");
+    assert_eq!(bytes.len(), 8 + 4 + 36 + 24 + 8 + 40);
+
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, (-10i16..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn finalize_updates_headers_only_once() {
+    /// A writer that counts how often the data chunk size is written.
+    struct CountingWriter {
+        inner: io::Cursor<Vec<u8>>,
+        data_size_writes: usize,
+    }
+
+    impl io::Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.inner.position() == 76 {
+                self.data_size_writes += 1;
+            }
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl io::Seek for CountingWriter {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut sink = CountingWriter { inner: io::Cursor::new(Vec::new()), data_size_writes: 0 };
+    {
+        let mut writer = WavWriter::new(&mut sink, spec).unwrap();
+        writer.write_sample(17_i16).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // Once when the data chunk is started, and once on finalize.
+    assert_eq!(sink.data_size_writes, 2);
+}
+
+#[test]
+fn write_with_scaling_maps_full_scale_to_full_scale() {
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut buffer = io::Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer, spec).unwrap().with_scaling();
+        for &s in &[i16::MAX, i16::MIN, 1, 0] {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    buffer.set_position(0);
+    let mut reader = WavReader::new(buffer).unwrap();
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [0x7fff00, -0x800000, 0x100, 0]);
+}
+
+#[test]
+fn add_region_writes_cue_and_adtl_chunks() {
+    use std::io::Read;
+    use read::{Chunk, ChunksReader, WavReader};
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 8,
+        sample_format: SampleFormat::Int,
+    };
+    let mut buffer = io::Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer, spec).unwrap();
+        // An odd number of samples, so the data chunk needs a padding byte.
+        for s in 0..7_i8 {
+            writer.write_sample(s).unwrap();
+        }
+        writer.add_region(2, 3, "verse");
+        writer.finalize().unwrap();
+    }
+
+    buffer.set_position(0);
+    let mut chunks = ChunksReader::new(&mut buffer).unwrap();
+    let mut cue = Vec::new();
+    let mut list = Vec::new();
+    while let Some(chunk) = chunks.next().unwrap() {
+        if let Chunk::Unknown(fourcc, mut reader) = chunk {
+            match &fourcc {
+                b"cue " => { reader.read_to_end(&mut cue).unwrap(); }
+                b"LIST" => { reader.read_to_end(&mut list).unwrap(); }
+                _ => {}
+            }
+        }
+    }
+
+    assert_eq!(&cue[..], &[1, 0, 0, 0,
+                           1, 0, 0, 0, 2, 0, 0, 0, b'd', b'a', b't', b'a',
+                           0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0][..]);
+    assert_eq!(&list[0..4], b"adtl");
+    assert_eq!(&list[4..12], b"ltxt\x14\0\0\0");
+    assert_eq!(&list[12..24], b"\x01\0\0\0\x03\0\0\0rgn ");
+    assert_eq!(&list[32..], b"labl\x0a\0\0\0\x01\0\0\0verse\0");
+
+    // The samples can still be read.
+    buffer.set_position(0);
+    let mut reader = WavReader::new(buffer).unwrap();
+    let samples: Vec<i8> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [0, 1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn write_planar_interleaves_channels() {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut buffer_interleaved = io::Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer_interleaved, spec).unwrap();
+        for &s in &[1_i16, -1, 2, -2, 3, -3] {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut buffer_planar = io::Cursor::new(Vec::new());
+    {
+        let left = [1_i16, 2, 3];
+        let right = [-1_i16, -2, -3];
+        let mut writer = WavWriter::new(&mut buffer_planar, spec).unwrap();
+
+        // A wrong number of channels or unequal lengths write nothing.
+        match writer.write_planar(&[&left[..]]) {
+            Err(Error::ChannelCountMismatch { expected: 2, actual: 1 }) => {}
+            _ => panic!("expected Error::ChannelCountMismatch"),
+        }
+        match writer.write_planar(&[&left[..], &right[..2]]) {
+            Err(Error::UnfinishedSample { samples: 5, channels: 2 }) => {}
+            _ => panic!("expected Error::UnfinishedSample"),
+        }
+        assert_eq!(writer.len(), 0);
+
+        writer.write_planar(&[&left[..], &right[..]]).unwrap();
+        assert_eq!(writer.duration(), 3);
+        writer.finalize().unwrap();
+    }
+
+    assert_eq!(buffer_interleaved.into_inner(), buffer_planar.into_inner());
+}
+
+#[test]
+fn into_inner_finalizes_and_returns_writer() {
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new(io::Cursor::new(Vec::new()), spec).unwrap();
+    for &s in &[1_i16, -1, 2, -2] {
+        writer.write_sample(s).unwrap();
+    }
+    writer.add_region(0, 1, "start");
+    let cursor = writer.into_inner().unwrap();
+    assert_eq!(cursor.position(), cursor.get_ref().len() as u64);
+
+    let bytes = cursor.into_inner();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.spec(), spec);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, -1, 2, -2]);
+    // The RIFF size covers the trailing chunks too.
+    assert_eq!(&bytes[4..8], &[bytes.len() as u8 - 8, 0, 0, 0]);
+
+    let mut writer = WavWriter::new(io::Cursor::new(Vec::new()), spec).unwrap();
+    writer.write_sample(1_i16).unwrap();
+    match writer.into_inner() {
+        Err(Error::UnfinishedSample { samples: 1, channels: 2 }) => {}
+        _ => panic!("expected Error::UnfinishedSample"),
+    }
+}
+
+#[test]
+fn finalize_into_bytes_equals_cursor_contents() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut buffer = io::Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut buffer, spec).unwrap();
+        for s in -3_i16..3 {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for s in -3_i16..3 {
+        writer.write_sample(s).unwrap();
+    }
+    assert_eq!(writer.finalize_into_bytes().unwrap(), buffer.into_inner());
+}
+
+#[test]
+fn write_sample_normalized_saturates_or_errors() {
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for &x in &[1.0, -1.0, 1.5, -1.5, 0.0, 0.5] {
+        writer.write_sample_normalized(x).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(io::Cursor::new(bytes)).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [i16::MAX, i16::MIN, i16::MAX, i16::MIN, 0, 16384]);
+
+    let mut writer = WavWriter::new_in_memory(spec).unwrap().with_strict_normalized();
+    writer.write_sample_normalized(1.0).unwrap();
+    writer.write_sample_normalized(-1.0).unwrap();
+    match writer.write_sample_normalized(1.5) {
+        Err(Error::TooWide) => {}
+        _ => panic!("1.5 is out of range in strict mode"),
+    }
+    match writer.write_sample_normalized(-1.5) {
+        Err(Error::TooWide) => {}
+        _ => panic!("-1.5 is out of range in strict mode"),
+    }
+    assert_eq!(writer.len(), 2);
+}
+
+#[test]
+fn write_sample_rejects_values_out_of_range() {
+    // The extremes that fit in each bit depth, and the values just outside.
+    let cases = [(8, -128_i32, 127_i32), (16, -32768, 32767), (24, -8_388_608, 8_388_607)];
+    for &(bits, min, max) in &cases {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: bits,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::new_in_memory(spec).unwrap();
+        writer.write_sample(min).unwrap();
+        writer.write_sample(max).unwrap();
+        for &x in &[min - 1, max + 1] {
+            match writer.write_sample(x) {
+                Err(Error::TooWide) => {}
+                _ => panic!("expected Error::TooWide for {} at {} bits", x, bits),
+            }
+        }
+        assert_eq!(writer.len(), 2);
+    }
+
+    // The same holds for narrower sample types.
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 8,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(127_i16).unwrap();
+    match writer.write_sample(200_i16) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide for 200 at 8 bits"),
+    }
+}
+
+#[test]
+fn riff_writer_writes_nested_chunks() {
+    let mut writer = RiffWriter::new(io::Cursor::new(Vec::new()), *b"TEST").unwrap();
+    writer.start_list(*b"INFO").unwrap();
+    writer.start_chunk(*b"INAM").unwrap();
+    writer.write_all(b"abc").unwrap();
+    writer.end_chunk().unwrap();
+    writer.end_chunk().unwrap();
+    assert!(writer.end_chunk().is_err());
+    let bytes = writer.finalize().unwrap().into_inner();
+
+    let mut expected = Vec::new();
+    expected.extend_from_slice(b"RIFF\x1c\0\0\0TEST");
+    expected.extend_from_slice(b"LIST\x10\0\0\0INFO");
+    expected.extend_from_slice(b"INAM\x03\0\0\0abc\0");
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn set_display_title_writes_disp_chunk() {
+    use read::{ChunksReader, WavReader};
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    // Before any samples, the chunk precedes the data chunk.
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.set_display_title("Take 1").unwrap();
+    writer.write_sample(3_i16).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    assert_eq!(&bytes[72..80], b"DISP\x0b\0\0\0");
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.display_title(), Some("Take 1"));
+    assert_eq!(reader.samples::<i16>().next().unwrap().unwrap(), 3);
+
+    // After samples, it is written after the data chunk on finalize.
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(3_i16).unwrap();
+    writer.set_display_title("Take 1").unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+
+    let mut chunks = ChunksReader::new(&bytes[..]).unwrap();
+    assert_eq!(chunks.display_title(), None);
+    while let Some(_) = chunks.next().unwrap() {}
+    assert_eq!(chunks.display_title(), Some("Take 1"));
+}
+
+#[test]
+fn set_metadata_round_trips_info_list() {
+    use std::io::Cursor;
+    use read::{ChunksReader, WavReader};
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let metadata = Metadata {
+        title: Some("Take 1".to_string()),
+        artist: Some("Hound".to_string()),
+        comment: None,
+    };
+
+    // Strings are null-terminated, and the odd-sized one is padded.
+    let chunk = metadata.to_chunk();
+    let mut expected = Vec::new();
+    expected.extend_from_slice(b"INFOINAM\x07\0\0\0Take 1\0\0");
+    expected.extend_from_slice(b"IART\x06\0\0\0Hound\0");
+    assert_eq!(&chunk.id, b"LIST");
+    assert_eq!(chunk.data, expected);
+
+    // Before any samples, the list precedes the data chunk.
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.set_metadata(metadata.clone()).unwrap();
+    writer.write_sample(3_i16).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.metadata(), &metadata);
+    assert_eq!(reader.samples::<i16>().next().unwrap().unwrap(), 3);
+
+    // After samples, the list is found once the remaining chunks are read.
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(3_i16).unwrap();
+    writer.set_metadata(metadata.clone()).unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut chunks = ChunksReader::new(&bytes[..]).unwrap();
+    chunks.read_until_data().unwrap();
+    assert_eq!(chunks.metadata(), &Metadata::default());
+    chunks.read_remaining_chunks().unwrap();
+    assert_eq!(chunks.metadata(), &metadata);
+
+    // The same holds for the chunk passed to `new_with_chunks()`.
+    let mut writer = WavWriter::new_with_chunks(Cursor::new(Vec::new()), spec, &[chunk]).unwrap();
+    writer.write_sample(3_i16).unwrap();
+    let bytes = writer.into_inner().unwrap().into_inner();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.metadata(), &metadata);
+    assert_eq!(reader.samples::<i16>().next().unwrap().unwrap(), 3);
+}
+
+#[test]
+fn always_write_fact_writes_duration_after_data() {
+    use read::{Chunk, ChunksReader};
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap().always_write_fact();
+    for s in 0..6_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    assert_eq!(writer.duration(), 3);
+    let bytes = writer.finalize_into_bytes().unwrap();
+
+    // The RIFF size covers the fact chunk: 12 bytes more than without it.
+    assert_eq!(&bytes[4..8], &[(72 + 12 + 12) as u8, 0, 0, 0][..]);
+    assert_eq!(bytes.len(), 8 + 72 + 12 + 12);
+    assert_eq!(&bytes[92..], &[b'f', b'a', b'c', b't', 4, 0, 0, 0, 3, 0, 0, 0][..]);
+
+    let mut chunks = ChunksReader::new(&bytes[..]).unwrap();
+    let mut chunk_kinds = Vec::new();
+    while let Some(chunk) = chunks.next().unwrap() {
+        chunk_kinds.push(match chunk {
+            Chunk::Fmt(_) => "fmt",
+            Chunk::Fact => "fact",
+            Chunk::Data => "data",
+            _ => "other",
+        });
+    }
+    // The first chunk is the `JUNK` chunk that reserves space for RF64.
+    assert_eq!(chunk_kinds, ["other", "fmt", "data", "fact"]);
+}
+
+#[test]
+fn write_frame_enforces_channel_grouping() {
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_frame(&[1_i16, -1]).unwrap();
+    match writer.write_frame(&[2_i16]) {
+        Err(Error::ChannelCountMismatch { expected: 2, actual: 1 }) => {}
+        _ => panic!("expected Error::ChannelCountMismatch"),
+    }
+    match writer.write_frame(&[2_i16, -2, 3]) {
+        Err(err @ Error::ChannelCountMismatch { expected: 2, actual: 3 }) => {
+            assert_eq!(err.to_string(), "Wrong number of channels: expected 2, got 3.");
+        }
+        _ => panic!("expected Error::ChannelCountMismatch"),
+    }
+    match writer.write_frame(&[0x10000_i32, 0]) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide"),
+    }
+    assert_eq!(writer.len(), 2);
+
+    // A frame cannot be written after a partial frame.
+    writer.write_sample(2_i16).unwrap();
+    match writer.write_frame(&[3_i16, -3]) {
+        Err(Error::UnfinishedSample { .. }) => {}
+        _ => panic!("expected Error::UnfinishedSample"),
+    }
+    writer.write_sample(-2_i16).unwrap();
+    writer.write_frame(&[3_i16, -3]).unwrap();
+
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, -1, 2, -2, 3, -3]);
+}
+
+#[test]
+fn streaming_writer_matches_seeking_writer() {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    let mut streaming_writer = StreamingWavWriter::new(Vec::new(), spec, 3).unwrap();
+    for s in -3..3_i32 {
+        writer.write_sample(s * 1000).unwrap();
+        streaming_writer.write_sample(s * 1000).unwrap();
+    }
+    assert_eq!(streaming_writer.samples_remaining(), 0);
+    match streaming_writer.write_sample(0_i32) {
+        Err(Error::FormatError(..)) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
+    let bytes = without_rf64_reserve(writer.finalize_into_bytes().unwrap());
+    assert_eq!(streaming_writer.finalize().unwrap(), bytes);
+
+    // An odd-sized data chunk is padded, and the RIFF size includes the pad byte.
+    let spec = WavSpec { channels: 1, bits_per_sample: 8, ..spec };
+    let mut streaming_writer = StreamingWavWriter::new(Vec::new(), spec, 1).unwrap();
+    streaming_writer.write_sample(7_i8).unwrap();
+    let bytes = streaming_writer.finalize().unwrap();
+    assert_eq!(bytes.len(), 46);
+    assert_eq!(&bytes[4..8], &[38, 0, 0, 0][..]);
+    assert_eq!(&bytes[40..], &[1, 0, 0, 0, 135, 0][..]);
+}
+
+#[test]
+fn streaming_writer_output_reads_back() {
+    use read::WavReader;
+
+    // A `Vec<u8>` does not implement `Seek`, the header is final immediately.
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut out = Vec::new();
+    {
+        let mut streaming_writer = StreamingWavWriter::new(&mut out, spec, 2).unwrap();
+        assert_eq!(streaming_writer.samples_remaining(), 4);
+        for &s in &[1_i16, -1, 300, -300] {
+            streaming_writer.write_sample(s).unwrap();
+        }
+        streaming_writer.finalize().unwrap();
+    }
+    let mut reader = WavReader::new(&out[..]).unwrap();
+    assert_eq!(reader.spec(), spec);
+    assert_eq!(reader.duration(), 2);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, -1, 300, -300]);
+
+    // A spec that cannot be written is rejected before any byte is written.
+    let mut out = Vec::new();
+    let spec = WavSpec { bits_per_sample: 12, ..spec };
+    assert!(StreamingWavWriter::new(&mut out, spec, 2).is_err());
+    assert!(out.is_empty());
+}
+
+#[test]
+fn streaming_writer_fails_when_samples_are_missing() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut streaming_writer = StreamingWavWriter::new(Vec::new(), spec, 2).unwrap();
+    streaming_writer.write_sample(1_i16).unwrap();
+    assert_eq!(streaming_writer.samples_remaining(), 1);
+    match streaming_writer.finalize() {
+        Err(Error::FormatError(..)) => {}
+        _ => panic!("expected Error::FormatError"),
+    }
+}
+
+#[test]
+fn streaming_writer_flush_pushes_out_samples() {
+    use std::io::Cursor;
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let buffered = io::BufWriter::new(Vec::new());
+    let mut streaming_writer = StreamingWavWriter::new(buffered, spec, 4).unwrap();
+    streaming_writer.write_sample(1_i16).unwrap();
+    streaming_writer.write_sample(2_i16).unwrap();
+    assert!(streaming_writer.writer.get_ref().is_empty());
+
+    // The header and the samples so far reach the underlying writer.
+    streaming_writer.flush().unwrap();
+    assert_eq!(streaming_writer.writer.get_ref().len(), 44 + 4);
+    streaming_writer.flush().unwrap();
+
+    streaming_writer.write_sample(3_i16).unwrap();
+    streaming_writer.write_sample(4_i16).unwrap();
+    let bytes = streaming_writer.finalize().unwrap().into_inner().unwrap();
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [1, 2, 3, 4]);
+}
+
+#[test]
+fn streaming_writer_writes_rf64_for_large_declared_size() {
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    // This makes for a data chunk of 10 bytes over 4 GiB.
+    let num_frames = (1 << 31) + 5;
+    let mut streaming_writer = StreamingWavWriter::new(Vec::new(), spec, num_frames).unwrap();
+    streaming_writer.write_sample(42_i16).unwrap();
+    assert_eq!(streaming_writer.samples_remaining(), num_frames - 1);
+    // The header is complete, only the samples are missing.
+    let bytes = streaming_writer.writer;
+    assert_eq!(&bytes[0..4], b"RF64");
+    assert_eq!(&bytes[12..16], b"ds64");
+
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.spec(), spec);
+    assert_eq!(reader.duration() as u64, num_frames);
+    assert_eq!(reader.samples::<i16>().next().unwrap().unwrap(), 42);
+}
+
+#[test]
+fn canonical_header_matches_finalized_header() {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 48000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for s in 0..10_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = without_rf64_reserve(writer.finalize_into_bytes().unwrap());
+    let header = canonical_header(&spec, 5).unwrap();
+    assert_eq!(header.len(), 44);
+    assert_eq!(&header[..], &bytes[..44]);
+
+    let spec = WavSpec { channels: 3, bits_per_sample: 24, ..spec };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for s in 0..6_i32 {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = without_rf64_reserve(writer.finalize_into_bytes().unwrap());
+    let header = canonical_header(&spec, 2).unwrap();
+    assert_eq!(header.len(), 68);
+    assert_eq!(&header[..], &bytes[..68]);
+
+    let spec = WavSpec { bits_per_sample: 12, ..spec };
+    match canonical_header(&spec, 2) {
+        Err(Error::Unsupported) => {}
+        _ => panic!("expected Error::Unsupported"),
+    }
+}
+
+#[test]
+fn written_bytes_do_not_depend_on_host_byte_order() {
+    // All fields are serialized byte by byte, so these exact bytes must be
+    // produced on big endian hosts as well.
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_sample(0.5_f32).unwrap();
+    writer.write_sample(-1.0_f32).unwrap();
+    let bytes = without_rf64_reserve(writer.finalize_into_bytes().unwrap());
+    let mut expected = vec![
+        b'R', b'I', b'F', b'F', 68, 0, 0, 0, b'W', b'A', b'V', b'E',
+        b'f', b'm', b't', b' ', 40, 0, 0, 0,
+        0xfe, 0xff, // WAVE_FORMAT_EXTENSIBLE.
+        1, 0, // Channels.
+        0x44, 0xac, 0, 0, // Sample rate.
+        0x10, 0xb1, 0x02, 0, // Bytes per second.
+        4, 0, 32, 0, // Block align, container bits per sample.
+        22, 0, 32, 0, // Extension size, valid bits per sample.
+        1, 0, 0, 0, // Channel mask.
+    ];
+    expected.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_IEEE_FLOAT);
+    expected.extend_from_slice(&[
+        b'd', b'a', b't', b'a', 8, 0, 0, 0,
+        0, 0, 0, 0x3f, 0, 0, 0x80, 0xbf,
+    ]);
+    assert_eq!(bytes, expected);
+
+    let spec = WavSpec {
+        channels: 3,
+        sample_rate: 48000,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_frame(&[0x12_3456_i32, -2, 0x7f_ffff]).unwrap();
+    let bytes = without_rf64_reserve(writer.finalize_into_bytes().unwrap());
+    let mut expected = vec![
+        b'R', b'I', b'F', b'F', 69, 0, 0, 0, b'W', b'A', b'V', b'E',
+        b'f', b'm', b't', b' ', 40, 0, 0, 0,
+        0xfe, 0xff, // WAVE_FORMAT_EXTENSIBLE.
+        3, 0, // Channels.
+        0x80, 0xbb, 0, 0, // Sample rate.
+        0x80, 0x97, 0x06, 0, // Bytes per second.
+        9, 0, 24, 0, // Block align, container bits per sample.
+        22, 0, 24, 0, // Extension size, valid bits per sample.
+        7, 0, 0, 0, // Channel mask.
+    ];
+    expected.extend_from_slice(&super::KSDATAFORMAT_SUBTYPE_PCM);
+    expected.extend_from_slice(&[
+        b'd', b'a', b't', b'a', 9, 0, 0, 0,
+        0x56, 0x34, 0x12, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x7f,
+    ]);
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn write_all_writes_every_sample() {
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.write_all((0..10_000).map(|i| (i % 1000) as i16)).unwrap();
+    assert_eq!(writer.len(), 10_000);
+    assert_eq!(writer.duration(), 5_000);
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(&bytes[..]).unwrap();
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    let expected: Vec<i16> = (0..10_000).map(|i| (i % 1000) as i16).collect();
+    assert_eq!(samples, expected);
+
+    // The samples before one that does not fit are written.
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    match writer.write_all(vec![1_i32, 2, 0x10000, 3]) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide"),
+    }
+    assert_eq!(writer.len(), 2);
+}
+
+#[test]
+fn new_with_chunks_checks_fmt_and_rewrites_fact() {
+    use std::io::Cursor;
+    use super::{RawChunk, SampleFormat, WavSpec};
+
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let fmt = RawChunk {
+        id: *b"fmt ",
+        data: vec![1, 0, 1, 0, 0x44, 0xac, 0, 0, 0x88, 0x58, 1, 0, 2, 0, 16, 0],
+    };
+    let fact = RawChunk { id: *b"fact", data: vec![100, 0, 0, 0] };
+    let data = RawChunk { id: *b"data", data: Vec::new() };
+
+    // A fmt chunk for a different spec is rejected.
+    let stereo = WavSpec { channels: 2, ..spec };
+    let chunks = [fmt.clone(), data.clone()];
+    match WavWriter::new_with_chunks(Cursor::new(Vec::new()), stereo, &chunks) {
+        Err(Error::IncompatibleSpec) => {}
+        _ => panic!("expected Error::IncompatibleSpec"),
+    }
+    // So is a fmt chunk that cannot be parsed.
+    let short = RawChunk { id: *b"fmt ", data: vec![1, 0, 1, 0] };
+    assert!(WavWriter::new_with_chunks(Cursor::new(Vec::new()), spec, &[short]).is_err());
+
+    // The stale fact chunk is replaced by one after the data.
+    let chunks = [fmt, fact, data];
+    let mut writer = WavWriter::new_with_chunks(Cursor::new(Vec::new()), spec, &chunks).unwrap();
+    for s in 0..3 {
+        writer.write_sample(s as i16).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    assert_eq!(&bytes[36..40], b"data");
+    assert_eq!(&bytes[50..], &[b'f', b'a', b'c', b't', 4, 0, 0, 0, 3, 0, 0, 0][..]);
+}
+
+#[test]
+fn preserved_chunks_round_trip_byte_for_byte() {
+    use std::io::Cursor;
+    use super::{SampleFormat, WavSpec};
+    use read::{ReadOptions, WavReader};
+
+    // A fmt chunk with a zero `cbSize` field, which hound would not write
+    // itself, an unknown chunk of odd size before the data, and unknown and
+    // known chunks after it.
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+    bytes.extend_from_slice(b"fmt \x12\0\0\0");
+    bytes.extend_from_slice(&[1, 0, 1, 0, 0x44, 0xac, 0, 0, 0x88, 0x58, 1, 0, 2, 0, 16, 0, 0, 0]);
+    bytes.extend_from_slice(b"JUNK\x03\0\0\0abc\0");
+    bytes.extend_from_slice(b"data\x06\0\0\0\x01\0\x02\0\x03\0");
+    bytes.extend_from_slice(b"abcd\x05\0\0\0hello\0");
+    bytes.extend_from_slice(b"DISP\x08\0\0\0\x01\0\0\0hey\0");
+    let riff_len = bytes.len() as u32 - 8;
+    (&mut bytes[4..8]).write_le_u32(riff_len).unwrap();
+
+    let options = ReadOptions { preserve_chunks: true, ..ReadOptions::default() };
+    let mut reader = WavReader::new_with_options(Cursor::new(&bytes[..]), options).unwrap();
+    let spec = reader.spec();
+    assert_eq!(spec, WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    });
+    let leading: Vec<[u8; 4]> = reader.preserved_chunks().iter().map(|c| c.id).collect();
+    assert_eq!(leading, [*b"fmt ", *b"JUNK", *b"data"]);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    let chunks = reader.into_preserved_chunks().unwrap();
+    assert_eq!(chunks.len(), 5);
+    assert_eq!(chunks[3].data, b"hello");
+    assert_eq!(chunks[4].data, b"\x01\0\0\0hey\0");
+
+    let mut written = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new_with_chunks(&mut written, spec, &chunks).unwrap();
+        for &sample in &samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    assert_eq!(written.into_inner(), bytes);
+}
+
+#[test]
+fn len_u64_and_seek_u64_exceed_u32() {
+    use std::io::Cursor;
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 8,
+        sample_format: SampleFormat::Int,
+    };
+    // Only the header is written, the reader does not need the samples.
+    let num_frames = 5_000_000_000;
+    let mut writer = StreamingWavWriter::new(Vec::new(), spec, num_frames).unwrap();
+    for s in 0..4 {
+        writer.write_sample(s as i8).unwrap();
+    }
+    let bytes = writer.writer;
+
+    let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(reader.duration_u64(), num_frames);
+    assert_eq!(reader.len_u64(), num_frames * 2);
+
+    reader.seek_u64(1).unwrap();
+    assert_eq!(reader.samples::<i8>().next().unwrap().unwrap(), 2);
+
+    // Seeking beyond `u32::MAX` frames positions the reader beyond the end of
+    // the buffer, so there is nothing to read, but the remaining length is
+    // that of a huge file.
+    reader.seek_u64(4_999_999_999).unwrap();
+    if cfg!(target_pointer_width = "64") {
+        assert_eq!(reader.samples::<i8>().size_hint(), (2, Some(2)));
+    }
+}
+
+#[test]
+fn data_crc32_does_not_depend_on_metadata() {
+    use std::io::Cursor;
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap().with_data_crc32();
+    assert_eq!(writer.data_crc32(), Some(0));
+    writer.write_sample(1_i16).unwrap();
+    writer.write_sample(-2_i16).unwrap();
+    writer.write_raw(&[3, 0, 4, 0]).unwrap();
+    {
+        let mut sample_writer = writer.get_i16_writer(2);
+        sample_writer.write_sample(5_i16);
+        sample_writer.write_sample(6_i16);
+        sample_writer.flush().unwrap();
+    }
+    let written_crc = writer.data_crc32().unwrap();
+    writer.set_display_title("title").unwrap();
+    let with_metadata = writer.finalize_into_bytes().unwrap();
+
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+    for &s in &[1_i16, -2, 3, 4, 5, 6] {
+        writer.write_sample(s).unwrap();
+    }
+    assert_eq!(writer.data_crc32(), None);
+    let without_metadata = writer.into_inner().unwrap().into_inner();
+    assert!(with_metadata != without_metadata);
+
+    // The CRC-32 of the little-endian bytes of the six samples.
+    let mut crc = ::crc::Crc32::new();
+    crc.update(&[1, 0, 0xfe, 0xff, 3, 0, 4, 0, 5, 0, 6, 0]);
+    assert_eq!(written_crc, crc.value());
+    for bytes in &[with_metadata, without_metadata] {
+        let mut reader = WavReader::new(Cursor::new(&bytes[..])).unwrap();
+        assert_eq!(reader.data_crc32().unwrap(), written_crc);
+    }
+}
+
+#[test]
+fn data_alignment_pads_with_silence() {
+    use std::io::Cursor;
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    writer.set_data_alignment(512);
+    for s in 1..11_i16 {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.data_chunk_range().end - reader.data_chunk_range().start, 512);
+    assert_eq!(reader.duration(), 128);
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(&samples[..10], &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    assert!(samples[10..].iter().all(|&s| s == 0));
+
+    // A frame of 24-bit stereo is 6 bytes, which does not divide 512,
+    // so the data is padded to a multiple of 1536 bytes.
+    let spec24 = WavSpec { bits_per_sample: 24, ..spec };
+    let mut writer = WavWriter::new_in_memory(spec24).unwrap();
+    writer.set_data_alignment(512);
+    for s in 0..2_i32 {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.data_chunk_range().end - reader.data_chunk_range().start, 1536);
+
+    // For 8-bit samples, silence is mid-scale, and a length that is aligned
+    // already is not padded.
+    let spec8 = WavSpec { channels: 1, bits_per_sample: 8, ..spec };
+    let mut writer = WavWriter::new_in_memory(spec8).unwrap();
+    writer.set_data_alignment(4);
+    for s in 0..5_i8 {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    assert_eq!(&bytes[80..], &[0x80, 0x81, 0x82, 0x83, 0x84, 0x80, 0x80, 0x80]);
+    let mut writer = WavWriter::new_in_memory(spec8).unwrap();
+    writer.set_data_alignment(4);
+    for s in 0..4_i8 {
+        writer.write_sample(s).unwrap();
+    }
+    let bytes = writer.finalize_into_bytes().unwrap();
+    assert_eq!(bytes.len(), 84);
+}
+
+#[test]
+fn editor_overwrites_samples_in_place() {
+    use std::io::Cursor;
+    use read::WavReader;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::new_in_memory(spec).unwrap();
+    for s in 0..8_i32 {
+        writer.write_sample(s).unwrap();
+    }
+    writer.set_display_title("after the data").unwrap();
+    let bytes = writer.finalize_into_bytes().unwrap();
+    let original_len = bytes.len();
+
+    let mut editor = WavEditor::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(editor.spec(), spec);
+    assert_eq!(editor.duration(), 4);
+    editor.write_frames_at(1, &[-1_i32, -2, -3, -4]).unwrap();
+    editor.write_frames_at(3, &[100_i32, 8_388_607]).unwrap();
+
+    // Writes that do not fit leave the file unmodified.
+    match editor.write_frames_at(3, &[1_i32, 2, 3, 4]) {
+        Err(Error::IoError(ref err)) if err.kind() == io::ErrorKind::InvalidInput => {}
+        _ => panic!("expected an InvalidInput error"),
+    }
+    match editor.write_frames_at(0, &[1_i32]) {
+        Err(Error::UnfinishedSample { samples: 1, channels: 2 }) => {}
+        _ => panic!("expected Error::UnfinishedSample"),
+    }
+    match editor.write_frames_at(0, &[1_i32, 8_388_608]) {
+        Err(Error::TooWide) => {}
+        _ => panic!("expected Error::TooWide"),
+    }
+
+    let bytes = editor.into_inner().into_inner();
+    assert_eq!(bytes.len(), original_len);
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert_eq!(samples, [0, 1, -1, -2, -3, -4, 100, 8_388_607]);
 }
@@ -0,0 +1,59 @@
+// Hound -- A wav encoding and decoding library in Rust
+// Copyright (C) 2015 Ruud van Asseldonk
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// This test lives in its own binary, because it installs a global allocator
+// that counts allocations, which would be disturbed by other tests running
+// concurrently.
+
+extern crate hound;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn read_spec_only_does_not_allocate() {
+    let files = ["testsamples/pcmwaveformat-16bit-44100Hz-mono.wav",
+                 "testsamples/pcmwaveformat-16bit-44100Hz-mono-extra.wav",
+                 "testsamples/waveformatex-16bit-44100Hz-stereo.wav",
+                 "testsamples/waveformatextensible-24bit-192kHz-mono.wav"];
+
+    for fname in &files {
+        let bytes = fs::read(fname).unwrap();
+        let mut reader = &bytes[..];
+
+        let before = ALLOCATIONS.load(Ordering::SeqCst);
+        let spec = hound::WavReader::read_spec_only(&mut reader).unwrap();
+        let after = ALLOCATIONS.load(Ordering::SeqCst);
+
+        assert!(spec.channels > 0);
+        assert_eq!(before, after, "reading the spec of {} allocated", fname);
+    }
+}